@@ -0,0 +1,221 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use clap::Parser;
+use map_reduce_core::config::Config;
+use map_reduce_core::counters::{BYTES_READ, RECORDS_PROCESSED};
+use map_reduce_core::job_kind::JobKind;
+use map_reduce_core::job_metrics::JobMetrics;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+/// Variants compared by default, as `mapreduce run-local --transport` values
+const VARIANTS: [&str; 4] = ["channels", "socket", "rpc", "stream"];
+
+/// Run the same job on the same data through every variant and compare them side by side
+#[derive(Parser)]
+#[command(name = "mapreduce-bench", author, version, about, long_about = None)]
+struct Args {
+    /// Job configuration file, TOML or JSON (defaults to $MAPREDUCE_CONFIG, then config.json)
+    #[arg(long)]
+    config: Option<String>,
+
+    /// Built-in job to run, overriding the configured one
+    #[arg(long)]
+    job: Option<JobKind>,
+
+    /// Variants to run: channels, socket, rpc (unary gRPC) and stream (gRPC streams)
+    #[arg(long, value_delimiter = ',', default_values_t = VARIANTS.map(String::from))]
+    variants: Vec<String>,
+
+    /// Runs per variant, the median of which is reported
+    #[arg(long, default_value_t = 3)]
+    runs: usize,
+
+    /// Directory receiving each run's config, metrics and log
+    #[arg(long, default_value = "bench")]
+    out_dir: PathBuf,
+}
+
+/// Measurements of one run of a variant
+struct RunResult {
+    /// From starting the variant's process to its exit
+    wall_ms: u64,
+    metrics: JobMetrics,
+}
+
+impl RunResult {
+    fn phase_ms(&self, phase: &str) -> u64 {
+        self.metrics
+            .phases
+            .iter()
+            .filter(|metrics| metrics.phase == phase)
+            .map(|metrics| metrics.duration_ms)
+            .sum()
+    }
+
+    fn bytes_sent(&self) -> u64 {
+        self.metrics
+            .phases
+            .iter()
+            .map(|phase| phase.bytes_sent)
+            .sum()
+    }
+
+    /// Input records processed per second of job time
+    fn records_per_sec(&self) -> f64 {
+        self.metrics.counters.get(RECORDS_PROCESSED) as f64 * 1000.0
+            / self.metrics.total_duration_ms.max(1) as f64
+    }
+
+    /// Input MB read per second of job time
+    fn mb_per_sec(&self) -> f64 {
+        self.metrics.counters.get(BYTES_READ) as f64 / 1_000_000.0 * 1000.0
+            / self.metrics.total_duration_ms.max(1) as f64
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Some(variant) = args
+        .variants
+        .iter()
+        .find(|v| !VARIANTS.contains(&v.as_str()))
+    {
+        panic!(
+            "Unknown variant {}, expected one of {}",
+            variant,
+            VARIANTS.join(", ")
+        );
+    }
+
+    let path = Config::resolve_path(args.config);
+    let mut config =
+        Config::load(&path).unwrap_or_else(|e| panic!("Failed to load configuration: {}", e));
+    if let Some(job) = args.job {
+        config.job = job;
+    }
+    // Every run of every variant works on the same generated data, and runs a single job
+    let data_seed = *config.data_seed.get_or_insert_with(rand::random);
+    config.job_queue.clear();
+    config.status_addr = None;
+
+    fs::create_dir_all(&args.out_dir).expect("Failed to create the benchmark directory");
+    let launcher = launcher_path();
+    println!(
+        "=== BENCHMARK: {} ({} runs per variant, data seed {}) ===",
+        config.job, args.runs, data_seed
+    );
+
+    let mut results = Vec::new();
+    for variant in &args.variants {
+        let mut runs = Vec::new();
+        for run in 1..=args.runs.max(1) {
+            print!("{} run {}/{}... ", variant, run, args.runs.max(1));
+            io::stdout().flush().ok();
+            match run_variant(&launcher, &config, variant, run, &args.out_dir) {
+                Ok(result) => {
+                    println!("{}ms", result.wall_ms);
+                    runs.push(result);
+                }
+                Err(e) => println!("failed: {}", e),
+            }
+        }
+        results.push((variant.as_str(), runs));
+    }
+
+    print_report(&mut results);
+}
+
+/// The `mapreduce` launcher next to this binary
+fn launcher_path() -> PathBuf {
+    let exe = env::current_exe().expect("Failed to locate the benchmark binary");
+    exe.with_file_name(format!("mapreduce{}", env::consts::EXE_SUFFIX))
+}
+
+/// Run the job once through a variant, in a process of its own
+fn run_variant(
+    launcher: &Path,
+    config: &Config,
+    variant: &str,
+    run: usize,
+    out_dir: &Path,
+) -> Result<RunResult, String> {
+    let name = format!("{}-{}", variant, run);
+    let metrics_path = out_dir.join(format!("{}-metrics.json", name));
+    let config_path = out_dir.join(format!("{}-config.json", name));
+    let log_path = out_dir.join(format!("{}.log", name));
+
+    let mut config = config.clone();
+    config.metrics_path = Some(metrics_path.display().to_string());
+    let _ = fs::remove_file(&metrics_path);
+    let config_file = File::create(&config_path).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(config_file, &config).map_err(|e| e.to_string())?;
+    let log = File::create(&log_path).map_err(|e| e.to_string())?;
+    let log_err = log.try_clone().map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    let status = Command::new(launcher)
+        .arg("--config")
+        .arg(&config_path)
+        .args(["run-local", "--transport", variant])
+        .stdin(Stdio::null())
+        .stdout(log)
+        .stderr(log_err)
+        .status()
+        .map_err(|e| format!("failed to start {}: {}", launcher.display(), e))?;
+    let wall_ms = start.elapsed().as_millis() as u64;
+    if !status.success() {
+        return Err(format!("{}, see {}", status, log_path.display()));
+    }
+
+    let metrics_file = File::open(&metrics_path)
+        .map_err(|e| format!("no metrics ({}), see {}", e, log_path.display()))?;
+    let metrics = serde_json::from_reader(metrics_file).map_err(|e| e.to_string())?;
+    Ok(RunResult { wall_ms, metrics })
+}
+
+/// Median run of each variant, by job time, side by side
+fn print_report(results: &mut [(&str, Vec<RunResult>)]) {
+    println!(
+        "\n{:<10} {:>9} {:>9} {:>9} {:>9} {:>12} {:>8} {:>14} {:>8}",
+        "variant",
+        "wall ms",
+        "job ms",
+        "map ms",
+        "reduce ms",
+        "records/s",
+        "MB/s",
+        "bytes sent",
+        "retries"
+    );
+    for (variant, runs) in results.iter_mut() {
+        runs.sort_by_key(|run| run.metrics.total_duration_ms);
+        let Some(median) = runs.get(runs.len() / 2) else {
+            println!("{:<10} no successful run", variant);
+            continue;
+        };
+        println!(
+            "{:<10} {:>9} {:>9} {:>9} {:>9} {:>12.0} {:>8.2} {:>14} {:>8}",
+            variant,
+            median.wall_ms,
+            median.metrics.total_duration_ms,
+            median.phase_ms("Map"),
+            median.phase_ms("Reduce"),
+            median.records_per_sec(),
+            median.mb_per_sec(),
+            median.bytes_sent(),
+            median
+                .metrics
+                .phases
+                .iter()
+                .map(|phase| phase.retries)
+                .sum::<u32>()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use clap::{Parser, Subcommand, ValueEnum};
+use map_reduce_core::config::Config;
+use map_reduce_core::job_kind::JobKind;
+use map_reduce_core::logging;
+use map_reduce_process_rpc::cli_args::{CoordinatorArgs, WorkerRole};
+use map_reduce_process_rpc::coordinator::{run_coordinator_role, run_local};
+use map_reduce_process_rpc::worker::{apply_worker_limits, run_worker};
+
+/// Single launcher for every variant and role of the MapReduce job
+#[derive(Parser)]
+#[command(name = "mapreduce", author, version, about, long_about = None)]
+struct Cli {
+    /// Job configuration file, TOML or JSON (defaults to $MAPREDUCE_CONFIG, then config.json)
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the whole job on this machine
+    RunLocal {
+        #[arg(long, value_enum, default_value_t = Transport::Channels)]
+        transport: Transport,
+
+        /// Built-in job to run (word-search, word-count, inverted-index, sort, join), overriding the configured one
+        #[arg(long)]
+        job: Option<JobKind>,
+
+        /// Failure seed logged by a previous run, replaying its injected failures and stragglers
+        #[arg(long)]
+        replay_seed: Option<u64>,
+    },
+    /// Run the coordinator of a multi-process job, with workers started on this machine
+    /// or on another one through --worker-launcher
+    Coordinator(CoordinatorArgs),
+    #[command(flatten)]
+    Worker(WorkerRole),
+}
+
+/// How the coordinator and its workers communicate
+#[derive(Clone, Copy, ValueEnum)]
+enum Transport {
+    /// Tokio tasks and channels (task-channels)
+    Channels,
+    /// OS threads and TCP sockets (thread-socket)
+    Socket,
+    /// Worker processes and unary gRPC calls (process-rpc)
+    #[value(alias = "grpc", alias = "unary")]
+    Rpc,
+    /// Worker processes and bidirectional gRPC streams (process-rpc)
+    #[value(alias = "grpc-stream")]
+    Stream,
+}
+
+fn main() {
+    logging::init();
+    let cli = Cli::parse();
+
+    // Worker limits apply to the runtime's threads only when set before they start
+    if let Command::Worker(role) = &cli.command {
+        apply_worker_limits(role);
+    }
+    tokio::runtime::Runtime::new()
+        .expect("Failed to start the Tokio runtime")
+        .block_on(run(cli));
+}
+
+async fn run(cli: Cli) {
+    let outcome = match cli.command {
+        Command::RunLocal {
+            transport,
+            job,
+            replay_seed,
+        } => {
+            let mut config = load_config(cli.config);
+            if let Some(job) = job {
+                config.job = job;
+            }
+            if replay_seed.is_some() {
+                config.failure_seed = replay_seed;
+            }
+            match transport {
+                Transport::Channels => map_reduce_task_channels::coordinator::run(config).await,
+                Transport::Socket => map_reduce_thread_socket::coordinator::run(config).await,
+                Transport::Rpc => run_local(config, "unary").await,
+                Transport::Stream => run_local(config, "stream").await,
+            }
+        }
+        Command::Coordinator(args) => run_coordinator_role(load_config(cli.config), args).await,
+        Command::Worker(role) => {
+            run_worker(role).await;
+            Ok(())
+        }
+    };
+    if let Err(e) = outcome {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn load_config(path: Option<String>) -> Config {
+    let path = Config::resolve_path(path);
+    Config::load(&path).unwrap_or_else(|e| panic!("Failed to load configuration: {}", e))
+}
@@ -0,0 +1,55 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::reduce_output::{merge_results, KeyResult};
+use crate::result_order::ResultOrder;
+use std::collections::BTreeMap;
+
+/// Intermediate node of the aggregation tree gathering a phase's final results:
+/// reduce tasks hand it their ordered results instead of reporting them to the coordinator,
+/// which collects one merged list from each aggregator
+/// Each task's results are kept once, whatever the number of its attempts that handed them over
+#[derive(Default)]
+pub struct Aggregator {
+    tasks: BTreeMap<usize, Vec<KeyResult>>,
+    duplicates: u32,
+}
+
+impl Aggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the results of a task, unless another attempt of it already handed them over
+    /// Returns false for such a duplicate
+    pub fn add(&mut self, task_index: usize, results: Vec<KeyResult>) -> bool {
+        if self.tasks.contains_key(&task_index) {
+            self.duplicates += 1;
+            return false;
+        }
+        self.tasks.insert(task_index, results);
+        true
+    }
+
+    /// Tasks whose results were kept
+    pub fn tasks(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Results handed over again by further attempts of a task, and dropped
+    pub fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+
+    /// Merge the kept results, each already in `order`, into one list for the node above,
+    /// the first `top_k` if set
+    pub fn merge(self, order: ResultOrder, top_k: Option<usize>) -> Vec<KeyResult> {
+        merge_results(self.tasks.into_values().collect(), order, top_k)
+    }
+}
+
+/// Aggregator the results of a reduce task are handed to, among `aggregators`
+pub fn aggregator_for(task_index: usize, aggregators: usize) -> usize {
+    task_index % aggregators.max(1)
+}
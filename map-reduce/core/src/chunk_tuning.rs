@@ -0,0 +1,223 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::input_source::InputSplit;
+use crate::map_reduce_job::MapReduceJob;
+use crate::phase_metrics::PhaseMetrics;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Probe tasks are this many times smaller than the static chunks (`partition_size` records
+/// or `split_size_bytes`), so every mapper is quickly done timing one
+pub const PROBE_DIVISOR: usize = 4;
+
+/// Piece of the map input: a range of records, or a split of the input files
+trait InputPiece {
+    /// Records or bytes it covers
+    fn size(&self) -> u64;
+
+    /// Extend it with the piece following it, if they are contiguous
+    fn absorb(&mut self, next: &Self) -> bool;
+}
+
+impl InputPiece for Range<usize> {
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+
+    fn absorb(&mut self, next: &Self) -> bool {
+        if self.end != next.start {
+            return false;
+        }
+        self.end = next.end;
+        true
+    }
+}
+
+impl InputPiece for InputSplit {
+    fn size(&self) -> u64 {
+        self.length
+    }
+
+    fn absorb(&mut self, next: &Self) -> bool {
+        if self.path != next.path || self.offset + self.length != next.offset {
+            return false;
+        }
+        self.length += next.length;
+        true
+    }
+}
+
+/// Pieces of the input in order, each flagged if it runs as a probe task
+type Pieces<T> = Vec<(T, bool)>;
+
+enum TunedInput {
+    Records(Arc<[String]>, Pieces<Range<usize>>),
+    Splits(Pieces<InputSplit>),
+}
+
+/// Adaptive chunk sizing of a map phase: the input is cut into small pieces, of which one
+/// probe task per mapper, spread over the input, runs first; the rest of the input is then
+/// re-partitioned into tasks expected to run for the target duration at the speed the probes ran
+/// Both partitionings list the whole input in order, so jobs numbering their input by position
+/// (e.g. the documents of an inverted index) see the same numbers in both
+pub struct ChunkTuner {
+    target_ms: u64,
+    workers: usize,
+    input: TunedInput,
+}
+
+impl ChunkTuner {
+    /// Tune the chunks of generated records, cut into pieces of `probe_size` records
+    pub fn for_records(
+        data: Arc<[String]>,
+        probe_size: usize,
+        target_ms: u64,
+        workers: usize,
+    ) -> Self {
+        let probe_size = probe_size.max(1);
+        let ranges = (0..data.len())
+            .step_by(probe_size)
+            .map(|start| start..(start + probe_size).min(data.len()))
+            .collect();
+        Self {
+            target_ms,
+            workers,
+            input: TunedInput::Records(data, flag_probes(ranges, workers)),
+        }
+    }
+
+    /// Tune the chunks of input splits, already cut to the probe size
+    pub fn for_splits(splits: Vec<InputSplit>, target_ms: u64, workers: usize) -> Self {
+        Self {
+            target_ms,
+            workers,
+            input: TunedInput::Splits(flag_probes(splits, workers)),
+        }
+    }
+
+    /// Assignments of the probe tasks, run before the rest of the input is re-partitioned
+    pub fn probe_assignments<P>(&self, context: P::Context) -> Vec<P::MapAssignment>
+    where
+        P: MapReduceJob<Input = Arc<[String]>>,
+    {
+        match &self.input {
+            TunedInput::Records(data, pieces) => select(pieces.clone(), true, |ranges| {
+                P::create_map_assignments(data.clone(), context, ranges)
+            }),
+            TunedInput::Splits(pieces) => select(pieces.clone(), true, |splits| {
+                P::create_split_assignments(splits, context)
+            }),
+        }
+    }
+
+    /// Assignments covering the rest of the input, sized after the median duration of the probe
+    /// tasks (`probe`, the metrics of the phase that ran them)
+    /// None are left if the probe phase did not run to its end, e.g. stopped early or cancelled
+    pub fn remaining_assignments<P>(
+        self,
+        probe: &PhaseMetrics,
+        context: P::Context,
+    ) -> Vec<P::MapAssignment>
+    where
+        P: MapReduceJob<Input = Arc<[String]>>,
+    {
+        if probe.completed_tasks + probe.dead_letters < probe.tasks {
+            return Vec::new();
+        }
+
+        match self.input {
+            TunedInput::Records(data, pieces) => {
+                let size = tuned_size(&pieces, probe, self.target_ms, self.workers);
+                println!(
+                    "Probe tasks took {} ms (median), re-partitioning the rest of the input into tasks of {} records",
+                    probe.task_p50_ms, size
+                );
+                select(coalesce(pieces, size), false, |ranges| {
+                    P::create_map_assignments(data, context, ranges)
+                })
+            }
+            TunedInput::Splits(pieces) => {
+                let size = tuned_size(&pieces, probe, self.target_ms, self.workers);
+                println!(
+                    "Probe tasks took {} ms (median), re-partitioning the rest of the input into tasks of {} bytes",
+                    probe.task_p50_ms, size
+                );
+                select(coalesce(pieces, size), false, |splits| {
+                    P::create_split_assignments(splits, context)
+                })
+            }
+        }
+    }
+}
+
+/// Flag one piece per worker as a probe, spread evenly over the input
+fn flag_probes<T>(pieces: Vec<T>, workers: usize) -> Pieces<T> {
+    let count = workers.clamp(1, pieces.len().max(1));
+    let len = pieces.len();
+    let mut next_probe = 0;
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(index, piece)| {
+            let probe = next_probe < count && index == next_probe * len / count;
+            if probe {
+                next_probe += 1;
+            }
+            (piece, probe)
+        })
+        .collect()
+}
+
+/// Size of the remaining tasks: as much input as the probes got through in the target duration,
+/// never smaller than a probe, nor so large that a worker is left without a task
+fn tuned_size<T: InputPiece>(
+    pieces: &Pieces<T>,
+    probe: &PhaseMetrics,
+    target_ms: u64,
+    workers: usize,
+) -> u64 {
+    let (probes, probed): (u64, u64) = pieces
+        .iter()
+        .filter(|(_, probe)| *probe)
+        .fold((0, 0), |(count, size), (piece, _)| {
+            (count + 1, size + piece.size())
+        });
+    let probe_size = probed / probes.max(1);
+    let remaining: u64 = pieces
+        .iter()
+        .filter(|(_, probe)| !*probe)
+        .map(|(piece, _)| piece.size())
+        .sum();
+
+    let size = probe_size * target_ms / probe.task_p50_ms.max(1);
+    size.min(remaining.div_ceil(workers.max(1) as u64))
+        .max(probe_size)
+        .max(1)
+}
+
+/// Merge the contiguous pieces between probes into chunks of at most `size`
+fn coalesce<T: InputPiece>(pieces: Pieces<T>, size: u64) -> Pieces<T> {
+    let mut coalesced: Pieces<T> = Vec::new();
+    for (piece, probe) in pieces {
+        if let Some((last, false)) = coalesced.last_mut() {
+            if !probe && last.size() + piece.size() <= size && last.absorb(&piece) {
+                continue;
+            }
+        }
+        coalesced.push((piece, probe));
+    }
+    coalesced
+}
+
+/// Create the assignments of every piece, in input order, and keep the probes or the others
+fn select<T, A>(pieces: Pieces<T>, probes: bool, create: impl FnOnce(Vec<T>) -> Vec<A>) -> Vec<A> {
+    let (pieces, flags): (Vec<T>, Vec<bool>) = pieces.into_iter().unzip();
+    create(pieces)
+        .into_iter()
+        .zip(flags)
+        .filter(|(_, probe)| *probe == probes)
+        .map(|(assignment, _)| assignment)
+        .collect()
+}
@@ -0,0 +1,103 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::job_control::{Cancellation, JobControl};
+use crate::job_kind::JobKind;
+use crate::job_status::{JobStatus, StatusReport};
+use crate::memory_result_sink::MemoryResultSink;
+use crate::reduce_output::KeyResult;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Variant running the jobs submitted to a `MapReduceCluster`, e.g. workers as Tokio tasks
+#[async_trait]
+pub trait ClusterBackend: Send + Sync + 'static {
+    /// Run the jobs of `config` on a worker pool of the variant, reporting to `control`
+    /// Fails if a job's results could not be handed over, e.g. with dead-lettered tasks
+    async fn run(&self, config: Config, control: JobControl) -> Result<(), String>;
+}
+
+/// Handle on a MapReduce variant for programs embedding the framework,
+/// instead of driving it from the variant's `main.rs`
+/// Every submitted job runs on a worker pool of its own, with the cluster's settings
+#[derive(Clone)]
+pub struct MapReduceCluster {
+    config: Config,
+    backend: Arc<dyn ClusterBackend>,
+}
+
+impl MapReduceCluster {
+    /// Cluster running jobs on `backend`, with the worker counts, timeouts, outputs and other
+    /// settings of `config`
+    pub fn new(config: Config, backend: impl ClusterBackend) -> Self {
+        Self {
+            config,
+            backend: Arc::new(backend),
+        }
+    }
+
+    /// Start a `job` reading `input`, a file, directory or object storage prefix,
+    /// returning at once with a handle on it
+    /// The jobs queued by the cluster's config are not run
+    pub fn submit(&self, job: JobKind, input: &str) -> Result<JobHandle, String> {
+        let mut config = self.config.clone();
+        config.job = job;
+        config.input_path = Some(input.to_string());
+        config.job_queue.clear();
+        config.validate().map_err(|problems| problems.join("; "))?;
+
+        let results = MemoryResultSink::new();
+        let control = JobControl::new(&config, Box::new(results.clone()));
+        let status = control.status.clone();
+        let cancellation = control.cancellation.clone();
+        let backend = self.backend.clone();
+        let task = tokio::spawn(async move { backend.run(config, control).await });
+        Ok(JobHandle {
+            results,
+            status,
+            cancellation,
+            task,
+        })
+    }
+}
+
+/// Job submitted to a `MapReduceCluster`
+pub struct JobHandle {
+    results: MemoryResultSink,
+    status: JobStatus,
+    cancellation: Cancellation,
+    task: JoinHandle<Result<(), String>>,
+}
+
+impl JobHandle {
+    /// Live status of the job: stage, phase, task progress, workers and counters
+    pub fn progress(&self) -> StatusReport {
+        self.status.report()
+    }
+
+    /// Stop the job, whose result is then an error
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Wait for the job to finish, returning the final results of each of its final stages
+    pub async fn result(self) -> Result<BTreeMap<String, Vec<KeyResult>>, String> {
+        let outcome = self
+            .task
+            .await
+            .map_err(|e| format!("The job failed: {}", e))?;
+        if self.cancellation.is_cancelled() {
+            return Err("The job was cancelled".to_string());
+        }
+        outcome.map_err(|e| format!("The job failed: {}", e))?;
+        Ok(self.results.take())
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::collections::HashMap;
+
+/// Completions of a phase's tasks, keyed by task index, accepting each task exactly once
+/// Later reports of a completed task, e.g. from a straggler that finished after its task was
+/// retried elsewhere, or from a replaced worker, are suppressed and counted instead; their state
+/// updates are already discarded by `StateStore::commit_task`, keyed by the same task
+#[derive(Debug, Default)]
+pub struct CompletionLog {
+    /// Worker slot whose report of the task was accepted
+    accepted: HashMap<usize, usize>,
+    duplicates: u32,
+}
+
+impl CompletionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept the completion of a task reported by a worker slot
+    /// Returns false if the task was already completed, the report to be suppressed
+    pub fn accept(&mut self, task_index: usize, worker_id: usize) -> bool {
+        if self.accepted.contains_key(&task_index) {
+            return false;
+        }
+        self.accepted.insert(task_index, worker_id);
+        true
+    }
+
+    /// Count a completion report that was not accepted, e.g. of a task its worker no longer runs
+    pub fn suppress(&mut self) {
+        self.duplicates += 1;
+    }
+
+    /// Worker slot whose completion of the task was accepted, None while it is not completed
+    pub fn completed_by(&self, task_index: usize) -> Option<usize> {
+        self.accepted.get(&task_index).copied()
+    }
+
+    /// Tasks completed so far
+    pub fn completed(&self) -> usize {
+        self.accepted.len()
+    }
+
+    /// Completion reports suppressed so far
+    pub fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+}
@@ -2,17 +2,56 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::failure_injection::FaultProfile;
+use crate::job_dag::{JobDag, StageConfig};
+use crate::job_kind::JobKind;
+use crate::scheduling_policy::SchedulingPolicyKind;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Environment variable holding the path of the configuration file
+pub const CONFIG_PATH_ENV: &str = "MAPREDUCE_CONFIG";
+
+/// Environment variable holding the token authenticating a distributed job's RPCs:
+/// handed by the coordinator to its workers, or given to the coordinator to reach them
+pub const JOB_TOKEN_ENV: &str = "MAPREDUCE_JOB_TOKEN";
+
+/// Prefix of the environment variables overriding configuration fields,
+/// e.g. `MAPREDUCE_NUM_MAPPERS=20` overrides `num_mappers`
+pub const CONFIG_ENV_PREFIX: &str = "MAPREDUCE_";
+
+/// Configuration file used when no path is given
+pub const DEFAULT_CONFIG_PATH: &str = "config.json";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    /// Built-in job to run (word-search, word-count, inverted-index, sort, join)
+    #[serde(default)]
+    pub job: JobKind,
     pub num_strings: usize,
     pub max_string_length: usize,
     pub num_target_words: usize,
     pub target_word_length: usize,
     pub partition_size: usize,
     pub keys_per_reducer: usize,
+    /// Number of reduce partitions, independent of the number of reducers, which take
+    /// the next partition as they free up (unset = enough for `keys_per_reducer` keys each)
+    #[serde(default)]
+    pub reduce_partitions: Option<usize>,
+    /// Fraction (0-1] of the map tasks run by the sampling pre-pass of range-partitioned jobs (sort)
+    /// to estimate their partition boundaries, at least one task
+    #[serde(default = "default_sample_fraction")]
+    pub sample_fraction: f64,
+    /// Skew mitigation: keys that received more than this many values in the map phase are split
+    /// into sub-keys reduced by several reducers, whose results are merged back (unset = no splitting)
+    /// Counts the values held in memory, so it cannot be combined with `state_memory_limit`
+    #[serde(default)]
+    pub hot_key_threshold: Option<usize>,
     pub num_mappers: usize,
     pub num_reducers: usize,
     /// Probability (0-100) that a mapper fails during execution
@@ -39,68 +78,606 @@ pub struct Config {
     /// Maximum delay in milliseconds for a reducer straggler
     #[serde(default = "default_straggler_delay")]
     pub reducer_straggler_delay_ms: u64,
+    /// Fault profile of the mappers, replacing the mapper failure and straggler settings above
+    #[serde(default)]
+    pub mapper_faults: Option<FaultProfile>,
+    /// Fault profile of the reducers, replacing the reducer failure and straggler settings above
+    #[serde(default)]
+    pub reducer_faults: Option<FaultProfile>,
+    /// Seed of the generated input strings and target words, so runs can be repeated
+    /// on the same data (random if unset)
+    #[serde(default)]
+    pub data_seed: Option<u64>,
+    /// Seed of the injected failures and stragglers, logged by the coordinator
+    /// so a failure schedule can be replayed (random if unset)
+    #[serde(default)]
+    pub failure_seed: Option<u64>,
+    /// Retry running tasks slower than this many times the phase's median task, at least 1
+    /// (0 = disabled)
+    #[serde(default)]
+    pub straggler_factor: f64,
+    /// Fraction (0-1) of map tasks whose results are verified by re-executing them
+    /// and comparing against the results they wrote (0 = disabled)
+    #[serde(default)]
+    pub verify_fraction: f64,
+    /// Order in which pending tasks are assigned (fifo, largest-first, retries-first)
+    #[serde(default)]
+    pub scheduling_policy: SchedulingPolicyKind,
+    /// Send each reduce task back to the reducer slot that last completed it, e.g. in the
+    /// previous stage or queued job, unless that reducer was replaced since
+    #[serde(default)]
+    pub partition_affinity: bool,
+    /// Failure domain (host tag) of each worker slot, assigned round-robin, so retries prefer
+    /// workers in another domain than the one a task failed in; empty treats every slot as its own
+    #[serde(default)]
+    pub failure_domains: Vec<String>,
+    /// Number of times a failed task is retried before it is dead-lettered, failing its job
+    #[serde(default = "default_max_task_retries")]
+    pub max_task_retries: u32,
+    /// Number of times a task that timed out or straggled is retried before it is
+    /// dead-lettered, counted apart from `max_task_retries`
+    #[serde(default = "default_max_task_timeouts")]
+    pub max_task_timeouts: u32,
+    /// Path to a text file or directory used as map input instead of generated strings,
+    /// or an object storage prefix (`s3://bucket/prefix`, `local://bucket/prefix`)
+    #[serde(default)]
+    pub input_path: Option<String>,
+    /// Smaller input of map-side joins, a file, directory or object storage prefix like `input_path`,
+    /// loaded by the coordinator and broadcast to every mapper
+    #[serde(default)]
+    pub join_input: Option<String>,
+    /// Named side inputs of the job, files or object storage prefixes like `input_path`,
+    /// read by the coordinator and broadcast to every worker with the job context of each phase
+    #[serde(default)]
+    pub side_inputs: BTreeMap<String, String>,
+    /// Target size in bytes of each input split when reading from `input_path`
+    #[serde(default = "default_split_size")]
+    pub split_size_bytes: usize,
+    /// Adaptive chunk sizing: one small probe task per mapper runs first, then the rest of the
+    /// map input is re-partitioned into tasks expected to run this long, instead of chunks of
+    /// `partition_size` records or `split_size_bytes` (unset = static chunks)
+    #[serde(default)]
+    pub target_task_ms: Option<u64>,
+    /// Destinations for the final results (defaults to stdout)
+    #[serde(default = "default_outputs")]
+    pub outputs: Vec<OutputConfig>,
+    /// Write only the first results in the job's result order, e.g. the most frequent words
+    /// Each reducer reports at most that many, set for the worker pool (unset = all results)
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    /// Print partial per-key aggregates as map tasks complete
+    #[serde(default)]
+    pub stream_partial_results: bool,
+    /// Stop the map phase early once any key's partial aggregate reaches this value
+    #[serde(default)]
+    pub early_stop_threshold: Option<i32>,
+    /// Path of a JSON file receiving the job metrics report
+    #[serde(default)]
+    pub metrics_path: Option<String>,
+    /// Address (`host:port`) of an HTTP endpoint serving the coordinator's status as JSON
+    #[serde(default)]
+    pub status_addr: Option<String>,
+    /// Pipeline of stages, each fed by the results of the stages it depends on
+    /// (empty = a single stage)
+    #[serde(default)]
+    pub stages: Vec<StageConfig>,
+    /// Directory holding the intermediate results passed between stages
+    #[serde(default = "default_work_dir")]
+    pub work_dir: String,
+    /// Number of intermediate values kept in memory before they are spilled
+    /// to sorted runs under `<work_dir>/spill` (unset = never spill)
+    #[serde(default)]
+    pub state_memory_limit: Option<usize>,
+    /// Directory of a sled database persisting the state servers' state (process-rpc)
+    #[serde(default)]
+    pub state_path: Option<String>,
+    /// Config files of further jobs of the same kind, run after this one on the same workers
+    #[serde(default)]
+    pub job_queue: Vec<String>,
+    /// Compress assignments with zstd before sending them to socket and gRPC workers
+    #[serde(default)]
+    pub compress_work: bool,
+}
+
+/// Destination for the final results of a job
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "format", rename_all = "lowercase")]
+pub enum OutputConfig {
+    Stdout,
+    Jsonl {
+        path: String,
+    },
+    Csv {
+        path: String,
+    },
+    /// JSONL object uploaded to `s3://bucket/key` or `local://bucket/key`
+    Object {
+        uri: String,
+    },
 }
 
 fn default_straggler_delay() -> u64 {
     1000
 }
 
+fn default_sample_fraction() -> f64 {
+    0.05
+}
+
+fn default_max_task_retries() -> u32 {
+    3
+}
+
+fn default_max_task_timeouts() -> u32 {
+    10
+}
+
+fn default_split_size() -> usize {
+    1024 * 1024
+}
+
+fn default_outputs() -> Vec<OutputConfig> {
+    vec![OutputConfig::Stdout]
+}
+
+fn default_work_dir() -> String {
+    "mapreduce-work".to_string()
+}
+
 impl Config {
+    /// Path of the configuration file: the given argument, else `$MAPREDUCE_CONFIG`,
+    /// else `config.json`
+    pub fn resolve_path(arg: Option<String>) -> String {
+        arg.or_else(|| std::env::var(CONFIG_PATH_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string())
+    }
+
+    /// Load a TOML (`.toml`) or JSON file, apply `MAPREDUCE_<FIELD>` environment overrides
+    /// and validate the result
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let contents = fs::read_to_string(path)?;
-        let config: Config = serde_json::from_str(&contents)?;
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let is_toml = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension == "toml");
+        let mut fields: Value = if is_toml {
+            toml::from_str(&contents).map_err(|e| format!("Invalid TOML in {}: {}", path, e))?
+        } else {
+            serde_json::from_str(&contents)
+                .map_err(|e| format!("Invalid JSON in {}: {}", path, e))?
+        };
+
+        let Value::Object(map) = &mut fields else {
+            return Err(format!("{} must contain a table of settings", path).into());
+        };
+        let overrides = apply_env_overrides(map);
+
+        let config: Config = serde_path_to_error::deserialize(fields)
+            .map_err(|e| describe_setting_error(&e, path, &overrides))?;
+
+        config.validate().map_err(|problems| {
+            format!(
+                "Invalid configuration in {}:\n  - {}",
+                path,
+                problems.join("\n  - ")
+            )
+        })?;
         Ok(config)
     }
 
+    /// Make the relative input and work paths absolute against the current directory,
+    /// e.g. for workers running in directories of their own; object storage URIs are kept
+    pub fn absolutize_paths(&mut self) -> io::Result<()> {
+        let absolute = |path: &mut String| -> io::Result<()> {
+            if !path.contains("://") {
+                *path = std::path::absolute(&*path)?.to_string_lossy().into_owned();
+            }
+            Ok(())
+        };
+        for path in self
+            .input_path
+            .iter_mut()
+            .chain(self.join_input.iter_mut())
+            .chain(self.side_inputs.values_mut())
+            .chain(self.stages.iter_mut().filter_map(|s| s.input_path.as_mut()))
+            .chain(self.state_path.iter_mut())
+        {
+            absolute(path)?;
+        }
+        absolute(&mut self.work_dir)
+    }
+
+    /// Number of reduce partitions for `keys` keys: `reduce_partitions` when it is set,
+    /// else enough for `keys_per_reducer` keys each, never more than there are keys
+    pub fn num_partitions(&self, keys: usize) -> usize {
+        match self.reduce_partitions {
+            Some(partitions) => partitions.min(keys),
+            None => keys.div_ceil(self.keys_per_reducer),
+        }
+    }
+
+    /// Check settings that parse but cannot run, returning every problem found
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+        let mut at_least_one = |name: &str, value: usize| {
+            if value == 0 {
+                problems.push(format!("{} must be at least 1", name));
+            }
+        };
+        at_least_one("num_mappers", self.num_mappers);
+        at_least_one("num_reducers", self.num_reducers);
+        at_least_one("keys_per_reducer", self.keys_per_reducer);
+        if let Some(partitions) = self.reduce_partitions {
+            at_least_one("reduce_partitions", partitions);
+        }
+        if let Some(top_k) = self.top_k {
+            at_least_one("top_k", top_k);
+        }
+        if let Some(threshold) = self.hot_key_threshold {
+            at_least_one("hot_key_threshold", threshold);
+        }
+        if let Some(target_ms) = self.target_task_ms {
+            at_least_one("target_task_ms", target_ms as usize);
+        }
+        if self.input_path.is_some() || self.join_input.is_some() || !self.side_inputs.is_empty() {
+            at_least_one("split_size_bytes", self.split_size_bytes);
+        }
+        if self.input_path.is_none() {
+            at_least_one("partition_size", self.partition_size);
+            at_least_one("max_string_length", self.max_string_length);
+        }
+
+        if self.job.joins_input() && self.join_input.is_none() {
+            problems.push(format!("The {} job needs a join_input", self.job));
+        }
+        if self
+            .failure_domains
+            .iter()
+            .any(|domain| domain.trim().is_empty())
+        {
+            problems.push("failure_domains cannot hold empty tags".to_string());
+        }
+        if self.hot_key_threshold.is_some() && !self.job.splits_hot_keys() {
+            problems.push(format!("The {} job cannot split hot keys", self.job));
+        }
+
+        for (name, probability) in [
+            (
+                "mapper_failure_probability",
+                self.mapper_failure_probability,
+            ),
+            (
+                "reducer_failure_probability",
+                self.reducer_failure_probability,
+            ),
+            (
+                "mapper_straggler_probability",
+                self.mapper_straggler_probability,
+            ),
+            (
+                "reducer_straggler_probability",
+                self.reducer_straggler_probability,
+            ),
+        ] {
+            if probability > 100 {
+                problems.push(format!(
+                    "{} is a percentage (0-100), got {}",
+                    name, probability
+                ));
+            }
+        }
+
+        for (name, profile, flat_settings_set) in [
+            (
+                "mapper_faults",
+                &self.mapper_faults,
+                self.mapper_failure_probability > 0 || self.mapper_straggler_probability > 0,
+            ),
+            (
+                "reducer_faults",
+                &self.reducer_faults,
+                self.reducer_failure_probability > 0 || self.reducer_straggler_probability > 0,
+            ),
+        ] {
+            let Some(profile) = profile else {
+                continue;
+            };
+            if flat_settings_set {
+                let role = name.trim_end_matches("_faults");
+                problems.push(format!(
+                    "{} replaces {}_failure_probability and {}_straggler_probability, set one or the other",
+                    name, role, role
+                ));
+            }
+            problems.extend(profile.problems(name));
+        }
+
+        if let Some(addr) = &self.status_addr {
+            if addr
+                .rsplit_once(':')
+                .is_none_or(|(_, port)| port.parse::<u16>().is_err())
+            {
+                problems.push(format!(
+                    "status_addr must be host:port, e.g. 127.0.0.1:8080, got {}",
+                    addr
+                ));
+            }
+        }
+
+        if self.hot_key_threshold.is_some() && self.state_memory_limit.is_some() {
+            problems.push(
+                "hot_key_threshold and state_memory_limit cannot be combined, spilled values are not counted"
+                    .to_string(),
+            );
+        }
+        if self.state_path.is_some() && self.state_memory_limit.is_some() {
+            problems.push(
+                "state_path and state_memory_limit cannot be combined, persisted state is not spilled"
+                    .to_string(),
+            );
+        }
+
+        // Below 1 even tasks of median duration would count as stragglers and be retried
+        if !(self.straggler_factor == 0.0 || self.straggler_factor >= 1.0) {
+            problems.push(format!(
+                "straggler_factor must be 0 (disabled) or at least 1, got {}",
+                self.straggler_factor
+            ));
+        }
+
+        if !(self.sample_fraction > 0.0 && self.sample_fraction <= 1.0) {
+            problems.push(format!(
+                "sample_fraction is a positive fraction (0-1], got {}",
+                self.sample_fraction
+            ));
+        }
+
+        if self.target_task_ms.is_some() && self.verify_fraction > 0.0 {
+            problems.push(
+                "target_task_ms and verify_fraction cannot be combined, the verified sample would be re-partitioned"
+                    .to_string(),
+            );
+        }
+
+        if !(0.0..=1.0).contains(&self.verify_fraction) {
+            problems.push(format!(
+                "verify_fraction is a fraction (0-1), got {}",
+                self.verify_fraction
+            ));
+        }
+
+        if let Err(e) = JobDag::from_config(self) {
+            problems.push(e.to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
     pub fn print_summary(&self) {
         println!("Configuration:");
-        println!("  - Strings: {}", self.num_strings);
-        println!("  - Max string length: {}", self.max_string_length);
-        println!("  - Target words: {}", self.num_target_words);
-        println!("  - Target word length: {}", self.target_word_length);
+        println!("  - Job: {}", self.job);
+        match &self.input_path {
+            Some(path) => {
+                println!("  - Input: {}", path);
+                println!("  - Split size: {} bytes", self.split_size_bytes);
+            }
+            None => {
+                println!("  - Strings: {}", self.num_strings);
+                println!("  - Max string length: {}", self.max_string_length);
+            }
+        }
+        if let Some(path) = &self.join_input {
+            println!("  - Join input: {}", path);
+        }
+        for (name, path) in &self.side_inputs {
+            println!("  - Side input '{}': {}", name, path);
+        }
+        if self.job.uses_targets() {
+            println!("  - Target words: {}", self.num_target_words);
+            println!("  - Target word length: {}", self.target_word_length);
+        }
+        if let Some(seed) = self.data_seed {
+            println!("  - Data seed: {}", seed);
+        }
         println!("  - Partition size: {}", self.partition_size);
-        println!("  - Keys per reducer: {}", self.keys_per_reducer);
+        if let Some(target_ms) = self.target_task_ms {
+            println!(
+                "  - Adaptive chunk sizing: map tasks of about {} ms after the probe tasks",
+                target_ms
+            );
+        }
+        match self.reduce_partitions {
+            Some(partitions) => println!("  - Reduce partitions: {}", partitions),
+            None => println!("  - Keys per reducer: {}", self.keys_per_reducer),
+        }
+        if self.job.samples_input() {
+            println!(
+                "  - Sampling pre-pass: {}% of map tasks",
+                self.sample_fraction * 100.0
+            );
+        }
+        if let Some(threshold) = self.hot_key_threshold {
+            println!("  - Hot keys: split above {} values", threshold);
+        }
         println!("  - Mappers: {}", self.num_mappers);
         println!("  - Reducers: {}", self.num_reducers);
+        if self.scheduling_policy != SchedulingPolicyKind::Fifo {
+            println!("  - Scheduling: {}", self.scheduling_policy);
+        }
+        if self.partition_affinity {
+            println!("  - Partition affinity: reduce tasks return to their last reducer");
+        }
+        if !self.failure_domains.is_empty() {
+            println!(
+                "  - Failure domains: {} (retries prefer another domain)",
+                self.failure_domains.join(", ")
+            );
+        }
+
+        if let Some(limit) = self.state_memory_limit {
+            println!(
+                "  - State memory limit: {} values (spilling to {}/spill)",
+                limit, self.work_dir
+            );
+        }
+
+        if let Some(path) = &self.state_path {
+            println!("  - Persisted state: {}", path);
+        }
+
+        if let Some(top_k) = self.top_k {
+            println!("  - Top-K: first {} results", top_k);
+        }
+        if self.stream_partial_results {
+            println!("  - Streaming partial results: enabled");
+        }
+        if let Some(threshold) = self.early_stop_threshold {
+            println!("  - Early stop threshold: {}", threshold);
+        }
 
-        if self.mapper_failure_probability > 0
-            || self.reducer_failure_probability > 0
-            || self.mapper_straggler_probability > 0
-            || self.reducer_straggler_probability > 0
+        if !self.job_queue.is_empty() {
+            println!("  - Queued jobs: {}", self.job_queue.join(", "));
+        }
+        if let Some(addr) = &self.status_addr {
+            println!("  - Status endpoint: http://{}/status", addr);
+        }
+        if self.compress_work {
+            println!("  - Work compression: zstd");
+        }
+
+        if !self.stages.is_empty() {
+            println!("\nStages (work dir: {}):", self.work_dir);
+            for stage in &self.stages {
+                if stage.depends_on.is_empty() {
+                    println!("  - {}", stage.name);
+                } else {
+                    println!("  - {} <- {}", stage.name, stage.depends_on.join(", "));
+                }
+            }
+        }
+
+        let mapper_faults = FaultProfile::mappers(self);
+        let reducer_faults = FaultProfile::reducers(self);
+        if mapper_faults.injects_faults()
+            || reducer_faults.injects_faults()
             || self.mapper_timeout_ms > 0
             || self.reducer_timeout_ms > 0
+            || self.straggler_factor > 0.0
+            || self.verify_fraction > 0.0
         {
             println!("\nFault Tolerance:");
-            if self.mapper_failure_probability > 0 {
-                println!(
-                    "  - Mapper failure probability: {}%",
-                    self.mapper_failure_probability
-                );
+            mapper_faults.print_summary("Mapper");
+            reducer_faults.print_summary("Reducer");
+            if self.mapper_timeout_ms > 0 {
+                println!("  - Mapper timeout: {}ms", self.mapper_timeout_ms);
             }
-            if self.reducer_failure_probability > 0 {
-                println!(
-                    "  - Reducer failure probability: {}%",
-                    self.reducer_failure_probability
-                );
+            if self.reducer_timeout_ms > 0 {
+                println!("  - Reducer timeout: {}ms", self.reducer_timeout_ms);
             }
-            if self.mapper_straggler_probability > 0 {
+            if self.straggler_factor > 0.0 {
                 println!(
-                    "  - Mapper straggler probability: {}% (delay up to {}ms)",
-                    self.mapper_straggler_probability, self.mapper_straggler_delay_ms
+                    "  - Straggler detection: tasks over {}x the median",
+                    self.straggler_factor
                 );
             }
-            if self.reducer_straggler_probability > 0 {
+            if self.verify_fraction > 0.0 {
                 println!(
-                    "  - Reducer straggler probability: {}% (delay up to {}ms)",
-                    self.reducer_straggler_probability, self.reducer_straggler_delay_ms
+                    "  - Output verification: {}% of map tasks re-executed",
+                    self.verify_fraction * 100.0
                 );
             }
-            if self.mapper_timeout_ms > 0 {
-                println!("  - Mapper timeout: {}ms", self.mapper_timeout_ms);
-            }
-            if self.reducer_timeout_ms > 0 {
-                println!("  - Reducer timeout: {}ms", self.reducer_timeout_ms);
-            }
+            println!("  - Max task retries: {}", self.max_task_retries);
+            println!("  - Max task timeouts: {}", self.max_task_timeouts);
+        }
+    }
+}
+
+/// Override fields with `MAPREDUCE_<FIELD>` environment variables, returning the overridden fields
+/// Values are parsed as JSON when possible (numbers, booleans, arrays), otherwise taken as strings
+/// Only variables naming a field of `Config` apply, so other `MAPREDUCE_` variables (the log
+/// filter, the job token, ...) and variables that are not valid UTF-8 are left alone
+fn apply_env_overrides(fields: &mut Map<String, Value>) -> Vec<String> {
+    let known = config_fields();
+    let mut overridden = Vec::new();
+    for (name, raw) in std::env::vars_os() {
+        let (Ok(name), Ok(raw)) = (name.into_string(), raw.into_string()) else {
+            continue;
+        };
+        let Some(field) = name.strip_prefix(CONFIG_ENV_PREFIX) else {
+            continue;
+        };
+        let field = field.to_lowercase();
+        if !known.contains(&field.as_str()) {
+            continue;
         }
+        let value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+        fields.insert(field.clone(), value);
+        overridden.push(field);
+    }
+    overridden
+}
+
+/// Names of the fields of `Config`, as its derived `Deserialize` declares them
+fn config_fields() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = Config::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Deserializer recording the field names a struct asks for, then bailing out
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> serde::Deserializer<'de> for FieldNames<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("only field names are collected"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        self.deserialize_any(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+/// Name the setting that failed to deserialize and where it came from (file or environment)
+fn describe_setting_error(
+    error: &serde_path_to_error::Error<serde_json::Error>,
+    path: &str,
+    overrides: &[String],
+) -> String {
+    let setting = error.path().to_string();
+    let inner = error.inner().to_string();
+    let overridden = overrides.iter().find(|field| {
+        setting.split(['.', '[']).next() == Some(field.as_str())
+            || inner.starts_with(&format!("unknown field `{}`", field))
+    });
+    let source = match overridden {
+        Some(field) => format!(
+            "environment variable {}{}",
+            CONFIG_ENV_PREFIX,
+            field.to_uppercase()
+        ),
+        None => path.to_string(),
+    };
+    if setting == "." {
+        format!("Invalid configuration in {}: {}", source, inner)
+    } else {
+        format!("Invalid setting '{}' in {}: {}", setting, source, inner)
     }
 }
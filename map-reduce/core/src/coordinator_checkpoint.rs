@@ -0,0 +1,83 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::job_id::JobId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Phase of a stage
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JobPhase {
+    #[default]
+    Map,
+    Reduce,
+}
+
+/// Orchestrator state of a running job, as needed by a standby coordinator to take over
+/// The standby reruns the interrupted phase from its start: the tasks assigned or completed
+/// since are not tracked, as the state is restored to its snapshot and a task commits at most once
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CoordinatorCheckpoint {
+    /// Id of the job, kept by the standby so its log lines and task keys match the primary's
+    pub job_id: JobId,
+    /// Position of the job in the worker pool's queue, the jobs before it being done
+    pub job_index: usize,
+    /// Seed of the generated map input and target words, so the standby regenerates the same
+    /// ones; input read from `input_path` is read again from the standby's config
+    pub data_seed: u64,
+    /// Index of the stage in progress, in execution order
+    pub stage: usize,
+    pub phase: JobPhase,
+    /// State store contents when the phase started
+    pub state: HashMap<String, Vec<i32>>,
+    pub finished: bool,
+}
+
+/// Change to the orchestrator state, shipped in order from the primary coordinator to the standby
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum CheckpointUpdate {
+    /// Replace the whole state, e.g. after the standby missed updates
+    Snapshot(CoordinatorCheckpoint),
+    JobStarted {
+        job_id: JobId,
+        job_index: usize,
+        data_seed: u64,
+    },
+    PhaseStarted {
+        stage: usize,
+        phase: JobPhase,
+        state: HashMap<String, Vec<i32>>,
+    },
+    JobFinished,
+}
+
+impl CoordinatorCheckpoint {
+    pub fn apply(&mut self, update: CheckpointUpdate) {
+        match update {
+            CheckpointUpdate::Snapshot(checkpoint) => *self = checkpoint,
+            CheckpointUpdate::JobStarted {
+                job_id,
+                job_index,
+                data_seed,
+            } => {
+                *self = Self {
+                    job_id,
+                    job_index,
+                    data_seed,
+                    ..Self::default()
+                };
+            }
+            CheckpointUpdate::PhaseStarted {
+                stage,
+                phase,
+                state,
+            } => {
+                self.stage = stage;
+                self.phase = phase;
+                self.state = state;
+            }
+            CheckpointUpdate::JobFinished => self.finished = true,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::hot_keys::{split_hot_keys, HotKey};
+use crate::in_memory_state_store::{LocalStateAccess, SharedStateMap};
+use crate::job_id::JobId;
+use crate::state_store::StateStore;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::io;
+
+/// Coordinator's view of the shared state, as the job loop of `JobRunner` drives it
+/// between phases: reset for each stage, read to partition the reduce keys, checkpointed
+#[async_trait]
+pub trait CoordinatorState: Send + Sync {
+    /// Start a job on a clear state, discarding the commits of other jobs' tasks from now on
+    fn begin_job(&self, job: JobId);
+
+    /// Remove all keys and committed tasks
+    fn clear(&self);
+
+    /// Initialize keys with empty vectors
+    async fn initialize(&self, keys: Vec<String>);
+
+    /// Copy of all keys and values
+    fn snapshot(&self) -> HashMap<String, Vec<i32>>;
+
+    /// Replace all keys and values with a previously taken snapshot
+    fn restore(&self, snapshot: HashMap<String, Vec<i32>>);
+
+    /// Run `f` on the keys and values of the whole state
+    fn with_map<R>(&self, f: impl FnOnce(&HashMap<String, Vec<i32>>) -> R) -> R;
+
+    /// In-memory maps the state is kept in, e.g. to stream partial results
+    fn maps(&self) -> Vec<SharedStateMap>;
+
+    /// Spread the values of each hot key over its sub-keys, see `hot_keys::split_hot_keys`
+    async fn split_hot_keys(&self, hot_keys: &[HotKey]);
+
+    /// Make a persisted state durable, e.g. at the end of a phase
+    async fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CoordinatorState for LocalStateAccess {
+    fn begin_job(&self, job: JobId) {
+        LocalStateAccess::begin_job(self, job);
+    }
+
+    fn clear(&self) {
+        LocalStateAccess::clear(self);
+    }
+
+    async fn initialize(&self, keys: Vec<String>) {
+        StateStore::initialize(self, keys).await;
+    }
+
+    fn snapshot(&self) -> HashMap<String, Vec<i32>> {
+        LocalStateAccess::snapshot(self)
+    }
+
+    fn restore(&self, snapshot: HashMap<String, Vec<i32>>) {
+        LocalStateAccess::restore(self, snapshot);
+    }
+
+    fn with_map<R>(&self, f: impl FnOnce(&HashMap<String, Vec<i32>>) -> R) -> R {
+        f(&self.get_map().lock().unwrap())
+    }
+
+    fn maps(&self) -> Vec<SharedStateMap> {
+        vec![self.get_map()]
+    }
+
+    async fn split_hot_keys(&self, hot_keys: &[HotKey]) {
+        split_hot_keys(self, hot_keys).await;
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::reduce_output::KeyResult;
+use crate::result_value::ResultValue;
+use crate::state_store::{Aggregate, StateStore};
+use crate::task_key::TaskKey;
+use async_trait::async_trait;
+
+/// State of a task injected with a wrong result: every value it writes is off by one
+/// Reads are passed through, so the task runs as usual and only its output is wrong
+#[derive(Clone)]
+pub struct CorruptedState<S> {
+    state: S,
+}
+
+impl<S: StateStore> CorruptedState<S> {
+    pub fn new(state: S) -> Self {
+        Self { state }
+    }
+}
+
+fn corrupt(updates: Vec<(String, i32)>) -> Vec<(String, i32)> {
+    updates
+        .into_iter()
+        .map(|(key, value)| (key, value.wrapping_add(1)))
+        .collect()
+}
+
+/// Results emitted by a task injected with a wrong result, off by one like the values it writes
+pub fn corrupt_results(results: Vec<KeyResult>) -> Vec<KeyResult> {
+    results
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                ResultValue::Scalar(value) => ResultValue::Scalar(value.wrapping_add(1)),
+                ResultValue::List(values) => {
+                    ResultValue::List(values.into_iter().map(|v| v.wrapping_add(1)).collect())
+                }
+            };
+            (key, value)
+        })
+        .collect()
+}
+
+#[async_trait]
+impl<S: StateStore> StateStore for CorruptedState<S> {
+    async fn initialize(&self, keys: Vec<String>) {
+        self.state.initialize(keys).await;
+    }
+
+    async fn update(&self, key: String, value: i32) {
+        self.state.update(key, value.wrapping_add(1)).await;
+    }
+
+    async fn update_many(&self, updates: Vec<(String, i32)>) {
+        self.state.update_many(corrupt(updates)).await;
+    }
+
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>) {
+        self.state.commit_task(task, corrupt(updates)).await;
+    }
+
+    async fn replace(&self, key: String, values: Vec<i32>) {
+        let values = values
+            .into_iter()
+            .map(|value| value.wrapping_add(1))
+            .collect();
+        self.state.replace(key, values).await;
+    }
+
+    async fn get(&self, key: &str) -> Vec<i32> {
+        self.state.get(key).await
+    }
+
+    async fn merge_reduce(&self, keys: &[String], aggregate: Aggregate) -> Vec<i32> {
+        self.state.merge_reduce(keys, aggregate).await
+    }
+}
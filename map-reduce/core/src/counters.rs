@@ -0,0 +1,92 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// Input records a map task processed
+pub const RECORDS_PROCESSED: &str = "records_processed";
+/// Input lines a map task skipped because they were not valid UTF-8
+pub const MALFORMED_RECORDS_SKIPPED: &str = "malformed_records_skipped";
+/// Input bytes a map task read
+pub const BYTES_READ: &str = "bytes_read";
+/// Keys a reduce task reduced
+pub const KEYS_REDUCED: &str = "keys_reduced";
+
+/// Named counters, summed over the tasks that reported them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Counters(BTreeMap<String, u64>);
+
+impl Counters {
+    pub fn increment(&mut self, name: &str, by: u64) {
+        *self.0.entry(name.to_string()).or_default() += by;
+    }
+
+    /// Add the counters of another task
+    pub fn merge(&mut self, other: &Counters) {
+        for (name, value) in other.iter() {
+            self.increment(name, value);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> u64 {
+        self.0.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Counters in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+impl FromIterator<(String, u64)> for Counters {
+    fn from_iter<I: IntoIterator<Item = (String, u64)>>(counters: I) -> Self {
+        let mut merged = Self::default();
+        for (name, value) in counters {
+            merged.increment(&name, value);
+        }
+        merged
+    }
+}
+
+impl IntoIterator for Counters {
+    type Item = (String, u64);
+    type IntoIter = std::collections::btree_map::IntoIter<String, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Counters a running task increments, reported with its completion
+#[derive(Clone, Default)]
+pub struct TaskCounters {
+    counters: Arc<Mutex<Counters>>,
+}
+
+impl TaskCounters {
+    pub fn increment(&self, name: &str, by: u64) {
+        self.counters.lock().unwrap().increment(name, by);
+    }
+
+    /// Count in-memory input records, and their bytes
+    pub fn records_read(&self, records: &[String]) {
+        let bytes = records.iter().map(|record| record.len() as u64).sum();
+        let mut counters = self.counters.lock().unwrap();
+        counters.increment(RECORDS_PROCESSED, records.len() as u64);
+        counters.increment(BYTES_READ, bytes);
+    }
+
+    /// Counters incremented so far, resetting them
+    pub fn take(&self) -> Counters {
+        mem::take(&mut *self.counters.lock().unwrap())
+    }
+}
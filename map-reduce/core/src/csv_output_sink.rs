@@ -0,0 +1,43 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::output_sink::OutputSink;
+use crate::result_value::ResultValue;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Writes results as a `key,value` CSV file with a header row
+/// List values are written as space-separated numbers
+pub struct CsvOutputSink {
+    path: PathBuf,
+}
+
+impl CsvOutputSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// Quote a field if it contains a separator, quote, or line break
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl OutputSink for CsvOutputSink {
+    fn write(&mut self, results: &[(String, ResultValue)]) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        writeln!(writer, "key,value")?;
+        for (key, value) in results {
+            writeln!(writer, "{},{}", escape_field(key), value)?;
+        }
+        writer.flush()?;
+        println!("Wrote {} results to {}", results.len(), self.path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+/// Range of the shared input data handed to a map task
+/// In-process workers share the data instead of copying their chunk, serialization
+/// writes the strings of the range only, so remote workers receive just their chunk
+#[derive(Clone, Default)]
+pub struct DataChunk {
+    data: Arc<[String]>,
+    range: Range<usize>,
+}
+
+impl DataChunk {
+    pub fn new(data: Arc<[String]>, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= data.len(),
+            "Chunk {:?} out of the data's {} strings",
+            range,
+            data.len()
+        );
+        Self { data, range }
+    }
+
+    /// Ranges of consecutive chunks of `chunk_size` strings covering `len` strings
+    pub fn ranges(len: usize, chunk_size: usize) -> Vec<Range<usize>> {
+        let chunk_size = chunk_size.max(1);
+        (0..len)
+            .step_by(chunk_size)
+            .map(|start| start..(start + chunk_size).min(len))
+            .collect()
+    }
+}
+
+impl Deref for DataChunk {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.data[self.range.clone()]
+    }
+}
+
+/// Chunk over data of its own, e.g. a remote worker's copy
+impl FromIterator<String> for DataChunk {
+    fn from_iter<I: IntoIterator<Item = String>>(strings: I) -> Self {
+        let data: Arc<[String]> = strings.into_iter().collect();
+        let range = 0..data.len();
+        Self { data, range }
+    }
+}
+
+impl Serialize for DataChunk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for DataChunk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
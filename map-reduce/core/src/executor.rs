@@ -2,23 +2,62 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::completion_log::CompletionLog;
+use crate::failure_domains::FailureDomains;
+use crate::job_context::JobContextOf;
+use crate::job_id::JobId;
+use crate::job_status::JobStatus;
+use crate::logging::phase_run_id;
+use crate::partial_results::StreamControl;
+use crate::phase_metrics::PhaseMetrics;
+use crate::reduce_output::KeyResult;
+use crate::scheduling_policy::{FifoPolicy, SchedulingPolicy, TaskSize};
 use crate::shutdown_signal::ShutdownSignal;
+use crate::task_durations::TaskDurations;
+use crate::task_key::TaskKey;
 use crate::worker::Worker;
 use crate::worker_factory::WorkerFactory;
-use crate::worker_synchronization::WorkerSynchronization;
-use std::cmp::max;
+use crate::worker_synchronization::{WorkerStatus, WorkerSynchronization};
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::mem;
 use std::time::{Duration, Instant};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
-/// Assignment tracking information
+/// Longest wait for a worker to accept a task when task timeouts are disabled
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Assignment tracking information, keyed by task index
 #[derive(Clone)]
-struct AssignmentInfo<A> {
-    assignment: A,
+struct AssignmentInfo {
+    worker_id: usize,
     start_time: Instant,
 }
 
+/// A task that exhausted its retry budget and was given up on
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub task_index: usize,
+    pub attempts: u32,
+}
+
+/// Why a task is retried
+enum RetryCause {
+    /// The task failed, its worker died or never got it: counts against the retry budget
+    Failed(String),
+    /// The task timed out or straggled, e.g. on an overloaded machine: counts against the
+    /// separate timeout budget, so a slow worker does not use up the retries of failures
+    Slow(String),
+}
+
+/// Retries of a task in the current phase, by cause
+#[derive(Debug, Clone, Copy, Default)]
+struct TaskAttempts {
+    failures: u32,
+    timeouts: u32,
+}
+
 /// Phase executor with fault tolerance and straggler detection
 /// Generic over worker type, synchronization signaling, and worker factory
 pub struct Executor<W, CS, F>
@@ -29,7 +68,39 @@ where
 {
     worker_factory: F,
     timeout: Option<Duration>,
-    _phantom: PhantomData<(W, CS)>,
+    /// Running tasks slower than this many times the median are retried (0 = disabled)
+    straggler_factor: f64,
+    durations: TaskDurations,
+    max_retries: u32,
+    /// Times a task is retried after timing out or straggling before it is dead-lettered
+    max_timeouts: u32,
+    dead_letters: Vec<DeadLetter>,
+    /// Results reported by the completed tasks of the last executed phase
+    results: Vec<Vec<KeyResult>>,
+    /// Accepted completions of the current phase's tasks, each task accepted once
+    completions: CompletionLog,
+    metrics: PhaseMetrics,
+    /// Bytes already sent through each worker slot when the phase started, on the wire and raw
+    bytes_baseline: Vec<(u64, u64)>,
+    status: Option<JobStatus>,
+    /// Job context every worker receives when it registers for a phase
+    context: JobContextOf<W::Assignment>,
+    /// Workers whose last assignment could not be delivered
+    undelivered: Vec<usize>,
+    /// Order in which pending tasks are assigned
+    scheduling: Box<dyn SchedulingPolicy>,
+    /// Failure domain of each worker slot, retries preferring idle workers outside the failed one
+    failure_domains: FailureDomains,
+    /// Worker slot each task of the next phase is kept off, as (task index, worker slot)
+    exclusions: Vec<(usize, usize)>,
+    /// Job whose phases are run, keying its tasks along with the phase run
+    job: JobId,
+    /// Random id of the current phase run, keying its tasks
+    phase: u64,
+    /// Signaling of the last phase and the number of workers it serves, kept warm so the next
+    /// phase re-tasks the same workers without binding new listeners
+    signaling: Option<(usize, CS)>,
+    _phantom: PhantomData<W>,
 }
 
 impl<W, CS, F> Executor<W, CS, F>
@@ -38,7 +109,7 @@ where
     CS: WorkerSynchronization,
     F: WorkerFactory<W>,
 {
-    pub fn new(worker_factory: F, timeout_ms: u64) -> Self {
+    pub fn new(worker_factory: F, timeout_ms: u64, max_retries: u32) -> Self {
         Self {
             worker_factory,
             timeout: if timeout_ms > 0 {
@@ -46,9 +117,120 @@ where
             } else {
                 None
             },
+            straggler_factor: 0.0,
+            durations: TaskDurations::new(),
+            max_retries,
+            max_timeouts: max_retries,
+            dead_letters: Vec::new(),
+            results: Vec::new(),
+            completions: CompletionLog::new(),
+            metrics: PhaseMetrics::default(),
+            bytes_baseline: Vec::new(),
+            status: None,
+            context: JobContextOf::<W::Assignment>::default(),
+            undelivered: Vec::new(),
+            scheduling: Box::new(FifoPolicy::default()),
+            failure_domains: FailureDomains::default(),
+            exclusions: Vec::new(),
+            job: JobId::default(),
+            phase: 0,
+            signaling: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Tasks that exceeded their retry budget during the last executed phase
+    pub fn dead_letters(&self) -> &[DeadLetter] {
+        &self.dead_letters
+    }
+
+    /// Results reported by the completed tasks of the last executed phase, each in the job's
+    /// result order, e.g. to merge into the final results
+    pub fn take_results(&mut self) -> Vec<Vec<KeyResult>> {
+        mem::take(&mut self.results)
+    }
+
+    /// Random id of the last executed phase run, keying its tasks
+    pub fn phase_run(&self) -> u64 {
+        self.phase
+    }
+
+    /// Worker slot whose completion of a task of the last executed phase was accepted
+    pub fn completed_by(&self, task_index: usize) -> Option<usize> {
+        self.completions.completed_by(task_index)
+    }
+
+    /// Keep tasks of the next phase off worker slots, as (task index, worker slot), e.g. to
+    /// re-execute a task on another worker than the one that ran it
+    /// A spare worker takes part in the phase, so every task has another one to run on
+    pub fn exclude_workers(&mut self, exclusions: Vec<(usize, usize)>) {
+        self.exclusions = exclusions;
+    }
+
+    /// Metrics of the last executed phase
+    pub fn metrics(&self) -> &PhaseMetrics {
+        &self.metrics
+    }
+
+    /// Dead-letter tasks that timed out or straggled more than `max_timeouts` times,
+    /// as many as the failure retries by default
+    pub fn set_max_timeouts(&mut self, max_timeouts: u32) {
+        self.max_timeouts = max_timeouts;
+    }
+
+    /// Retry running tasks slower than `factor` times the median completed task of the phase
+    pub fn set_straggler_factor(&mut self, factor: f64) {
+        self.straggler_factor = factor;
+    }
+
+    /// Assign the pending tasks of every following phase in the order of `policy` (FIFO by default)
+    pub fn set_scheduling_policy(&mut self, policy: Box<dyn SchedulingPolicy>) {
+        self.scheduling = policy;
+    }
+
+    /// Failure domains of the worker slots, e.g. the hosts they run on
+    pub fn set_failure_domains(&mut self, domains: FailureDomains) {
+        self.failure_domains = domains;
+    }
+
+    /// Durations of the tasks completed during the last executed phase
+    pub fn durations(&self) -> &TaskDurations {
+        &self.durations
+    }
+
+    /// Run the following phases as part of `job`, e.g. the next job of a resident worker pool
+    pub fn set_job(&mut self, job: JobId) {
+        self.job = job;
+    }
+
+    /// Broadcast `context` to the workers of every following phase, e.g. a stage's target words
+    pub fn set_context(&mut self, context: JobContextOf<W::Assignment>) {
+        self.context = context;
+    }
+
+    /// Report the progress of every following phase, and its workers and failures, to `status`
+    pub fn set_status(&mut self, status: JobStatus) {
+        self.status = Some(status);
+    }
+
+    /// Print the dead-letter list, if any task was given up on
+    pub fn print_dead_letters(&self, phase: &str) {
+        if self.dead_letters.is_empty() {
+            return;
+        }
+
+        eprintln!(
+            "\n☠️  {} phase: {} task(s) exceeded the retry budget:",
+            phase,
+            self.dead_letters.len()
+        );
+        for dead_letter in &self.dead_letters {
+            eprintln!(
+                "  - Task {} failed after {} attempts",
+                dead_letter.task_index, dead_letter.attempts
+            );
+        }
+    }
 }
 
 impl<W, CS, F> Executor<W, CS, F>
@@ -59,114 +241,260 @@ where
     F: WorkerFactory<W>,
 {
     pub async fn execute<SD>(
+        &mut self,
+        workers: Vec<W>,
+        assignments: Vec<W::Assignment>,
+        shutdown_signal: &SD,
+    ) -> Vec<W>
+    where
+        SD: ShutdownSignal + Sync,
+        W::Assignment: Clone + TaskSize,
+    {
+        self.execute_with_progress(workers, assignments, shutdown_signal, |_, _| {
+            StreamControl::Continue
+        })
+        .await
+    }
+
+    /// Execute the phase, reporting (completed, total) tasks after each completion
+    /// The phase stops distributing work as soon as the callback returns `StreamControl::Stop`
+    pub async fn execute_with_progress<SD, P>(
+        &mut self,
+        workers: Vec<W>,
+        assignments: Vec<W::Assignment>,
+        shutdown_signal: &SD,
+        on_progress: P,
+    ) -> Vec<W>
+    where
+        SD: ShutdownSignal + Sync,
+        W::Assignment: Clone + TaskSize,
+        P: FnMut(usize, usize) -> StreamControl,
+    {
+        // The run id keys the phase's tasks, and ties the workers' log lines to the phase's
+        self.phase = rand::random();
+        let span = info_span!(
+            "phase",
+            job_id = %self.job,
+            phase = W::PHASE,
+            run = %phase_run_id(self.phase)
+        );
+        self.run_phase(workers, assignments, shutdown_signal, on_progress)
+            .instrument(span)
+            .await
+    }
+
+    async fn run_phase<SD, P>(
         &mut self,
         mut workers: Vec<W>,
         assignments: Vec<W::Assignment>,
         shutdown_signal: &SD,
+        mut on_progress: P,
     ) -> Vec<W>
     where
         SD: ShutdownSignal + Sync,
-        W::Assignment: Clone,
+        W::Assignment: Clone + TaskSize,
+        P: FnMut(usize, usize) -> StreamControl,
     {
+        self.dead_letters.clear();
+        self.results.clear();
+        self.completions = CompletionLog::new();
+        let mut exclusions = mem::take(&mut self.exclusions);
+        self.undelivered.clear();
+        self.durations = TaskDurations::new();
+        let phase_start = Instant::now();
+        self.metrics = PhaseMetrics {
+            tasks: assignments.len(),
+            tasks_per_worker: vec![0; workers.len()],
+            ..PhaseMetrics::default()
+        };
+        self.bytes_baseline = workers
+            .iter()
+            .map(|w| (w.bytes_sent(), w.raw_bytes_sent()))
+            .collect();
+
         if assignments.is_empty() {
             return workers;
         }
 
-        // Setup signaling
-        let mut signaling = CS::setup(workers.len());
+        // A cancelled job starts no further phases, its workers may already be gone
+        if shutdown_signal.is_cancelled() {
+            info!("Shutdown requested, skipping phase");
+            return workers;
+        }
+        if let Some(status) = &self.status {
+            status.phase_started(W::PHASE, assignments.len(), workers.len());
+        }
+
+        // Switch the warm signaling of the last phase over, or set it up for a new worker set
+        let mut signaling = match self.signaling.take() {
+            Some((num_workers, mut signaling)) if num_workers == workers.len() => {
+                debug!(num_workers, "Re-tasking the workers of the last phase");
+                signaling.switch_phase();
+                signaling
+            }
+            _ => CS::setup(workers.len()),
+        };
 
-        let mut assignment_index = 0;
-        let mut active_workers = 0;
-        let mut worker_assignments: HashMap<usize, AssignmentInfo<W::Assignment>> = HashMap::new();
+        // Only as many workers as there are tasks take part in the phase, and a spare one for
+        // the tasks kept off some worker; a single worker runs every task regardless
+        if workers.len() < 2 {
+            exclusions.clear();
+        }
+        let spare_slots = usize::from(!exclusions.is_empty());
+        let active_slots = min(workers.len(), assignments.len() + spare_slots);
+        let sizes: Vec<u64> = assignments.iter().map(TaskSize::task_size).collect();
+        self.scheduling.start(&sizes);
+        for (task_index, worker_id) in exclusions {
+            self.scheduling.exclude(task_index, worker_id);
+        }
+        let mut attempts = vec![TaskAttempts::default(); assignments.len()];
+        let mut running: HashMap<usize, AssignmentInfo> = HashMap::new();
+        let mut completed = 0;
 
         // Distribute initial assignments
-        for (worker_id, worker) in workers.iter().enumerate().take(assignments.len()) {
+        for (worker_id, worker) in workers.iter().enumerate().take(active_slots) {
             // Initialize worker with synchronization sender
             let status_sender = signaling.get_status_sender(worker_id);
-            worker.initialize(status_sender.clone().into());
+            worker.initialize(status_sender.into(), self.context.clone());
 
             // Wait for worker to be ready (Startup Phase)
-            if !signaling.wait_for_worker_ready(worker_id).await {
-                eprintln!(
-                    "⚠️  Worker {} failed to start (handshake timeout)!",
-                    worker_id
-                );
-                // We continue, but don't assign work. The straggler/failure logic below needs to handle this?
-                // Actually, if we don't assign work, active_workers won't increment.
-                // We should probably try to respawn immediately or just fail the job?
-                // For this implementation, let's assume we proceed and let the loop handle it (it won't).
-                // Better: Panic or return error?
-                // Let's just print for now, as the user asked for the mechanism.
+            if !wait_until_ready(&signaling, worker, worker_id).await {
+                warn!(worker_id, "⚠️  Worker failed to start (handshake timeout)!");
             }
 
-            let assignment = assignments[assignment_index].clone();
-            worker.send_work(assignment.clone(), status_sender.into());
-            worker_assignments.insert(
+            assign_next(
+                worker,
                 worker_id,
-                AssignmentInfo {
-                    assignment,
-                    start_time: Instant::now(),
-                },
-            );
-            assignment_index += 1;
-            active_workers += 1;
+                &signaling,
+                self.job,
+                self.phase,
+                &assignments,
+                self.scheduling.as_mut(),
+                &mut running,
+                &self.status,
+                self.timeout,
+                &mut self.undelivered,
+                &mut self.metrics,
+            )
+            .await;
         }
 
         // Process completions and reassignments
-        while active_workers > 0 {
+        while !running.is_empty() {
             // Check shutdown signal
             if shutdown_signal.is_cancelled() {
-                println!("Distributor received shutdown signal, stopping work distribution");
+                info!("Distributor received shutdown signal, stopping work distribution");
                 break;
             }
 
-            // Check for stragglers if timeout is configured
-            if let Some(timeout_duration) = self.timeout {
-                let mut stragglers = Vec::new();
-                for (worker_id, info) in &worker_assignments {
-                    if info.start_time.elapsed() > timeout_duration {
-                        stragglers.push(*worker_id);
-                    }
+            // Keep the live status showing how far behind each worker's queue is
+            if let Some(status) = &self.status {
+                status.queue_depths(workers.iter().map(Worker::queue_depth));
+            }
+
+            // Assignments that never reached their worker are handled like worker failures
+            for worker_id in mem::take(&mut self.undelivered) {
+                if task_of(&running, worker_id).is_some() {
+                    self.metrics.failures += 1;
+                    self.retry_task(
+                        worker_id,
+                        &mut workers,
+                        &mut signaling,
+                        &assignments,
+                        &mut attempts,
+                        &mut running,
+                        active_slots,
+                        RetryCause::Failed("assignment not delivered".to_string()),
+                    )
+                    .await;
                 }
+            }
 
-                // Handle stragglers
-                for worker_id in stragglers {
-                    if let Some(info) = worker_assignments.remove(&worker_id) {
-                        eprintln!(
-                            "⏱️  Worker {} is a straggler (timeout exceeded)! Respawning and reassigning work...",
-                            worker_id
-                        );
+            // Workers that died are replaced right away, without waiting for a timeout
+            let crashed: Vec<(usize, String)> = running
+                .values()
+                .filter_map(|info| {
+                    workers[info.worker_id]
+                        .exit_reason()
+                        .map(|reason| (info.worker_id, reason))
+                })
+                .collect();
 
-                        // Replace worker
-                        let failed_worker = mem::replace(
-                            &mut workers[worker_id],
-                            self.worker_factory.create_worker(worker_id).await,
-                        );
-                        drop(failed_worker);
+            for (worker_id, reason) in crashed {
+                self.metrics.crashes += 1;
+                warn!(
+                    worker_id,
+                    %reason,
+                    "💀 Worker died! Respawning and retrying its task..."
+                );
+                self.retry_task(
+                    worker_id,
+                    &mut workers,
+                    &mut signaling,
+                    &assignments,
+                    &mut attempts,
+                    &mut running,
+                    active_slots,
+                    RetryCause::Failed(format!("worker died: {}", reason)),
+                )
+                .await;
+            }
 
-                        // Reset signaling for the worker
-                        let completion_sender = signaling.reset_worker(worker_id).await;
+            // Check for stragglers if timeout is configured
+            if let Some(timeout_duration) = self.timeout {
+                let stragglers: Vec<usize> = running
+                    .values()
+                    .filter(|info| info.start_time.elapsed() > timeout_duration)
+                    .map(|info| info.worker_id)
+                    .collect();
 
-                        // Initialize new worker
-                        workers[worker_id].initialize(completion_sender.clone().into());
+                for worker_id in stragglers {
+                    self.metrics.timeouts += 1;
+                    warn!(
+                        worker_id,
+                        "⏱️  Worker is a straggler (timeout exceeded)! Respawning and retrying its task..."
+                    );
+                    self.retry_task(
+                        worker_id,
+                        &mut workers,
+                        &mut signaling,
+                        &assignments,
+                        &mut attempts,
+                        &mut running,
+                        active_slots,
+                        RetryCause::Slow("timed out".to_string()),
+                    )
+                    .await;
+                }
+            }
 
-                        // Wait for new worker to be ready
-                        if !signaling.wait_for_worker_ready(worker_id).await {
-                            eprintln!("⚠️  Respawned Worker {} failed to start!", worker_id);
-                        }
+            // Check for tasks much slower than the ones completed so far
+            let threshold = self.durations.straggler_threshold(self.straggler_factor);
+            let stragglers: Vec<(usize, Duration)> = running
+                .values()
+                .map(|info| (info.worker_id, info.start_time.elapsed()))
+                .filter(|(_, elapsed)| threshold.is_some_and(|threshold| *elapsed > threshold))
+                .collect();
 
-                        // Reassign work
-                        workers[worker_id]
-                            .send_work(info.assignment.clone(), completion_sender.into());
-                        worker_assignments.insert(
-                            worker_id,
-                            AssignmentInfo {
-                                assignment: info.assignment,
-                                start_time: Instant::now(),
-                            },
-                        );
-                    }
-                }
+            for (worker_id, elapsed) in stragglers {
+                self.metrics.stragglers += 1;
+                warn!(
+                    worker_id,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    median_ms = self.durations.median().unwrap_or_default().as_millis() as u64,
+                    "🐢 Worker is a straggler! Respawning and retrying its task..."
+                );
+                self.retry_task(
+                    worker_id,
+                    &mut workers,
+                    &mut signaling,
+                    &assignments,
+                    &mut attempts,
+                    &mut running,
+                    active_slots,
+                    RetryCause::Slow(format!("straggler after {}ms", elapsed.as_millis())),
+                )
+                .await;
             }
 
             // Wait for completion
@@ -181,77 +509,88 @@ where
             let wait_duration = max(wait_duration, Duration::from_millis(10));
 
             match tokio::time::timeout(wait_duration, signaling.wait_next()).await {
-                Ok(completion_result) => {
-                    if let Some(result) = completion_result {
-                        match result {
-                            Ok(worker_id) => {
-                                // Worker completed successfully
-                                worker_assignments.remove(&worker_id);
-                                active_workers -= 1;
-
-                                // Assign next assignment if available
-                                if assignment_index < assignments.len() {
-                                    let assignment = assignments[assignment_index].clone();
-                                    let completion = signaling.get_status_sender(worker_id);
-                                    workers[worker_id]
-                                        .send_work(assignment.clone(), completion.into());
-                                    worker_assignments.insert(
-                                        worker_id,
-                                        AssignmentInfo {
-                                            assignment,
-                                            start_time: Instant::now(),
-                                        },
-                                    );
-                                    assignment_index += 1;
-                                    active_workers += 1;
-                                }
-                            }
-                            Err(worker_id) => {
-                                // Worker failed - respawn and reassign
-                                eprintln!(
-                                    "⚠️  Worker {} failed! Respawning and reassigning work...",
-                                    worker_id
-                                );
-
-                                if let Some(info) = worker_assignments.get(&worker_id).cloned() {
-                                    // Replace worker
-                                    let failed_worker = mem::replace(
-                                        &mut workers[worker_id],
-                                        self.worker_factory.create_worker(worker_id).await,
-                                    );
-                                    drop(failed_worker);
-
-                                    // Reset signaling for the worker
-                                    let completion_token = signaling.reset_worker(worker_id).await;
-
-                                    // Initialize new worker
-                                    workers[worker_id].initialize(completion_token.clone().into());
-
-                                    // Wait for new worker to be ready
-                                    if !signaling.wait_for_worker_ready(worker_id).await {
-                                        eprintln!(
-                                            "⚠️  Respawned Worker {} failed to start!",
-                                            worker_id
-                                        );
-                                    }
-
-                                    // Reassign work
-                                    workers[worker_id].send_work(
-                                        info.assignment.clone(),
-                                        completion_token.into(),
-                                    );
-                                    worker_assignments.insert(
-                                        worker_id,
-                                        AssignmentInfo {
-                                            assignment: info.assignment,
-                                            start_time: Instant::now(),
-                                        },
-                                    );
-                                }
-                            }
+                Ok(Some(WorkerStatus::Completed {
+                    worker_id,
+                    task_id,
+                    counters,
+                    results,
+                })) => {
+                    // Worker completed successfully, hand it the next pending task
+                    // Only the accepted report of a task is counted, late duplicates are suppressed
+                    let accepted = reported_task(&running, worker_id, task_id)
+                        .filter(|task_index| self.completions.accept(*task_index, worker_id));
+                    if let Some(task_index) = accepted {
+                        let info = running.remove(&task_index).unwrap();
+                        debug!(worker_id, chunk = task_index, "Task completed");
+                        self.scheduling.task_completed(task_index, worker_id);
+                        self.metrics.counters.merge(&counters);
+                        if !results.is_empty() {
+                            self.results.push(results);
+                        }
+                        self.durations.record(info.start_time.elapsed());
+                        if let Some(status) = &self.status {
+                            status.task_completed(worker_id, &counters);
+                        }
+                        completed += 1;
+                        self.metrics.completed_tasks = completed;
+                        self.metrics.tasks_per_worker[worker_id] += 1;
+                        if on_progress(completed, assignments.len()) == StreamControl::Stop {
+                            info!(
+                                "Stopping phase early after {}/{} tasks",
+                                completed,
+                                assignments.len()
+                            );
+                            break;
                         }
+                    } else {
+                        self.suppress_duplicate(worker_id, task_id);
                     }
+                    if task_of(&running, worker_id).is_some() {
+                        continue;
+                    }
+                    assign_next(
+                        &workers[worker_id],
+                        worker_id,
+                        &signaling,
+                        self.job,
+                        self.phase,
+                        &assignments,
+                        self.scheduling.as_mut(),
+                        &mut running,
+                        &self.status,
+                        self.timeout,
+                        &mut self.undelivered,
+                        &mut self.metrics,
+                    )
+                    .await;
                 }
+                Ok(Some(WorkerStatus::Failed { worker_id, task_id })) => {
+                    // Worker failed - respawn it and retry its task
+                    if let Some(task_index) = reported_task(&running, worker_id, task_id) {
+                        self.metrics.failures += 1;
+                        warn!(
+                            worker_id,
+                            chunk = task_index,
+                            "⚠️  Worker failed! Respawning and retrying its task..."
+                        );
+                        self.retry_task(
+                            worker_id,
+                            &mut workers,
+                            &mut signaling,
+                            &assignments,
+                            &mut attempts,
+                            &mut running,
+                            active_slots,
+                            RetryCause::Failed("task failed".to_string()),
+                        )
+                        .await;
+                    }
+                }
+                Ok(Some(WorkerStatus::Superseded { worker_id, task_id })) => {
+                    // A replaced worker finished anyway, its task is retried or already completed
+                    self.suppress_duplicate(worker_id, task_id);
+                }
+                Ok(None) => {}
                 Err(_) => {
                     // Timeout occurred - loop will check for stragglers and shutdown
                     continue;
@@ -259,6 +598,270 @@ where
             }
         }
 
+        if let Some(status) = &self.status {
+            status.phase_finished();
+        }
+        self.metrics.duration_ms = phase_start.elapsed().as_millis() as u64;
+        self.metrics.dead_letters = self.dead_letters.len();
+        self.metrics.duplicates_suppressed = self.completions.duplicates();
+        let percentile_ms = |percent| {
+            self.durations
+                .percentile(percent)
+                .unwrap_or_default()
+                .as_millis() as u64
+        };
+        self.metrics.task_p50_ms = percentile_ms(50);
+        self.metrics.task_p90_ms = percentile_ms(90);
+        self.metrics.task_p99_ms = percentile_ms(99);
+        self.metrics.task_max_ms = self.durations.longest().as_millis() as u64;
+        for (worker, (bytes, raw_bytes)) in workers.iter().zip(&self.bytes_baseline) {
+            self.metrics.bytes_sent += worker.bytes_sent().saturating_sub(*bytes);
+            self.metrics.raw_bytes_sent += worker.raw_bytes_sent().saturating_sub(*raw_bytes);
+        }
+        self.signaling = Some((workers.len(), signaling));
+
         workers
     }
+
+    /// Count a completion report that was not accepted, with the worker whose report was
+    fn suppress_duplicate(&mut self, worker_id: usize, task_id: Option<usize>) {
+        self.completions.suppress();
+        let completed_by = task_id.and_then(|task_index| self.completions.completed_by(task_index));
+        debug!(
+            worker_id,
+            chunk = ?task_id,
+            completed_by = ?completed_by,
+            "Suppressed a duplicate completion"
+        );
+    }
+
+    /// Replace a failed or straggling worker and requeue its task,
+    /// unless its failures exhausted the task's retry budget
+    #[allow(clippy::too_many_arguments)]
+    async fn retry_task(
+        &mut self,
+        worker_id: usize,
+        workers: &mut [W],
+        signaling: &mut CS,
+        assignments: &[W::Assignment],
+        attempts: &mut [TaskAttempts],
+        running: &mut HashMap<usize, AssignmentInfo>,
+        active_slots: usize,
+        cause: RetryCause,
+    ) where
+        W::Assignment: Clone + TaskSize,
+    {
+        let Some(task_index) = task_of(running, worker_id) else {
+            return;
+        };
+        running.remove(&task_index);
+
+        let task_attempts = &mut attempts[task_index];
+        let reason = match cause {
+            RetryCause::Failed(reason) => {
+                task_attempts.failures += 1;
+                reason
+            }
+            RetryCause::Slow(reason) => {
+                task_attempts.timeouts += 1;
+                reason
+            }
+        };
+        let dead_lettered =
+            task_attempts.failures > self.max_retries || task_attempts.timeouts > self.max_timeouts;
+        let total_attempts = task_attempts.failures + task_attempts.timeouts;
+        if let Some(status) = &self.status {
+            status.task_failed(worker_id, task_index, reason, dead_lettered);
+        }
+        if dead_lettered {
+            error!(
+                chunk = task_index,
+                attempts = total_attempts,
+                "☠️  Task exceeded its retry budget, moving it to the dead-letter list"
+            );
+            self.dead_letters.push(DeadLetter {
+                task_index,
+                attempts: total_attempts,
+            });
+        } else {
+            self.metrics.retries += 1;
+            self.scheduling.task_failed(task_index, worker_id);
+            self.scheduling.retry(task_index);
+        }
+
+        // Replace worker
+        let failed_worker = mem::replace(
+            &mut workers[worker_id],
+            self.worker_factory.create_worker(worker_id).await,
+        );
+        let (bytes, raw_bytes) = self.bytes_baseline[worker_id];
+        self.metrics.bytes_sent += failed_worker.bytes_sent().saturating_sub(bytes);
+        self.metrics.raw_bytes_sent += failed_worker.raw_bytes_sent().saturating_sub(raw_bytes);
+        self.bytes_baseline[worker_id] = (0, 0);
+        drop(failed_worker);
+        self.scheduling.worker_replaced(worker_id);
+
+        // Reset signaling for the worker
+        let status_sender = signaling.reset_worker(worker_id).await;
+
+        // Initialize new worker
+        workers[worker_id].initialize(status_sender.into(), self.context.clone());
+
+        // Wait for new worker to be ready
+        if !wait_until_ready(signaling, &workers[worker_id], worker_id).await {
+            warn!(worker_id, "⚠️  Respawned worker failed to start!");
+        }
+        if let Some(status) = &self.status {
+            status.worker_respawned(worker_id);
+        }
+
+        // Prefer idle workers outside the failed worker's domain for the retry, then the other
+        // idle workers, then its replacement
+        let failed_domain = self.failure_domains.domain_of(worker_id);
+        let mut idle_workers: Vec<usize> = (0..active_slots)
+            .filter(|id| *id != worker_id && task_of(running, *id).is_none())
+            .collect();
+        idle_workers.sort_by_key(|id| self.failure_domains.domain_of(*id) == failed_domain);
+        idle_workers.push(worker_id);
+        for idle_id in idle_workers {
+            if !assign_next(
+                &workers[idle_id],
+                idle_id,
+                signaling,
+                self.job,
+                self.phase,
+                assignments,
+                self.scheduling.as_mut(),
+                running,
+                &self.status,
+                self.timeout,
+                &mut self.undelivered,
+                &mut self.metrics,
+            )
+            .await
+            {
+                break;
+            }
+        }
+    }
+}
+
+/// Task the worker is running, if any
+fn task_of(running: &HashMap<usize, AssignmentInfo>, worker_id: usize) -> Option<usize> {
+    running
+        .iter()
+        .find(|(_, info)| info.worker_id == worker_id)
+        .map(|(task_index, _)| *task_index)
+}
+
+/// Task a worker's report is about, None if the worker no longer runs it,
+/// e.g. a late report of a task that was already retried on another worker
+/// Reports not tagged with a task are about the worker's current task
+fn reported_task(
+    running: &HashMap<usize, AssignmentInfo>,
+    worker_id: usize,
+    task_id: Option<usize>,
+) -> Option<usize> {
+    let current = task_of(running, worker_id)?;
+    match task_id {
+        Some(task_id) if task_id != current => {
+            info!(
+                worker_id,
+                chunk = task_id,
+                running = current,
+                "Ignoring late report of a task the worker no longer runs"
+            );
+            None
+        }
+        _ => Some(current),
+    }
+}
+
+/// Wait for a worker's handshake, giving up if the worker dies first
+async fn wait_until_ready<W, CS>(signaling: &CS, worker: &W, worker_id: usize) -> bool
+where
+    W: Worker,
+    CS: WorkerSynchronization,
+{
+    let ready = signaling.wait_for_worker_ready(worker_id);
+    tokio::pin!(ready);
+    loop {
+        tokio::select! {
+            ready = &mut ready => return ready,
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                if let Some(reason) = worker.exit_reason() {
+                    warn!(worker_id, %reason, "💀 Worker died before becoming ready!");
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+/// Send the next pending task, in the scheduling policy's order, to a worker
+/// Returns false if there was nothing left to assign
+/// A worker whose assignment could not be delivered is added to `undelivered`, as is one that
+/// did not accept it within the task timeout: a full queue frees a slot once the worker's oldest
+/// task completes, which a task running for longer is retried for anyway
+#[allow(clippy::too_many_arguments)]
+async fn assign_next<W, CS>(
+    worker: &W,
+    worker_id: usize,
+    signaling: &CS,
+    job: JobId,
+    phase: u64,
+    assignments: &[W::Assignment],
+    scheduling: &mut dyn SchedulingPolicy,
+    running: &mut HashMap<usize, AssignmentInfo>,
+    status: &Option<JobStatus>,
+    task_timeout: Option<Duration>,
+    undelivered: &mut Vec<usize>,
+    metrics: &mut PhaseMetrics,
+) -> bool
+where
+    W: Worker,
+    CS: WorkerSynchronization,
+    W::Completion: From<CS::StatusSender>,
+    W::Assignment: Clone + TaskSize,
+{
+    let Some(task_index) = scheduling.next_task_for(worker_id) else {
+        return false;
+    };
+
+    // Waits while the worker's queue is full
+    let status_sender =
+        signaling.get_task_status_sender(worker_id, TaskKey::new(job, phase, task_index));
+    let delivery = worker.send_work(assignments[task_index].clone(), status_sender.into());
+    let delivery_timeout = task_timeout.unwrap_or(DELIVERY_TIMEOUT);
+    let delivered = match tokio::time::timeout(delivery_timeout, delivery).await {
+        Ok(delivered) => delivered,
+        Err(_) => Err(format!(
+            "the worker did not accept it within {:?}",
+            delivery_timeout
+        )),
+    };
+    match delivered {
+        Ok(()) => metrics.max_queue_depth = max(metrics.max_queue_depth, worker.queue_depth()),
+        Err(e) => {
+            warn!(
+                worker_id,
+                chunk = task_index,
+                error = %e,
+                "⚠️  Task could not be delivered! Respawning and retrying it..."
+            );
+            undelivered.push(worker_id);
+        }
+    }
+    debug!(worker_id, chunk = task_index, "Task assigned");
+    if let Some(status) = status {
+        status.task_assigned(worker_id, task_index, worker.queue_depth());
+    }
+    running.insert(
+        task_index,
+        AssignmentInfo {
+            worker_id,
+            start_time: Instant::now(),
+        },
+    );
+    true
 }
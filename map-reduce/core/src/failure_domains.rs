@@ -0,0 +1,33 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::collections::HashSet;
+
+/// Failure domain of each worker slot, e.g. the host or rack its workers run on, so retries
+/// can be steered away from where a task failed
+/// Slots take the configured tags round-robin; without tags every slot is a domain of its own,
+/// since the replacement of a failed worker runs on the same host and port
+#[derive(Debug, Clone, Default)]
+pub struct FailureDomains {
+    tags: Vec<String>,
+}
+
+impl FailureDomains {
+    pub fn new(tags: Vec<String>) -> Self {
+        Self { tags }
+    }
+
+    /// Failure domain of a worker slot
+    pub fn domain_of(&self, worker_id: usize) -> String {
+        match self.tags.len() {
+            0 => format!("slot-{}", worker_id),
+            n => self.tags[worker_id % n].clone(),
+        }
+    }
+
+    /// Whether the slots span several domains, otherwise there is nowhere else to retry
+    pub fn spans_several(&self) -> bool {
+        self.tags.is_empty() || self.tags.iter().collect::<HashSet<_>>().len() > 1
+    }
+}
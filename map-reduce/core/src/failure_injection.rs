@@ -0,0 +1,380 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::kill_switch::{ForcedFault, KillSwitch};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Faults injected into the tasks of one worker role, set with `mapper_faults` / `reducer_faults`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultProfile {
+    /// Probability (0-100) that a task fails
+    #[serde(default)]
+    pub failure_probability: u32,
+    /// How a task drawn to fail fails
+    #[serde(default)]
+    pub failure_mode: FailureMode,
+    /// Probability (0-100) that a task is delayed
+    #[serde(default)]
+    pub straggler_probability: u32,
+    /// Delay of a delayed task
+    #[serde(default)]
+    pub straggler_delay: DelayDistribution,
+    /// Probabilities replacing the ones above from some time into the job on
+    #[serde(default)]
+    pub schedule: Vec<ScheduledRates>,
+}
+
+/// How a task drawn to fail fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailureMode {
+    /// The worker reports the task as failed
+    #[default]
+    Error,
+    /// The worker dies without reporting, as a crashed process would
+    Crash,
+    /// The task reports success but writes wrong values, only caught by output verification
+    WrongResult,
+}
+
+/// Distribution of the delay of a straggling task
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum DelayDistribution {
+    /// Always the same delay
+    Fixed { ms: u64 },
+    /// Any delay from 1ms up to `max_ms`
+    Uniform { max_ms: u64 },
+    /// Mostly short delays and a long tail, averaging `mean_ms`
+    Exponential { mean_ms: u64 },
+}
+
+/// Failure and straggler probabilities applying from `after_ms` into the job on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledRates {
+    pub after_ms: u64,
+    #[serde(default)]
+    pub failure_probability: u32,
+    #[serde(default)]
+    pub straggler_probability: u32,
+}
+
+impl Default for DelayDistribution {
+    fn default() -> Self {
+        DelayDistribution::Uniform { max_ms: 1000 }
+    }
+}
+
+impl DelayDistribution {
+    fn sample(&self, rng: &mut StdRng) -> u64 {
+        match *self {
+            DelayDistribution::Fixed { ms } => ms,
+            DelayDistribution::Uniform { max_ms } => rng.random_range(1..=max_ms.max(1)),
+            DelayDistribution::Exponential { mean_ms } => {
+                let draw: f64 = rng.random();
+                (-(1.0 - draw).ln() * mean_ms as f64).max(1.0) as u64
+            }
+        }
+    }
+}
+
+impl fmt::Display for DelayDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DelayDistribution::Fixed { ms } => write!(f, "delay {}ms", ms),
+            DelayDistribution::Uniform { max_ms } => write!(f, "delay up to {}ms", max_ms),
+            DelayDistribution::Exponential { mean_ms } => {
+                write!(f, "exponential delay, mean {}ms", mean_ms)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FailureMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FailureMode::Error => "error",
+            FailureMode::Crash => "crash",
+            FailureMode::WrongResult => "wrong-result",
+        })
+    }
+}
+
+impl FaultProfile {
+    /// Mapper profile of the job: `mapper_faults`, else the mapper failure and straggler settings
+    pub fn mappers(config: &Config) -> Self {
+        config.mapper_faults.clone().unwrap_or_else(|| {
+            Self::flat(
+                config.mapper_failure_probability,
+                config.mapper_straggler_probability,
+                config.mapper_straggler_delay_ms,
+            )
+        })
+    }
+
+    /// Reducer profile of the job: `reducer_faults`, else the reducer failure and straggler settings
+    pub fn reducers(config: &Config) -> Self {
+        config.reducer_faults.clone().unwrap_or_else(|| {
+            Self::flat(
+                config.reducer_failure_probability,
+                config.reducer_straggler_probability,
+                config.reducer_straggler_delay_ms,
+            )
+        })
+    }
+
+    /// Profile of the flat settings: reported failures, uniform delays, no schedule
+    fn flat(failure_probability: u32, straggler_probability: u32, max_delay_ms: u64) -> Self {
+        Self {
+            failure_probability,
+            straggler_probability,
+            straggler_delay: DelayDistribution::Uniform {
+                max_ms: max_delay_ms,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Whether any task may fail or straggle, at any time
+    pub fn injects_faults(&self) -> bool {
+        self.failure_probability > 0
+            || self.straggler_probability > 0
+            || self
+                .schedule
+                .iter()
+                .any(|rates| rates.failure_probability > 0 || rates.straggler_probability > 0)
+    }
+
+    /// Failure and straggler probabilities `elapsed_ms` into the job
+    fn rates_at(&self, elapsed_ms: u64) -> (u32, u32) {
+        self.schedule
+            .iter()
+            .rev()
+            .find(|rates| rates.after_ms <= elapsed_ms)
+            .map_or(
+                (self.failure_probability, self.straggler_probability),
+                |rates| (rates.failure_probability, rates.straggler_probability),
+            )
+    }
+
+    /// Problems with the profile set as `name`, for the config validation
+    pub fn problems(&self, name: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut percentage = |setting: String, probability: u32| {
+            if probability > 100 {
+                problems.push(format!(
+                    "{}.{} is a percentage (0-100), got {}",
+                    name, setting, probability
+                ));
+            }
+        };
+        percentage("failure_probability".to_string(), self.failure_probability);
+        percentage(
+            "straggler_probability".to_string(),
+            self.straggler_probability,
+        );
+        for (index, rates) in self.schedule.iter().enumerate() {
+            percentage(
+                format!("schedule[{}].failure_probability", index),
+                rates.failure_probability,
+            );
+            percentage(
+                format!("schedule[{}].straggler_probability", index),
+                rates.straggler_probability,
+            );
+        }
+
+        let delay = match self.straggler_delay {
+            DelayDistribution::Fixed { ms } => ms,
+            DelayDistribution::Uniform { max_ms } => max_ms,
+            DelayDistribution::Exponential { mean_ms } => mean_ms,
+        };
+        if delay == 0 {
+            problems.push(format!("{}.straggler_delay must be at least 1ms", name));
+        }
+        if self
+            .schedule
+            .windows(2)
+            .any(|pair| pair[0].after_ms >= pair[1].after_ms)
+        {
+            problems.push(format!(
+                "{}.schedule must be in increasing after_ms order",
+                name
+            ));
+        }
+        problems
+    }
+
+    /// Print the profile's faults, in the config summary
+    pub fn print_summary(&self, role: &str) {
+        if self.failure_probability > 0 {
+            println!(
+                "  - {} failure probability: {}% ({})",
+                role, self.failure_probability, self.failure_mode
+            );
+        }
+        if self.straggler_probability > 0 {
+            println!(
+                "  - {} straggler probability: {}% ({})",
+                role, self.straggler_probability, self.straggler_delay
+            );
+        }
+        for rates in &self.schedule {
+            println!(
+                "  - {} from {}ms: failure probability {}%, straggler probability {}%",
+                role, rates.after_ms, rates.failure_probability, rates.straggler_probability
+            );
+        }
+    }
+}
+
+/// Fault profile of a worker role, the seed of its failure schedule, and when the job started
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailureInjection {
+    pub profile: FaultProfile,
+    pub seed: u64,
+    /// Unix time in milliseconds the profile's schedule counts from
+    pub started_at_ms: u64,
+}
+
+/// Failure injected into a task by a worker
+pub enum InjectedFault {
+    /// Report the task as failed
+    Failure,
+    /// Exit the worker without reporting the task
+    Crash,
+    /// Run the task on state corrupting what it writes, and report success
+    WrongResult,
+    /// Delay in milliseconds before running the task
+    Straggler(u64),
+    /// Hold the task until the worker's kill switch releases it
+    Hang,
+}
+
+impl FailureInjection {
+    /// Mapper settings of the job, with the job's failure seed
+    pub fn mappers(config: &Config, seed: u64) -> Self {
+        Self {
+            profile: FaultProfile::mappers(config),
+            seed: mix(seed, 0),
+            started_at_ms: unix_time_ms(),
+        }
+    }
+
+    /// Reducer settings of the job, with a seed of their own derived from the job's failure seed
+    pub fn reducers(config: &Config, seed: u64) -> Self {
+        Self {
+            profile: FaultProfile::reducers(config),
+            seed: mix(seed, 1),
+            started_at_ms: unix_time_ms(),
+        }
+    }
+
+    /// Injector of the worker occupying a slot in the given epoch
+    /// Its seed depends only on the role's seed, the slot and the epoch,
+    /// so replaying a job seed replays the faults of every worker
+    /// (of a profile with a schedule, only as far as tasks run at the same times)
+    pub fn for_worker(&self, worker_id: usize, epoch: u64) -> FailureInjector {
+        FailureInjector {
+            settings: Self {
+                seed: mix(mix(self.seed, worker_id as u64), epoch),
+                ..self.clone()
+            },
+            rng: None,
+            kill_switch: KillSwitch::default(),
+        }
+    }
+}
+
+/// Draws the faults of one worker from its own seeded generator, in the order tasks arrive
+#[derive(Serialize, Deserialize)]
+pub struct FailureInjector {
+    settings: FailureInjection,
+    #[serde(skip)]
+    rng: Option<StdRng>,
+    /// Faults forced on demand, taking precedence over the drawn ones
+    #[serde(skip)]
+    kill_switch: KillSwitch,
+}
+
+impl FailureInjector {
+    /// Seed of this worker's failure schedule
+    pub fn seed(&self) -> u64 {
+        self.settings.seed
+    }
+
+    /// Kill switch forcing faults onto this worker on demand
+    pub fn kill_switch(&self) -> &KillSwitch {
+        &self.kill_switch
+    }
+
+    /// Fault to inject into the next task, if any
+    /// A fault forced through the kill switch is injected without drawing one
+    pub fn next_fault(&mut self) -> Option<InjectedFault> {
+        match self.kill_switch.forced() {
+            Some(ForcedFault::Crash) => return Some(InjectedFault::Crash),
+            Some(ForcedFault::Hang) => return Some(InjectedFault::Hang),
+            Some(ForcedFault::CorruptResults) => return Some(InjectedFault::WrongResult),
+            None => {}
+        }
+
+        let profile = &self.settings.profile;
+        let elapsed_ms = unix_time_ms().saturating_sub(self.settings.started_at_ms);
+        let (failure_probability, straggler_probability) = profile.rates_at(elapsed_ms);
+        if failure_probability == 0 && straggler_probability == 0 {
+            return None;
+        }
+
+        let seed = self.settings.seed;
+        let rng = self.rng.get_or_insert_with(|| StdRng::seed_from_u64(seed));
+        if failure_probability > 0 && rng.random_range(0..100) < failure_probability {
+            return Some(match profile.failure_mode {
+                FailureMode::Error => InjectedFault::Failure,
+                FailureMode::Crash => InjectedFault::Crash,
+                FailureMode::WrongResult => InjectedFault::WrongResult,
+            });
+        }
+        if straggler_probability > 0 && rng.random_range(0..100) < straggler_probability {
+            return Some(InjectedFault::Straggler(
+                profile.straggler_delay.sample(rng),
+            ));
+        }
+        None
+    }
+}
+
+/// Failure seed of the job: the configured one when replaying, else a random one, logged for replay
+pub fn resolve_failure_seed(config: &Config) -> u64 {
+    let seed = config.failure_seed.unwrap_or_else(rand::random);
+    let injects_faults = FaultProfile::mappers(config).injects_faults()
+        || FaultProfile::reducers(config).injects_faults();
+    if injects_faults {
+        println!(
+            "🎲 Failure seed: {} (replay with failure_seed = {} or --replay-seed {})",
+            seed, seed, seed
+        );
+    }
+    seed
+}
+
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// SplitMix64 step, spreading `value` over the seed
+fn mix(seed: u64, value: u64) -> u64 {
+    let mut z = seed ^ value.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,86 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::input_source::{InputSource, InputSplit, RecordReader};
+use std::cmp::{max, min};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Input source backed by a text file or a directory of text files
+#[derive(Clone)]
+pub struct FileInputSource {
+    path: PathBuf,
+}
+
+impl FileInputSource {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// List the files making up the input, sorted for a stable split order
+    fn files(&self) -> io::Result<Vec<PathBuf>> {
+        if self.path.is_dir() {
+            let mut files = Vec::new();
+            for entry in fs::read_dir(&self.path)? {
+                let path = entry?.path();
+                if path.is_file() {
+                    files.push(path);
+                }
+            }
+            files.sort();
+            Ok(files)
+        } else {
+            Ok(vec![self.path.clone()])
+        }
+    }
+
+    /// Split a single file, moving each boundary forward to the end of its line
+    fn split_file(path: &Path, split_size: u64) -> io::Result<Vec<InputSplit>> {
+        let size = fs::metadata(path)?.len();
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut splits = Vec::new();
+        let mut start = 0;
+        let mut line_tail = Vec::new();
+
+        while start < size {
+            let mut end = min(start + split_size, size);
+            if end < size {
+                // Include the rest of the line the tentative boundary falls into
+                reader.seek(SeekFrom::Start(end - 1))?;
+                line_tail.clear();
+                let read = reader.read_until(b'\n', &mut line_tail)?;
+                end = end - 1 + read as u64;
+            }
+
+            splits.push(InputSplit {
+                path: path.to_string_lossy().into_owned(),
+                offset: start,
+                length: end - start,
+            });
+            start = end;
+        }
+
+        Ok(splits)
+    }
+}
+
+impl InputSource for FileInputSource {
+    fn splits(&self, split_size: usize) -> io::Result<Vec<InputSplit>> {
+        let split_size = max(split_size, 1) as u64;
+        let mut splits = Vec::new();
+        for file in self.files()? {
+            splits.extend(Self::split_file(&file, split_size)?);
+        }
+        Ok(splits)
+    }
+
+    fn open_split(&self, split: &InputSplit) -> io::Result<RecordReader> {
+        let mut file = File::open(&split.path)?;
+        file.seek(SeekFrom::Start(split.offset))?;
+        Ok(RecordReader::new(BufReader::new(file.take(split.length))))
+    }
+}
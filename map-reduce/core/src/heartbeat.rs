@@ -0,0 +1,38 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::status_sender::StatusSender;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Background task sending periodic heartbeats for a worker
+/// The task is aborted when the handle is dropped
+pub struct HeartbeatHandle {
+    task: JoinHandle<()>,
+}
+
+impl HeartbeatHandle {
+    /// Start sending heartbeats through the status sender every `interval`
+    pub fn spawn<CS>(worker_id: usize, sender: CS, interval: Duration) -> Self
+    where
+        CS: StatusSender + 'static,
+    {
+        let task = tokio::spawn(async move {
+            loop {
+                if !sender.heartbeat(worker_id).await {
+                    warn!(worker_id, "Failed to send heartbeat");
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        Self { task }
+    }
+}
+
+impl Drop for HeartbeatHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::reduce_output::KeyResult;
+use crate::result_value::ResultValue;
+use crate::state_store::StateStore;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Separates a hot key from the index of one of its sub-keys, a control character
+/// the keys of the input are not expected to hold
+pub const SUB_KEY_SEPARATOR: char = '\u{1f}';
+
+/// Key holding the `index`-th share of the values of a split hot key
+pub fn sub_key(key: &str, index: usize) -> String {
+    format!("{}{}{}", key, SUB_KEY_SEPARATOR, index)
+}
+
+/// Hot key a sub-key is a share of, None for other keys
+pub fn split_key(key: &str) -> Option<&str> {
+    key.rsplit_once(SUB_KEY_SEPARATOR).map(|(key, _)| key)
+}
+
+pub fn is_sub_key(key: &str) -> bool {
+    key.contains(SUB_KEY_SEPARATOR)
+}
+
+/// Hot key found after the map phase, and the number of sub-keys it is split into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotKey {
+    pub key: String,
+    pub values: usize,
+    pub splits: usize,
+}
+
+/// Skew detection: the keys that received more than `threshold` values in the map phase,
+/// each to be split into enough sub-keys for `threshold` values each, at most one per reducer
+/// Counts the values in `map` alone, values spilled to disk are not seen
+pub fn detect_hot_keys(
+    map: &HashMap<String, Vec<i32>>,
+    threshold: usize,
+    num_reducers: usize,
+) -> Vec<HotKey> {
+    if num_reducers < 2 {
+        return Vec::new();
+    }
+    let mut hot_keys: Vec<HotKey> = map
+        .iter()
+        .filter(|(key, values)| values.len() > threshold && !is_sub_key(key))
+        .map(|(key, values)| HotKey {
+            key: key.clone(),
+            values: values.len(),
+            splits: values.len().div_ceil(threshold).min(num_reducers),
+        })
+        .collect();
+    hot_keys.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+    hot_keys
+}
+
+impl HotKey {
+    /// Sub-keys of the hot key with their share of its values, runs of consecutive values
+    pub fn shares(&self, values: Vec<i32>) -> Vec<(String, Vec<i32>)> {
+        let share = values.len().div_ceil(self.splits).max(1);
+        values
+            .chunks(share)
+            .enumerate()
+            .map(|(index, values)| (sub_key(&self.key, index), values.to_vec()))
+            .collect()
+    }
+}
+
+impl fmt::Display for HotKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' ({} values) into {} sub-keys",
+            self.key, self.values, self.splits
+        )
+    }
+}
+
+/// Spread the values of each hot key over its sub-keys
+/// The hot key keeps its values, so splitting again, e.g. after a coordinator failover, is harmless
+pub async fn split_hot_keys<S: StateStore>(state: &S, hot_keys: &[HotKey]) {
+    for hot_key in hot_keys {
+        let values = state.get(&hot_key.key).await;
+        for (key, values) in hot_key.shares(values) {
+            state.replace(key, values).await;
+        }
+        println!("🔥 Split hot key {}", hot_key);
+    }
+}
+
+/// Keys to reduce: every key of the state but the hot keys split into sub-keys
+pub fn reduce_keys(map: &HashMap<String, Vec<i32>>) -> Vec<String> {
+    map.keys()
+        .filter(|key| !map.contains_key(&sub_key(key, 0)))
+        .cloned()
+        .collect()
+}
+
+/// Final merge step: take the results of sub-keys out of the reduce tasks' results,
+/// returning the tasks left and the result of each hot key, recombined from its sub-keys' by `merge`
+pub fn merge_sub_key_results(
+    tasks: Vec<Vec<KeyResult>>,
+    merge: impl Fn(Vec<ResultValue>) -> ResultValue,
+) -> (Vec<Vec<KeyResult>>, Vec<KeyResult>) {
+    let mut parts: BTreeMap<String, Vec<ResultValue>> = BTreeMap::new();
+    let tasks = tasks
+        .into_iter()
+        .map(|results| {
+            results
+                .into_iter()
+                .filter_map(|(key, value)| match split_key(&key) {
+                    Some(hot_key) => {
+                        parts.entry(hot_key.to_string()).or_default().push(value);
+                        None
+                    }
+                    None => Some((key, value)),
+                })
+                .collect()
+        })
+        .collect();
+    let merged = parts
+        .into_iter()
+        .map(|(key, values)| (key, merge(values)))
+        .collect();
+    (tasks, merged)
+}
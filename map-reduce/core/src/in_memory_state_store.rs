@@ -2,15 +2,31 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
-use crate::state_store::StateStore;
+use crate::job_id::JobId;
+use crate::sorted_run_reader::{merge_runs, MemoryRunReader, RunEntry, SortedRunReader};
+use crate::spill_runs::SpillRuns;
+use crate::state_store::{Aggregate, StateStore};
+use crate::task_key::TaskKey;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Keys and their in-memory values, shared with the coordinator
+pub type SharedStateMap = Arc<Mutex<HashMap<String, Vec<i32>>>>;
 
 /// Local in-memory state using Arc<Mutex<HashMap>>
+/// Optionally spills values to sorted runs on disk once too many are buffered
 #[derive(Clone)]
 pub struct LocalStateAccess {
-    map: Arc<Mutex<HashMap<String, Vec<i32>>>>,
+    map: SharedStateMap,
+    spill: Option<Arc<Mutex<SpillRuns>>>,
+    /// Tasks whose updates were committed
+    committed: Arc<Mutex<HashSet<TaskKey>>>,
+    /// Job whose task commits are accepted, any job's until one begins
+    job: Arc<Mutex<Option<JobId>>>,
 }
 
 impl Default for LocalStateAccess {
@@ -23,30 +39,168 @@ impl LocalStateAccess {
     pub fn new() -> Self {
         Self {
             map: Arc::new(Mutex::new(HashMap::new())),
+            spill: None,
+            committed: Arc::new(Mutex::new(HashSet::new())),
+            job: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// State spilling to sorted runs under `spill_dir` whenever `memory_limit` values are buffered
+    /// Spilled runs are merged into an indexed run when the reduce phase first reads them back
+    pub fn with_spilling(spill_dir: impl AsRef<Path>, memory_limit: usize) -> io::Result<Self> {
+        Ok(Self {
+            map: Arc::new(Mutex::new(HashMap::new())),
+            spill: Some(Arc::new(Mutex::new(SpillRuns::new(
+                spill_dir,
+                memory_limit,
+            )?))),
+            committed: Arc::new(Mutex::new(HashSet::new())),
+            job: Arc::new(Mutex::new(None)),
+        })
+    }
+
     /// Get the underlying map for result extraction
-    pub fn get_map(&self) -> Arc<Mutex<HashMap<String, Vec<i32>>>> {
+    /// When spilling, it only holds the values not spilled yet (all of them once keys are reduced)
+    pub fn get_map(&self) -> SharedStateMap {
         self.map.clone()
     }
+
+    /// Remove all keys and committed tasks, e.g. before running the next stage of a pipeline
+    pub fn clear(&self) {
+        let mut map = self.map.lock().unwrap();
+        map.clear();
+        self.committed.lock().unwrap().clear();
+        if let Some(spill) = &self.spill {
+            spill.lock().unwrap().clear();
+        }
+    }
+
+    /// Start a job on a clear state, discarding the commits of other jobs' tasks from now on,
+    /// e.g. late ones of the previous job of a resident worker pool
+    pub fn begin_job(&self, job: JobId) {
+        self.clear();
+        *self.job.lock().unwrap() = Some(job);
+    }
+
+    /// Copy of all keys and values, e.g. to checkpoint the state at a phase boundary
+    pub fn snapshot(&self) -> HashMap<String, Vec<i32>> {
+        let map = self.map.lock().unwrap();
+        map.keys()
+            .map(|key| (key.clone(), self.values(&map, key)))
+            .collect()
+    }
+
+    /// Replace all keys and values with a previously taken snapshot
+    pub fn restore(&self, snapshot: HashMap<String, Vec<i32>>) {
+        let mut map = self.map.lock().unwrap();
+        if let Some(spill) = &self.spill {
+            spill.lock().unwrap().clear();
+        }
+        *map = snapshot;
+    }
+
+    /// Append a value to a key, spilling to disk if too many values are buffered
+    fn push(&self, map: &mut HashMap<String, Vec<i32>>, key: String, value: i32) {
+        map.entry(key).or_default().push(value);
+        if let Some(spill) = &self.spill {
+            let mut spill = spill.lock().unwrap();
+            if spill.buffer_value() {
+                spill.spill(map).expect("Failed to spill state to disk");
+            }
+        }
+    }
+
+    /// Spilled values of a key followed by the ones still in memory
+    fn values(&self, map: &HashMap<String, Vec<i32>>, key: &str) -> Vec<i32> {
+        let mut values = match &self.spill {
+            Some(spill) => spill
+                .lock()
+                .unwrap()
+                .get(key)
+                .expect("Failed to read spilled state"),
+            None => Vec::new(),
+        };
+        values.extend(map.get(key).into_iter().flatten());
+        values
+    }
 }
 
 #[async_trait]
 impl StateStore for LocalStateAccess {
     async fn update(&self, key: String, value: i32) {
         let mut map = self.map.lock().unwrap();
-        map.entry(key).or_default().push(value);
+        self.push(&mut map, key, value);
     }
 
-    async fn replace(&self, key: String, value: i32) {
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>) {
+        if self.job.lock().unwrap().is_some_and(|job| job != task.job) {
+            warn!(job_id = %task.job, "Discarded the updates of another job's task");
+            return;
+        }
         let mut map = self.map.lock().unwrap();
-        map.insert(key, vec![value]);
+        if !self.committed.lock().unwrap().insert(task) {
+            return;
+        }
+        for (key, value) in updates {
+            self.push(&mut map, key, value);
+        }
+    }
+
+    async fn replace(&self, key: String, values: Vec<i32>) {
+        let mut map = self.map.lock().unwrap();
+        if let Some(spill) = &self.spill {
+            spill.lock().unwrap().remove(&key);
+        }
+        map.insert(key, values);
     }
 
     async fn get(&self, key: &str) -> Vec<i32> {
         let map = self.map.lock().unwrap();
-        map.get(key).cloned().unwrap_or_default()
+        self.values(&map, key)
+    }
+
+    /// Merge the spilled values and the values still in memory key by key
+    /// The spilled runs are merged once into an indexed run, and each key's entry is read from
+    /// it with a single seek, off the runtime's worker threads
+    async fn merge_reduce(&self, keys: &[String], aggregate: Aggregate) -> Vec<i32> {
+        if keys.is_empty() {
+            return Vec::new();
+        }
+        let buffered = {
+            let map = self.map.lock().unwrap();
+            keys.iter()
+                .filter_map(|key| {
+                    let values = map.get(key).filter(|values| !values.is_empty())?;
+                    Some(RunEntry {
+                        key: key.clone(),
+                        values: values.clone(),
+                    })
+                })
+                .collect()
+        };
+        let memory = MemoryRunReader::new(buffered);
+
+        let keys = keys.to_vec();
+        let spill = self.spill.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut runs: Vec<Box<dyn SortedRunReader>> = vec![Box::new(memory)];
+            if let Some(spill) = spill {
+                runs.push(Box::new(spill.lock().unwrap().key_reader(&keys)?));
+            }
+            let mut partials = vec![Vec::new(); keys.len()];
+            merge_runs(runs, &keys, |index, values| {
+                partials[index].push(aggregate(values))
+            })?;
+            io::Result::Ok(
+                partials
+                    .iter()
+                    .map(|partials| aggregate(partials))
+                    .collect(),
+            )
+        })
+        .await
+        .expect("Spilled state reader panicked")
+        .expect("Failed to read spilled state")
     }
 
     async fn initialize(&self, keys: Vec<String>) {
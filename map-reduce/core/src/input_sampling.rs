@@ -0,0 +1,52 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::collections::HashMap;
+
+/// Map tasks run by the sampling pre-pass: `fraction` of them spread evenly over the input,
+/// at least one if `fraction` is positive
+pub fn sample_assignments<A: Clone>(assignments: &[A], fraction: f64) -> Vec<A> {
+    if fraction <= 0.0 || assignments.is_empty() {
+        return Vec::new();
+    }
+
+    let count = ((assignments.len() as f64 * fraction).ceil() as usize).clamp(1, assignments.len());
+    (0..count)
+        .map(|i| assignments[i * assignments.len() / count].clone())
+        .collect()
+}
+
+/// Quantiles of the sampled records, each weighted by its count in the sample state, splitting
+/// them into `partitions` ranges of about the same size
+/// Duplicate boundaries are merged, so skewed samples may yield fewer partitions
+pub fn quantile_boundaries(sample: &HashMap<String, Vec<i32>>, partitions: usize) -> Vec<String> {
+    let mut records: Vec<(&String, u64)> = sample
+        .iter()
+        .map(|(record, counts)| (record, counts.iter().sum::<i32>().max(0) as u64))
+        .collect();
+    records.sort_unstable();
+    let total: u64 = records.iter().map(|(_, count)| count).sum();
+
+    // The boundary of partition `i` is the record at rank `i * total / partitions` of the sample
+    let mut boundaries: Vec<String> = Vec::new();
+    let mut records = records.into_iter();
+    let mut current = None;
+    let mut seen = 0;
+    for i in 1..partitions as u64 {
+        let rank = i * total / partitions as u64;
+        while seen <= rank {
+            let Some((record, count)) = records.next() else {
+                break;
+            };
+            seen += count;
+            current = Some(record);
+        }
+        match current {
+            Some(record) if seen > rank => boundaries.push(record.clone()),
+            _ => break,
+        }
+    }
+    boundaries.dedup();
+    boundaries
+}
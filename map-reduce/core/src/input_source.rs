@@ -0,0 +1,101 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
+
+/// Byte range of an input file processed by a single map assignment
+/// Boundaries always fall on line boundaries, so every split holds whole records
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InputSplit {
+    pub path: String,
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Trait for abstracting where map input comes from (files, object storage, etc.)
+pub trait InputSource: Send + Sync {
+    /// Divide the input into splits of roughly `split_size` bytes
+    fn splits(&self, split_size: usize) -> io::Result<Vec<InputSplit>>;
+
+    /// Stream the records (lines) of a split, holding a buffer of it at a time rather than all of it
+    fn open_split(&self, split: &InputSplit) -> io::Result<RecordReader>;
+
+    /// Read all the records contained in a split
+    fn read_split(&self, split: &InputSplit) -> io::Result<SplitRecords> {
+        self.open_split(split)?.read_all()
+    }
+}
+
+/// Records read from a split, without the lines that were not valid UTF-8
+#[derive(Debug, Default)]
+pub struct SplitRecords {
+    pub records: Vec<String>,
+    /// Lines skipped because they were not valid UTF-8
+    pub malformed: usize,
+}
+
+/// Newline-separated records read one at a time, skipping malformed lines instead of failing
+/// A read error ends the records early, and is returned by `finish`
+pub struct RecordReader {
+    input: Box<dyn BufRead + Send>,
+    line: Vec<u8>,
+    malformed: usize,
+    error: Option<io::Error>,
+}
+
+impl RecordReader {
+    pub fn new(input: impl BufRead + Send + 'static) -> Self {
+        Self {
+            input: Box::new(input),
+            line: Vec::new(),
+            malformed: 0,
+            error: None,
+        }
+    }
+
+    /// Number of malformed lines skipped, or the error that ended the records early
+    pub fn finish(self) -> io::Result<usize> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.malformed),
+        }
+    }
+
+    /// Collect the remaining records
+    pub fn read_all(mut self) -> io::Result<SplitRecords> {
+        let records = self.by_ref().collect();
+        let malformed = self.finish()?;
+        Ok(SplitRecords { records, malformed })
+    }
+}
+
+impl Iterator for RecordReader {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.error.is_some() {
+            return None;
+        }
+        loop {
+            self.line.clear();
+            match self.input.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+
+            let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            match std::str::from_utf8(line) {
+                Ok(record) => return Some(record.to_string()),
+                Err(_) => self.malformed += 1,
+            }
+        }
+    }
+}
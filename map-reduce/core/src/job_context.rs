@@ -0,0 +1,41 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::side_inputs::SideInputs;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// Assignment whose job-wide data, e.g. the target words of a search, is left out of it
+/// Every worker receives that data once per phase, as the job context of its registration
+pub trait BroadcastContext {
+    /// Data shared by all tasks of a phase, () if each assignment carries all it needs
+    type JobContext: Clone + Default + Send + Sync + Serialize + DeserializeOwned + 'static;
+}
+
+/// Job context of a phase as broadcast to its workers: the job's own data for the phase,
+/// which it derefs to, and the side inputs of the job
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhaseContext<C> {
+    pub job: C,
+    #[serde(default)]
+    pub side_inputs: SideInputs,
+}
+
+impl<C> PhaseContext<C> {
+    pub fn new(job: C, side_inputs: SideInputs) -> Self {
+        Self { job, side_inputs }
+    }
+}
+
+impl<C> Deref for PhaseContext<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.job
+    }
+}
+
+/// Job context of the phase running assignments of type `A`
+pub type JobContextOf<A> = PhaseContext<<A as BroadcastContext>::JobContext>;
@@ -0,0 +1,62 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::job_status::JobStatus;
+use crate::result_sink::ResultSink;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Request to stop the jobs of a coordinator, e.g. on Ctrl+C or from a `JobHandle`
+/// Clones share the request, so a clone kept by the caller reaches the running coordinator
+#[derive(Clone, Debug)]
+pub struct Cancellation {
+    cancelled: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Cancellation {
+    fn default() -> Self {
+        Self {
+            cancelled: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl Cancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Wait until the jobs are cancelled
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.subscribe();
+        let _ = cancelled.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// What a coordinator reports to, and takes orders from, for the jobs it runs:
+/// the sink of their final results, their live status and their cancellation
+pub struct JobControl {
+    pub result_sink: Box<dyn ResultSink>,
+    pub status: JobStatus,
+    pub cancellation: Cancellation,
+}
+
+impl JobControl {
+    pub fn new(config: &Config, result_sink: Box<dyn ResultSink>) -> Self {
+        Self {
+            result_sink,
+            status: JobStatus::new(config.job),
+            cancellation: Cancellation::new(),
+        }
+    }
+}
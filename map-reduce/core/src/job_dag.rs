@@ -0,0 +1,183 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::{Config, OutputConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A MapReduce stage in a job pipeline
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StageConfig {
+    pub name: String,
+    /// Stages whose results become the input splits of this stage
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Input for stages without dependencies (defaults to the job input)
+    #[serde(default)]
+    pub input_path: Option<String>,
+}
+
+/// Pipeline of MapReduce stages, stored in dependency order
+pub struct JobDag {
+    stages: Vec<StageConfig>,
+    work_dir: PathBuf,
+}
+
+impl JobDag {
+    /// Validate the stage graph and order stages so dependencies run first
+    pub fn new(
+        stages: Vec<StageConfig>,
+        work_dir: impl Into<PathBuf>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut names = HashSet::new();
+        for stage in &stages {
+            if !names.insert(stage.name.as_str()) {
+                return Err(format!("Duplicate stage name '{}'", stage.name).into());
+            }
+        }
+        for stage in &stages {
+            for dependency in &stage.depends_on {
+                if !names.contains(dependency.as_str()) {
+                    return Err(format!(
+                        "Stage '{}' depends on unknown stage '{}'",
+                        stage.name, dependency
+                    )
+                    .into());
+                }
+            }
+        }
+
+        // Kahn's algorithm, keeping declaration order among ready stages
+        let mut remaining = stages;
+        let mut ordered: Vec<StageConfig> = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let done: HashSet<&str> = ordered.iter().map(|s| s.name.as_str()).collect();
+            let Some(next) = remaining
+                .iter()
+                .position(|s| s.depends_on.iter().all(|d| done.contains(d.as_str())))
+            else {
+                let cyclic: Vec<&str> = remaining.iter().map(|s| s.name.as_str()).collect();
+                return Err(format!("Stages {:?} form a dependency cycle", cyclic).into());
+            };
+            ordered.push(remaining.remove(next));
+        }
+
+        Ok(Self {
+            stages: ordered,
+            work_dir: work_dir.into(),
+        })
+    }
+
+    /// Build the pipeline described by the config, or a single stage if none is described
+    pub fn from_config(config: &Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let stages = if config.stages.is_empty() {
+            vec![StageConfig {
+                name: "job".to_string(),
+                depends_on: Vec::new(),
+                input_path: None,
+            }]
+        } else {
+            config.stages.clone()
+        };
+        Self::new(stages, &config.work_dir)
+    }
+
+    /// Stages in execution order
+    pub fn stages(&self) -> &[StageConfig] {
+        &self.stages
+    }
+
+    /// Whether no other stage depends on a stage, so its results are the job's
+    pub fn is_final(&self, stage: &StageConfig) -> bool {
+        self.dependents(stage).next().is_none()
+    }
+
+    fn dependents<'a>(&'a self, stage: &'a StageConfig) -> impl Iterator<Item = &'a StageConfig> {
+        self.stages
+            .iter()
+            .filter(|s| s.depends_on.contains(&stage.name))
+    }
+
+    /// Directory holding the results of upstream stages consumed by a stage
+    fn input_dir(&self, stage_name: &str) -> PathBuf {
+        self.work_dir.join(stage_name).join("input")
+    }
+
+    /// Derive the configuration used to run a stage:
+    /// its input is the results of its dependencies, and its results feed its dependents
+    /// (or the job outputs, for final stages, suffixed with the stage name if there are several)
+    pub fn stage_config(&self, base: &Config, stage: &StageConfig) -> std::io::Result<Config> {
+        let mut config = base.clone();
+        config.stages.clear();
+
+        config.input_path = if stage.depends_on.is_empty() {
+            stage.input_path.clone().or_else(|| base.input_path.clone())
+        } else {
+            Some(path_to_string(&self.input_dir(&stage.name)))
+        };
+
+        if !self.is_final(stage) {
+            let mut outputs = Vec::new();
+            for dependent in self.dependents(stage) {
+                let input_dir = self.input_dir(&dependent.name);
+                fs::create_dir_all(&input_dir)?;
+                outputs.push(OutputConfig::Jsonl {
+                    path: path_to_string(&input_dir.join(format!("{}.jsonl", stage.name))),
+                });
+            }
+            config.outputs = outputs;
+        } else if self.stages.iter().filter(|s| self.is_final(s)).count() > 1 {
+            config.outputs = base
+                .outputs
+                .iter()
+                .map(|output| stage_output(output, &stage.name))
+                .collect();
+        }
+
+        Ok(config)
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+/// Output of one of several final stages, e.g. `counts.jsonl` becomes `counts-search.jsonl`,
+/// so the stages don't overwrite each other's results
+fn stage_output(output: &OutputConfig, stage_name: &str) -> OutputConfig {
+    match output {
+        OutputConfig::Stdout => OutputConfig::Stdout,
+        OutputConfig::Jsonl { path } => OutputConfig::Jsonl {
+            path: suffixed(path, stage_name),
+        },
+        OutputConfig::Csv { path } => OutputConfig::Csv {
+            path: suffixed(path, stage_name),
+        },
+        OutputConfig::Object { uri } => OutputConfig::Object {
+            uri: suffixed(uri, stage_name),
+        },
+    }
+}
+
+/// Insert `-<stage_name>` before the extension of the last path segment, if any
+fn suffixed(path: &str, stage_name: &str) -> String {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = name_start + dot;
+            format!("{}-{}{}", &path[..dot], stage_name, &path[dot..])
+        }
+        _ => format!("{}-{}", path, stage_name),
+    }
+}
+
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
@@ -0,0 +1,89 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Registry of the built-in jobs, selected by name with the `job` setting or `--job`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobKind {
+    /// Count the lines containing each of the generated target words
+    #[default]
+    WordSearch,
+    /// Count every word of the input, case-folded
+    WordCount,
+    /// List the documents containing each word of the input
+    InvertedIndex,
+    /// Sort the input lines into range-partitioned runs
+    Sort,
+    /// Join the input records against the smaller `join_input`, broadcast to the mappers
+    Join,
+}
+
+impl JobKind {
+    pub const ALL: [JobKind; 5] = [
+        JobKind::WordSearch,
+        JobKind::WordCount,
+        JobKind::InvertedIndex,
+        JobKind::Sort,
+        JobKind::Join,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobKind::WordSearch => "word-search",
+            JobKind::WordCount => "word-count",
+            JobKind::InvertedIndex => "inverted-index",
+            JobKind::Sort => "sort",
+            JobKind::Join => "join",
+        }
+    }
+
+    /// Whether the job looks for generated target words
+    pub fn uses_targets(&self) -> bool {
+        matches!(self, JobKind::WordSearch)
+    }
+
+    /// Whether the job runs a sampling pre-pass before its map phase, e.g. for range partitioning
+    pub fn samples_input(&self) -> bool {
+        matches!(self, JobKind::Sort)
+    }
+
+    /// Whether the job can split hot keys over several reducers and merge their results back,
+    /// unlike sort, whose reducers each write a run of their range
+    pub fn splits_hot_keys(&self) -> bool {
+        !matches!(self, JobKind::Sort)
+    }
+
+    /// Whether the job joins its input against a secondary input, `join_input`
+    pub fn joins_input(&self) -> bool {
+        matches!(self, JobKind::Join)
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        JobKind::ALL
+            .into_iter()
+            .find(|job| job.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = JobKind::ALL.iter().map(JobKind::name).collect();
+                format!(
+                    "Unknown job '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
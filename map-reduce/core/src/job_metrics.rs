@@ -0,0 +1,125 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::counters::Counters;
+use crate::phase_metrics::PhaseMetrics;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Structured metrics report for a whole job
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobMetrics {
+    /// Name of the variant that ran the job (task-channels, thread-socket, process-rpc)
+    pub variant: String,
+    pub total_duration_ms: u64,
+    pub phases: Vec<PhaseMetrics>,
+    /// Counters summed over every phase
+    pub counters: Counters,
+    #[serde(skip, default = "Instant::now")]
+    start_time: Instant,
+}
+
+impl JobMetrics {
+    pub fn new(variant: impl Into<String>) -> Self {
+        Self {
+            variant: variant.into(),
+            total_duration_ms: 0,
+            phases: Vec::new(),
+            counters: Counters::default(),
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Record the metrics of a phase that just completed
+    pub fn record(&mut self, stage: &str, phase: &str, metrics: &PhaseMetrics) {
+        let mut metrics = metrics.clone();
+        metrics.stage = stage.to_string();
+        metrics.phase = phase.to_string();
+        self.counters.merge(&metrics.counters);
+        self.phases.push(metrics);
+    }
+
+    /// Mark the end of the job
+    pub fn finish(&mut self) {
+        self.total_duration_ms = self.start_time.elapsed().as_millis() as u64;
+    }
+
+    pub fn print_report(&self) {
+        println!("\n=== METRICS ({}) ===", self.variant);
+        for phase in &self.phases {
+            println!(
+                "{} / {}: {}ms, {}/{} tasks, {} retries ({} failures, {} timeouts, {} crashes), {} dead-lettered, {} bytes sent",
+                phase.stage,
+                phase.phase,
+                phase.duration_ms,
+                phase.completed_tasks,
+                phase.tasks,
+                phase.retries,
+                phase.failures,
+                phase.timeouts,
+                phase.crashes,
+                phase.dead_letters,
+                phase.bytes_sent
+            );
+            if phase.raw_bytes_sent > phase.bytes_sent {
+                println!(
+                    "  - Compression: {} raw bytes sent as {} ({:.1}x)",
+                    phase.raw_bytes_sent,
+                    phase.bytes_sent,
+                    phase.raw_bytes_sent as f64 / phase.bytes_sent.max(1) as f64
+                );
+            }
+            println!(
+                "  - Exactly-once: {} completions accepted, {} duplicates suppressed",
+                phase.completed_tasks, phase.duplicates_suppressed
+            );
+            println!("  - Tasks per worker: {:?}", phase.tasks_per_worker);
+            println!("  - Max queue depth: {}", phase.max_queue_depth);
+            println!(
+                "  - Task durations: p50 {}ms, p90 {}ms, p99 {}ms, max {}ms ({} stragglers detected)",
+                phase.task_p50_ms,
+                phase.task_p90_ms,
+                phase.task_p99_ms,
+                phase.task_max_ms,
+                phase.stragglers
+            );
+            if !phase.counters.is_empty() {
+                println!("  - Counters: {}", format_counters(&phase.counters));
+            }
+        }
+        if !self.counters.is_empty() {
+            println!("Job counters: {}", format_counters(&self.counters));
+        }
+        println!(
+            "Duplicate completions suppressed: {}",
+            self.phases
+                .iter()
+                .map(|phase| phase.duplicates_suppressed)
+                .sum::<u32>()
+        );
+        println!("Total job time: {}ms", self.total_duration_ms);
+    }
+
+    /// Write the report as JSON
+    pub fn write_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path.as_ref())?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        println!("Wrote metrics to {}", path.as_ref().display());
+        Ok(())
+    }
+}
+
+/// Counters as `name=value` pairs in name order
+fn format_counters(counters: &Counters) -> String {
+    counters
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,86 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::job_id::JobId;
+use crate::job_kind::JobKind;
+use std::collections::VecDeque;
+
+/// Job waiting for a resident worker pool
+pub struct QueuedJob {
+    pub id: JobId,
+    /// Position of the job in the queue, from 0
+    pub index: usize,
+    pub config: Config,
+}
+
+/// Jobs submitted to a resident worker pool, run one after the other on the same workers
+/// The workers are built for the pool's job kind and started with its worker settings
+/// (worker counts, timeouts, retries, injected faults), so only jobs of that kind are accepted
+pub struct JobQueue {
+    kind: JobKind,
+    pending: VecDeque<QueuedJob>,
+    submitted: usize,
+}
+
+impl JobQueue {
+    /// Queue of the pool's own job, followed by the jobs of the config files in its `job_queue`
+    /// A queued job's own `job_queue` is ignored
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut queue = Self {
+            kind: config.job,
+            pending: VecDeque::new(),
+            submitted: 0,
+        };
+        queue.submit(config.clone())?;
+        for path in &config.job_queue {
+            let job = Config::load(path)
+                .map_err(|e| format!("Failed to load queued job {}: {}", path, e))?;
+            queue.submit(job)?;
+        }
+        Ok(queue)
+    }
+
+    /// Queue a job, returning the id its tasks will carry
+    pub fn submit(&mut self, config: Config) -> Result<JobId, String> {
+        if config.job != self.kind {
+            return Err(format!(
+                "A {} job cannot run on a pool of {} workers",
+                config.job, self.kind
+            ));
+        }
+        let id = JobId::generate();
+        self.pending.push_back(QueuedJob {
+            id,
+            index: self.submitted,
+            config,
+        });
+        self.submitted += 1;
+        Ok(id)
+    }
+
+    /// Carry on with the job at `index` under the id it had, e.g. after a coordinator takeover,
+    /// dropping the jobs before it as they are done
+    pub fn resume(&mut self, index: usize, id: JobId) {
+        self.pending.retain(|job| job.index >= index);
+        if let Some(job) = self.pending.front_mut().filter(|job| job.index == index) {
+            job.id = id;
+        }
+    }
+
+    /// Configs of the jobs not taken yet, e.g. to adapt their paths to the workers
+    pub fn configs_mut(&mut self) -> impl Iterator<Item = &mut Config> {
+        self.pending.iter_mut().map(|job| &mut job.config)
+    }
+
+    /// Next job to run, None once the queue is drained
+    pub fn next_job(&mut self) -> Option<QueuedJob> {
+        self.pending.pop_front()
+    }
+
+    /// Number of jobs submitted so far, including the ones already taken
+    pub fn submitted(&self) -> usize {
+        self.submitted
+    }
+}
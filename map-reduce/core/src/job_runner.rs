@@ -0,0 +1,424 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::coordinator_checkpoint::{CoordinatorCheckpoint, JobPhase};
+use crate::coordinator_state::CoordinatorState;
+use crate::input_sampling::sample_assignments;
+use crate::job_context::PhaseContext;
+use crate::job_control::JobControl;
+use crate::job_dag::JobDag;
+use crate::job_id::JobId;
+use crate::job_metrics::JobMetrics;
+use crate::job_queue::JobQueue;
+use crate::map_reduce_job::MapReduceJob;
+use crate::output_verification::{
+    compare_results, merge_contributions, report_verification, split_verification_sample,
+    task_contribution,
+};
+use crate::reduce_output::KeyResult;
+use crate::result_order::ResultOrder;
+use crate::shutdown_signal::ShutdownSignal;
+use crate::utils::{
+    create_map_assignments, create_partial_results_subscriber, create_reduce_assignments,
+    deliver_results, find_hot_keys, generate_test_data, load_join_input, load_side_inputs,
+    report_metrics, report_results, serve_job_status, stream_partial_results,
+};
+use crate::worker::Worker;
+use crate::worker_factory::WorkerFactory;
+use crate::worker_pool::WorkerPool;
+use crate::worker_synchronization::WorkerSynchronization;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Parts of the coordinator's job loop that differ between variants
+#[async_trait]
+pub trait CoordinatorHooks: Send + Sync {
+    /// A job started on the input generated from `data_seed`, e.g. to replicate the
+    /// orchestrator's progress to a standby coordinator
+    fn job_started(&self, _job_id: JobId, _job_index: usize, _data_seed: u64) {}
+
+    /// A phase of the stage at `stage` (in execution order) started on the state `snapshot` copies
+    fn phase_started(
+        &self,
+        _stage: usize,
+        _phase: JobPhase,
+        _snapshot: &dyn Fn() -> HashMap<String, Vec<i32>>,
+    ) {
+    }
+
+    /// Final results of a reduce phase run reported elsewhere than to its executor,
+    /// e.g. gathered through an aggregator tree
+    async fn collect_results(
+        &self,
+        _job: JobId,
+        _phase_run: u64,
+        _order: ResultOrder,
+        _top_k: Option<usize>,
+    ) -> Vec<Vec<KeyResult>> {
+        Vec::new()
+    }
+}
+
+/// Hooks of variants with nothing to add to the job loop
+impl CoordinatorHooks for () {}
+
+/// Job loop of a coordinator: runs the queued jobs, stage by stage, on a mapper and a reducer
+/// pool, the variant only wiring up its state, shutdown signal and hooks
+pub struct JobRunner<'a, S, SD> {
+    /// Name of the variant, labelling the job metrics
+    pub variant: String,
+    pub state: &'a S,
+    pub shutdown_signal: &'a SD,
+    pub hooks: &'a dyn CoordinatorHooks,
+}
+
+impl<S, SD> JobRunner<'_, S, SD>
+where
+    S: CoordinatorState,
+    SD: ShutdownSignal + Sync,
+{
+    /// Run the queued jobs on the same workers, one after the other, each on a state of its own
+    /// When resuming from a checkpoint, jobs and stages already done are skipped and the
+    /// interrupted phase is rerun from the state it started with
+    /// Fails with the jobs whose results could not be written, e.g. with dead-lettered tasks
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run<P, M, MS, MF, R, RS, RF>(
+        &self,
+        config: &Config,
+        mut queue: JobQueue,
+        mut resume: Option<CoordinatorCheckpoint>,
+        mappers: &mut WorkerPool<M, MS, MF>,
+        reducers: &mut WorkerPool<R, RS, RF>,
+        control: &mut JobControl,
+    ) -> Result<(), String>
+    where
+        P: MapReduceJob<Input = Arc<[String]>>,
+        M: Worker<Assignment = P::MapAssignment>,
+        MS: WorkerSynchronization,
+        M::Completion: From<MS::StatusSender>,
+        MF: WorkerFactory<M>,
+        R: Worker<Assignment = P::ReduceAssignment>,
+        RS: WorkerSynchronization,
+        R::Completion: From<RS::StatusSender>,
+        RF: WorkerFactory<R>,
+    {
+        let state = self.state;
+        let shutdown_signal = self.shutdown_signal;
+        let status = &control.status;
+        let result_sink = control.result_sink.as_mut();
+
+        // A standby taking over carries on with the primary's job, under the same id
+        if let Some(checkpoint) = &resume {
+            queue.resume(checkpoint.job_index, checkpoint.job_id);
+        }
+
+        // Report the progress of the jobs, served over HTTP if configured
+        serve_job_status(config, status).await;
+        mappers.executor.set_status(status.clone());
+        reducers.executor.set_status(status.clone());
+
+        let mut failed_jobs = Vec::new();
+        while let Some(job) = queue.next_job() {
+            let mut job_config = job.config;
+            if queue.submitted() > 1 {
+                info!(
+                    job_id = %job.id,
+                    "=== Job {}/{} ===",
+                    job.index + 1,
+                    queue.submitted()
+                );
+                if job.index > 0 {
+                    job_config.print_summary();
+                }
+            }
+
+            // Only the first job run after a takeover is the interrupted one
+            let resume = resume.take();
+            let dag = match JobDag::from_config(&job_config) {
+                Ok(dag) => dag,
+                Err(e) => {
+                    error!(job_id = %job.id, error = %e, "❌ Failed to build the job's pipeline");
+                    failed_jobs.push(format!("job {}: invalid pipeline: {}", job.id, e));
+                    continue;
+                }
+            };
+            // A resumed job regenerates the interrupted one's input from its seed
+            let data_seed = match &resume {
+                Some(checkpoint) => checkpoint.data_seed,
+                None => job_config.data_seed.unwrap_or_else(rand::random),
+            };
+            job_config.data_seed = Some(data_seed);
+            let (data, targets) = generate_test_data(&job_config);
+            self.hooks.job_started(job.id, job.index, data_seed);
+            state.begin_job(job.id);
+            mappers.executor.set_job(job.id);
+            reducers.executor.set_job(job.id);
+            status.begin_job(job.id, job.index, queue.submitted());
+
+            let mut job_metrics = JobMetrics::new(self.variant.clone());
+
+            // Tasks of the job given up on, each leaving its results incomplete
+            let mut dead_letters = 0;
+            // Why the job stopped before running all its stages, if it did
+            let mut job_error = None;
+
+            // Subscriber for partial results streamed during the map phases
+            let mut partial_results_subscriber = create_partial_results_subscriber(&job_config);
+
+            // Side inputs of the job, broadcast to every worker with the job context of each phase
+            let side_inputs = load_side_inputs(&job_config);
+            reducers
+                .executor
+                .set_context(PhaseContext::new(Default::default(), side_inputs.clone()));
+
+            // Run the stages in dependency order, reusing the worker pools
+            for (stage_index, stage) in dag.stages().iter().enumerate() {
+                // Stages finished before a takeover already wrote their results
+                let resumed = resume.as_ref().filter(|c| c.stage == stage_index);
+                if resume.as_ref().is_some_and(|c| stage_index < c.stage) {
+                    continue;
+                }
+
+                let stage_config = match dag.stage_config(&job_config, stage) {
+                    Ok(stage_config) => stage_config,
+                    Err(e) => {
+                        job_error = Some(format!("failed to prepare stage {}: {}", stage.name, e));
+                        break;
+                    }
+                };
+                if dag.len() > 1 {
+                    info!(job_id = %job.id, stage = %stage.name, "=== Stage ===");
+                }
+                status.begin_stage(&stage.name);
+                let mut context = P::create_context(&stage_config, &data, targets.clone());
+                // Secondary input of map-side joins, broadcast to the mappers with the map context
+                if let Some(records) = load_join_input(&stage_config) {
+                    P::apply_join_input(&stage_config, &mut context, records);
+                }
+                let (map_assignments, chunk_tuner) =
+                    create_map_assignments::<P>(&stage_config, data.clone(), context.clone());
+
+                // Sampling pre-pass: a fraction of the map tasks run against a scratch state,
+                // from which the job completes its context, e.g. with range partition boundaries
+                let sample =
+                    sample_assignments(&map_assignments, P::sample_fraction(&stage_config));
+                if !sample.is_empty() {
+                    info!(
+                        job_id = %job.id,
+                        stage = %stage.name,
+                        "=== Sampling the input with {} of {} map tasks ===",
+                        sample.len(),
+                        map_assignments.len()
+                    );
+                    state.clear();
+                    mappers.executor.set_context(PhaseContext::new(
+                        P::map_context(&context),
+                        side_inputs.clone(),
+                    ));
+                    mappers.execute(sample, shutdown_signal).await;
+                    mappers.executor.print_dead_letters("Sample");
+                    job_metrics.record(&stage.name, "Sample", mappers.executor.metrics());
+                    state.with_map(|map| P::apply_sample(&stage_config, &mut context, map));
+                }
+
+                // Reset state for the stage, or restore the state the interrupted phase started with
+                match resumed {
+                    Some(checkpoint) => state.restore(checkpoint.state.clone()),
+                    None => {
+                        state.clear();
+                        state.initialize(P::initial_keys(&context)).await;
+                    }
+                }
+
+                // Execute map phase, unless the takeover happened during the reduce phase
+                if resumed.is_none_or(|c| c.phase == JobPhase::Map) {
+                    self.hooks
+                        .phase_started(stage_index, JobPhase::Map, &|| state.snapshot());
+
+                    info!(
+                        job_id = %job.id,
+                        stage = %stage.name,
+                        "=== Map phase: distributing data to {} mappers ===",
+                        config.num_mappers
+                    );
+                    mappers.executor.set_context(PhaseContext::new(
+                        P::map_context(&context),
+                        side_inputs.clone(),
+                    ));
+
+                    // Run the sample to verify first, one task at a time from the phase's initial
+                    // state, so the results each task writes can be told apart
+                    let (sample, map_assignments) =
+                        split_verification_sample(map_assignments, job_config.verify_fraction);
+                    if !sample.is_empty() {
+                        let initial = state.snapshot();
+                        let mut recorded = Vec::with_capacity(sample.len());
+                        for (_, assignment) in &sample {
+                            state.restore(initial.clone());
+                            mappers
+                                .execute(vec![assignment.clone()], shutdown_signal)
+                                .await;
+                            mappers.executor.print_dead_letters("Map (sampled)");
+                            dead_letters += mappers.executor.dead_letters().len();
+                            job_metrics.record(
+                                &stage.name,
+                                "Map (sampled)",
+                                mappers.executor.metrics(),
+                            );
+                            recorded.push((
+                                mappers.executor.completed_by(0),
+                                task_contribution(&initial, state.snapshot()),
+                            ));
+                        }
+
+                        // Re-execute each sampled task on another worker than the one that
+                        // recorded it, and compare what the two runs wrote
+                        info!(
+                            job_id = %job.id,
+                            stage = %stage.name,
+                            "=== Verifying {} sampled map tasks ===",
+                            sample.len()
+                        );
+                        let mut divergences = Vec::new();
+                        for ((task, assignment), (recorded_by, recorded)) in
+                            sample.iter().zip(&recorded)
+                        {
+                            state.restore(initial.clone());
+                            if let Some(worker_id) = recorded_by {
+                                mappers.executor.exclude_workers(vec![(0, *worker_id)]);
+                            }
+                            mappers
+                                .execute(vec![assignment.clone()], shutdown_signal)
+                                .await;
+                            job_metrics.record(&stage.name, "Verify", mappers.executor.metrics());
+                            let reexecuted = task_contribution(&initial, state.snapshot());
+                            divergences.extend(compare_results(*task, recorded, &reexecuted));
+                        }
+                        report_verification(sample.len(), &divergences);
+                        state.restore(merge_contributions(
+                            &initial,
+                            recorded.iter().map(|(_, contribution)| contribution),
+                        ));
+                    }
+                    mappers
+                        .execute_with_progress(
+                            map_assignments,
+                            shutdown_signal,
+                            stream_partial_results::<P>(
+                                state.maps(),
+                                partial_results_subscriber.as_deref_mut(),
+                            ),
+                        )
+                        .await;
+
+                    // Adaptive chunk sizing: the rest of the input is re-partitioned after the probe tasks
+                    if let Some(tuner) = chunk_tuner {
+                        mappers.executor.print_dead_letters("Map (probe)");
+                        dead_letters += mappers.executor.dead_letters().len();
+                        job_metrics.record(&stage.name, "Map (probe)", mappers.executor.metrics());
+                        let remaining = tuner.remaining_assignments::<P>(
+                            mappers.executor.metrics(),
+                            context.clone(),
+                        );
+                        if !remaining.is_empty() {
+                            mappers
+                                .execute_with_progress(
+                                    remaining,
+                                    shutdown_signal,
+                                    stream_partial_results::<P>(
+                                        state.maps(),
+                                        partial_results_subscriber.as_deref_mut(),
+                                    ),
+                                )
+                                .await;
+                        }
+                    }
+                    info!(job_id = %job.id, stage = %stage.name, "All mappers completed!");
+                    if let Err(e) = state.flush().await {
+                        warn!(job_id = %job.id, error = %e, "Failed to persist state");
+                    }
+                    mappers.executor.print_dead_letters("Map");
+                    dead_letters += mappers.executor.dead_letters().len();
+                    job_metrics.record(&stage.name, "Map", mappers.executor.metrics());
+                }
+
+                // Execute reduce phase
+                self.hooks
+                    .phase_started(stage_index, JobPhase::Reduce, &|| state.snapshot());
+                info!(
+                    job_id = %job.id,
+                    stage = %stage.name,
+                    "=== Reduce phase: starting {} reducers ===",
+                    config.num_reducers
+                );
+                let hot_keys = state.with_map(|map| find_hot_keys::<P>(&stage_config, map));
+                state.split_hot_keys(&hot_keys).await;
+                let reduce_assignments = state.with_map(|map| {
+                    create_reduce_assignments::<P>(&stage_config, context.clone(), map)
+                });
+                reducers.execute(reduce_assignments, shutdown_signal).await;
+                info!(job_id = %job.id, stage = %stage.name, "All reducers completed!");
+                if let Err(e) = state.flush().await {
+                    warn!(job_id = %job.id, error = %e, "Failed to persist state");
+                }
+                reducers.executor.print_dead_letters("Reduce");
+                dead_letters += reducers.executor.dead_letters().len();
+                job_metrics.record(&stage.name, "Reduce", reducers.executor.metrics());
+
+                // Hand stage results to its dependents, or to the result sink for final stages
+                let mut tasks = reducers.executor.take_results();
+                tasks.extend(
+                    self.hooks
+                        .collect_results(
+                            job.id,
+                            reducers.executor.phase_run(),
+                            P::result_order(),
+                            stage_config.top_k,
+                        )
+                        .await,
+                );
+                // A cancelled stage's phases stopped early, so its results are incomplete
+                if shutdown_signal.is_cancelled() {
+                    break;
+                }
+                deliver_results::<P>(&dag, stage, &stage_config, tasks, result_sink);
+            }
+
+            // A cancelled job, one with dead-lettered tasks or a stage that could not be prepared
+            // writes none of its incomplete results and leaves the outputs of earlier runs untouched
+            let outcome = if let Some(e) = job_error {
+                result_sink.discard();
+                Err(e)
+            } else if shutdown_signal.is_cancelled() {
+                result_sink.discard();
+                Err("cancelled, no results written".to_string())
+            } else if dead_letters > 0 {
+                result_sink.discard();
+                Err(format!(
+                    "{} task(s) exceeded the retry budget, no results written",
+                    dead_letters
+                ))
+            } else {
+                report_results(result_sink)
+            };
+            if let Err(e) = outcome {
+                error!(job_id = %job.id, error = %e, "❌ Job failed");
+                failed_jobs.push(format!("job {}: {}", job.id, e));
+            }
+            report_metrics(&job_config, &mut job_metrics);
+            if shutdown_signal.is_cancelled() {
+                break;
+            }
+        }
+
+        if failed_jobs.is_empty() {
+            Ok(())
+        } else {
+            Err(failed_jobs.join("; "))
+        }
+    }
+}
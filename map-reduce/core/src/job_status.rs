@@ -0,0 +1,276 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::counters::Counters;
+use crate::job_id::JobId;
+use crate::job_kind::JobKind;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Failures kept in the status report, the oldest dropped first
+pub const RECENT_FAILURES: usize = 20;
+
+/// What a worker slot is doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorkerActivity {
+    #[default]
+    Idle,
+    Running,
+    /// Its worker failed and is being replaced
+    Respawning,
+}
+
+/// State of a worker slot of the current (or last) phase of its kind
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkerState {
+    pub activity: WorkerActivity,
+    /// Task the worker is running
+    pub chunk: Option<usize>,
+    /// Tasks the slot completed during the phase
+    pub completed_tasks: usize,
+    /// Workers of the slot replaced during the phase
+    pub respawns: u32,
+    /// Assignments sent to the slot's worker and not yet reported on
+    pub outstanding: usize,
+    /// Assignments waiting in the worker's work queue, as last observed
+    pub queue_depth: usize,
+    /// Deepest the slot's work queue was observed during the phase
+    pub max_queue_depth: usize,
+}
+
+/// Task progress of the current phase
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChunkProgress {
+    pub tasks: usize,
+    pub completed: usize,
+    pub running: usize,
+    pub retries: u32,
+    pub dead_letters: usize,
+    /// Assignments waiting in the work queues of all workers of the phase
+    pub queued: usize,
+}
+
+/// A failed, crashed, timed-out or straggling task attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct FailureRecord {
+    /// Milliseconds since the coordinator started
+    pub at_ms: u64,
+    pub job_id: String,
+    pub phase: &'static str,
+    pub worker_id: usize,
+    pub chunk: usize,
+    pub reason: String,
+}
+
+/// Status of the coordinator's jobs, as served by the status endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub job: JobKind,
+    /// Id of the running job, as in the log lines
+    pub job_id: String,
+    /// Position of the running job in the queue, from 0, and the jobs submitted
+    pub job_index: usize,
+    pub jobs: usize,
+    pub stage: String,
+    /// Phase being executed (map, reduce), None between phases
+    pub phase: Option<&'static str>,
+    pub uptime_ms: u64,
+    pub progress: ChunkProgress,
+    /// Worker slots by phase
+    pub workers: BTreeMap<&'static str, Vec<WorkerState>>,
+    pub recent_failures: VecDeque<FailureRecord>,
+    /// Counters of the running job, summed over its completed tasks
+    pub counters: Counters,
+}
+
+/// Shared, live status of the coordinator, updated by the executors
+#[derive(Clone)]
+pub struct JobStatus {
+    started: Instant,
+    report: Arc<Mutex<StatusReport>>,
+}
+
+impl JobStatus {
+    pub fn new(job: JobKind) -> Self {
+        Self {
+            started: Instant::now(),
+            report: Arc::new(Mutex::new(StatusReport {
+                job,
+                job_id: String::new(),
+                job_index: 0,
+                jobs: 0,
+                stage: String::new(),
+                phase: None,
+                uptime_ms: 0,
+                progress: ChunkProgress::default(),
+                workers: BTreeMap::new(),
+                recent_failures: VecDeque::new(),
+                counters: Counters::default(),
+            })),
+        }
+    }
+
+    /// Snapshot of the status
+    pub fn report(&self) -> StatusReport {
+        let mut report = self.report.lock().unwrap().clone();
+        report.uptime_ms = self.started.elapsed().as_millis() as u64;
+        report
+    }
+
+    /// Start the job at `index` of the `jobs` submitted so far
+    pub fn begin_job(&self, job_id: JobId, index: usize, jobs: usize) {
+        let mut report = self.report.lock().unwrap();
+        report.job_id = job_id.to_string();
+        report.job_index = index;
+        report.jobs = jobs;
+        report.stage.clear();
+        report.counters = Counters::default();
+    }
+
+    pub fn begin_stage(&self, stage: &str) {
+        self.report.lock().unwrap().stage = stage.to_string();
+    }
+
+    pub(crate) fn phase_started(&self, phase: &'static str, tasks: usize, workers: usize) {
+        let mut report = self.report.lock().unwrap();
+        report.phase = Some(phase);
+        report.progress = ChunkProgress {
+            tasks,
+            ..ChunkProgress::default()
+        };
+        report
+            .workers
+            .insert(phase, vec![WorkerState::default(); workers]);
+    }
+
+    pub(crate) fn phase_finished(&self) {
+        let mut report = self.report.lock().unwrap();
+        let report = &mut *report;
+        if let Some(workers) = report.phase.and_then(|phase| report.workers.get_mut(phase)) {
+            for worker in workers.iter_mut() {
+                worker.activity = WorkerActivity::Idle;
+                worker.chunk = None;
+            }
+        }
+        report.phase = None;
+        report.progress.running = 0;
+        report.progress.queued = 0;
+    }
+
+    /// Record a task sent to a worker, whose queue then held `queue_depth` assignments
+    pub(crate) fn task_assigned(&self, worker_id: usize, task_index: usize, queue_depth: usize) {
+        self.update_worker(worker_id, |progress, worker| {
+            progress.running += 1;
+            worker.activity = WorkerActivity::Running;
+            worker.chunk = Some(task_index);
+            worker.outstanding += 1;
+        });
+        self.update_queue_depths([(worker_id, queue_depth)]);
+    }
+
+    /// Refresh the queue depths of the phase's workers, in slot order
+    pub(crate) fn queue_depths(&self, depths: impl IntoIterator<Item = usize>) {
+        self.update_queue_depths(depths.into_iter().enumerate());
+    }
+
+    pub(crate) fn task_completed(&self, worker_id: usize, counters: &Counters) {
+        self.update_worker(worker_id, |progress, worker| {
+            progress.running = progress.running.saturating_sub(1);
+            progress.completed += 1;
+            worker.activity = WorkerActivity::Idle;
+            worker.chunk = None;
+            worker.completed_tasks += 1;
+            worker.outstanding = worker.outstanding.saturating_sub(1);
+        });
+        self.report.lock().unwrap().counters.merge(counters);
+    }
+
+    /// Record a failed attempt, its worker being replaced and its task retried unless `dead_lettered`
+    pub(crate) fn task_failed(
+        &self,
+        worker_id: usize,
+        task_index: usize,
+        reason: String,
+        dead_lettered: bool,
+    ) {
+        let at_ms = self.started.elapsed().as_millis() as u64;
+        self.update_worker(worker_id, |progress, worker| {
+            progress.running = progress.running.saturating_sub(1);
+            if dead_lettered {
+                progress.dead_letters += 1;
+            } else {
+                progress.retries += 1;
+            }
+            // The replaced worker's queue goes with it
+            worker.activity = WorkerActivity::Respawning;
+            worker.chunk = None;
+            worker.respawns += 1;
+            worker.outstanding = 0;
+            worker.queue_depth = 0;
+        });
+
+        let mut report = self.report.lock().unwrap();
+        let Some(phase) = report.phase else {
+            return;
+        };
+        if report.recent_failures.len() == RECENT_FAILURES {
+            report.recent_failures.pop_front();
+        }
+        let job_id = report.job_id.clone();
+        report.recent_failures.push_back(FailureRecord {
+            at_ms,
+            job_id,
+            phase,
+            worker_id,
+            chunk: task_index,
+            reason,
+        });
+    }
+
+    pub(crate) fn worker_respawned(&self, worker_id: usize) {
+        self.update_worker(worker_id, |_, worker| {
+            if worker.activity == WorkerActivity::Respawning {
+                worker.activity = WorkerActivity::Idle;
+            }
+        });
+    }
+
+    /// Set the queue depths of the given worker slots, and the phase's total
+    fn update_queue_depths(&self, depths: impl IntoIterator<Item = (usize, usize)>) {
+        let mut report = self.report.lock().unwrap();
+        let report = &mut *report;
+        let Some(workers) = report.phase.and_then(|phase| report.workers.get_mut(phase)) else {
+            return;
+        };
+        for (worker_id, depth) in depths {
+            if let Some(worker) = workers.get_mut(worker_id) {
+                worker.queue_depth = depth;
+                worker.max_queue_depth = worker.max_queue_depth.max(depth);
+            }
+        }
+        report.progress.queued = workers.iter().map(|worker| worker.queue_depth).sum();
+    }
+
+    fn update_worker(
+        &self,
+        worker_id: usize,
+        update: impl FnOnce(&mut ChunkProgress, &mut WorkerState),
+    ) {
+        let mut report = self.report.lock().unwrap();
+        let report = &mut *report;
+        let Some(phase) = report.phase else {
+            return;
+        };
+        if let Some(worker) = report
+            .workers
+            .get_mut(phase)
+            .and_then(|workers| workers.get_mut(worker_id))
+        {
+            update(&mut report.progress, worker);
+        }
+    }
+}
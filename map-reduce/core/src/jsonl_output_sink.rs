@@ -0,0 +1,50 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::output_sink::OutputSink;
+use crate::result_value::ResultValue;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+#[derive(Serialize)]
+struct Record<'a> {
+    key: &'a str,
+    value: &'a ResultValue,
+}
+
+/// Writes one JSON object per line: {"key": ..., "value": ...}
+/// with a number or an array of numbers as value
+pub struct JsonlOutputSink {
+    path: PathBuf,
+}
+
+impl JsonlOutputSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OutputSink for JsonlOutputSink {
+    fn write(&mut self, results: &[(String, ResultValue)]) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        write_records(&mut writer, results)?;
+        writer.flush()?;
+        println!("Wrote {} results to {}", results.len(), self.path.display());
+        Ok(())
+    }
+}
+
+/// Write results as JSON lines
+pub(crate) fn write_records(
+    writer: &mut impl Write,
+    results: &[(String, ResultValue)],
+) -> io::Result<()> {
+    for (key, value) in results {
+        serde_json::to_writer(&mut *writer, &Record { key, value })?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Fault forced onto a worker on demand, e.g. by a chaos test through a worker's admin RPC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcedFault {
+    /// Exit the worker at once, reporting nothing, as a dead process would
+    Crash,
+    /// Hold the next task until the fault is cleared or replaced, heartbeats going on
+    Hang,
+    /// Run the next tasks on state corrupting what they write, reporting success
+    CorruptResults,
+}
+
+impl fmt::Display for ForcedFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ForcedFault::Crash => "crash",
+            ForcedFault::Hang => "hang",
+            ForcedFault::CorruptResults => "corrupt-results",
+        })
+    }
+}
+
+impl FromStr for ForcedFault {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "crash" => Ok(ForcedFault::Crash),
+            "hang" => Ok(ForcedFault::Hang),
+            "corrupt-results" => Ok(ForcedFault::CorruptResults),
+            other => Err(format!(
+                "Unknown fault '{}' (expected crash, hang or corrupt-results)",
+                other
+            )),
+        }
+    }
+}
+
+/// Fault forced onto a worker from outside its failure schedule, in effect until cleared
+/// Clones share the switch, so a handle kept by an admin service reaches the running worker
+#[derive(Clone, Debug)]
+pub struct KillSwitch {
+    forced: Arc<watch::Sender<Option<ForcedFault>>>,
+}
+
+impl Default for KillSwitch {
+    fn default() -> Self {
+        Self {
+            forced: Arc::new(watch::channel(None).0),
+        }
+    }
+}
+
+impl KillSwitch {
+    /// Force a fault onto the worker, or clear the forced fault with None
+    pub fn force(&self, fault: Option<ForcedFault>) {
+        self.forced.send_replace(fault);
+    }
+
+    /// Fault currently forced onto the worker
+    pub fn forced(&self) -> Option<ForcedFault> {
+        *self.forced.borrow()
+    }
+
+    /// Wait until the worker is no longer forced to hang
+    pub async fn released(&self) {
+        let mut forced = self.forced.subscribe();
+        let _ = forced
+            .wait_for(|fault| *fault != Some(ForcedFault::Hang))
+            .await;
+    }
+}
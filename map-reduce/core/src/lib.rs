@@ -2,20 +2,80 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+pub mod aggregator;
+pub mod chunk_tuning;
+pub mod cluster;
+pub mod completion_log;
 pub mod config;
+pub mod coordinator_checkpoint;
+pub mod coordinator_state;
+pub mod corrupted_state;
+pub mod counters;
+pub mod csv_output_sink;
+pub mod data_chunk;
 pub mod executor;
+pub mod failure_domains;
+pub mod failure_injection;
+pub mod file_input_source;
+pub mod heartbeat;
+pub mod hot_keys;
 pub mod in_memory_state_store;
+pub mod input_sampling;
+pub mod input_source;
+pub mod job_context;
+pub mod job_control;
+pub mod job_dag;
+pub mod job_id;
+pub mod job_kind;
+pub mod job_metrics;
+pub mod job_queue;
+pub mod job_runner;
+pub mod job_status;
+pub mod jsonl_output_sink;
+pub mod kill_switch;
+pub mod local_object_store;
+pub mod logging;
 pub mod map_reduce_job;
 pub mod mapper;
+pub mod memory_result_sink;
+pub mod object_store;
+pub mod object_store_input_source;
+pub mod object_store_output_sink;
+pub mod output_result_sink;
+pub mod output_sink;
+pub mod output_verification;
+pub mod partial_results;
+pub mod partitioner;
+pub mod phase_metrics;
+pub mod process_runtime;
+pub mod reduce_output;
 pub mod reducer;
+pub mod result_order;
+pub mod result_sink;
+pub mod result_value;
+pub mod s3_object_store;
+pub mod scheduling_policy;
 pub mod shutdown_signal;
+pub mod side_inputs;
+pub mod sled_state_store;
+pub mod sorted_run_reader;
+pub mod spill_runs;
 pub mod state_store;
 pub mod status_sender;
+pub mod status_server;
+pub mod stdout_output_sink;
+pub mod stdout_partial_results;
+pub mod task_contribution;
+pub mod task_durations;
+pub mod task_key;
 pub mod utils;
+pub mod work_compression;
 pub mod work_receiver;
 pub mod work_sender;
 pub mod worker;
+pub mod worker_epochs;
 pub mod worker_factory;
 pub mod worker_message;
+pub mod worker_pool;
 pub mod worker_runtime;
 pub mod worker_synchronization;
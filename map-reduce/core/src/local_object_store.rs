@@ -0,0 +1,81 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::object_store::{ObjectInfo, ObjectStore};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the directory of the local buckets
+pub const LOCAL_OBJECT_STORE_ROOT_ENV: &str = "LOCAL_OBJECT_STORE_ROOT";
+/// Directory of the local buckets when the environment variable is not set
+pub const DEFAULT_LOCAL_OBJECT_STORE_ROOT: &str = "object-store";
+
+/// Object store mock keeping each bucket in a local directory,
+/// with `/`-separated keys mapped to nested files
+pub struct LocalObjectStore {
+    root: PathBuf,
+}
+
+impl LocalObjectStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Bucket directory under `$LOCAL_OBJECT_STORE_ROOT` (defaults to `object-store`)
+    pub fn from_env(bucket: &str) -> Self {
+        let root = std::env::var(LOCAL_OBJECT_STORE_ROOT_ENV)
+            .unwrap_or_else(|_| DEFAULT_LOCAL_OBJECT_STORE_ROOT.to_string());
+        Self::new(Path::new(&root).join(bucket))
+    }
+
+    fn collect(&self, dir: &Path, objects: &mut Vec<ObjectInfo>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                self.collect(&path, objects)?;
+            } else if let Ok(relative) = path.strip_prefix(&self.root) {
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                objects.push(ObjectInfo {
+                    key,
+                    size: entry.metadata()?.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ObjectStore for LocalObjectStore {
+    fn list(&self, prefix: &str) -> io::Result<Vec<ObjectInfo>> {
+        let mut objects = Vec::new();
+        if self.root.is_dir() {
+            self.collect(&self.root, &mut objects)?;
+        }
+        objects.retain(|object| object.key.starts_with(prefix));
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn get_range(&self, key: &str, offset: u64, length: u64) -> io::Result<Vec<u8>> {
+        let mut file = File::open(self.root.join(key))?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        file.take(length).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn put(&self, key: &str, body: &[u8]) -> io::Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, body)
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::task_key::TaskKey;
+use std::io::{self, IsTerminal};
+use tracing::{info_span, Span};
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable holding the log filter, e.g. `warn` or `info,map_reduce_core::executor=debug`
+pub const LOG_FILTER_ENV: &str = "MAPREDUCE_LOG";
+
+/// Install the log subscriber of a binary, writing to stderr
+/// Filtered by `MAPREDUCE_LOG`, `info` if unset or invalid
+pub fn init() {
+    let filter = EnvFilter::try_from_env(LOG_FILTER_ENV).unwrap_or_else(|_| EnvFilter::new("info"));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_ansi(io::stderr().is_terminal())
+        .with_writer(io::stderr)
+        .try_init();
+}
+
+/// Phase run id as shown in logs, shared by the executor's and the workers' lines
+pub fn phase_run_id(phase: u64) -> String {
+    format!("{:016x}", phase)
+}
+
+/// Span of a task attempt, tagged with its job, phase run and chunk (the task index)
+pub fn task_span(task: Option<TaskKey>) -> Span {
+    match task {
+        Some(task) => info_span!(
+            "task",
+            job_id = %task.job,
+            phase = %phase_run_id(task.phase),
+            chunk = task.index
+        ),
+        None => info_span!("task"),
+    }
+}
@@ -2,45 +2,161 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::config::Config;
+use crate::counters::TaskCounters;
+use crate::input_source::InputSplit;
+use crate::job_context::{BroadcastContext, JobContextOf};
+use crate::partitioner::{HashPartitioner, Partitioner};
+use crate::reduce_output::ReduceOutput;
+use crate::result_order::ResultOrder;
+use crate::result_value::ResultValue;
+use crate::scheduling_policy::TaskSize;
 use crate::state_store::StateStore;
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::ops::Range;
 
 /// Trait that defines a specific MapReduce job
 /// Abstracts the job domain from the execution model
 #[async_trait]
 pub trait MapReduceJob: Send + 'static {
+    /// Name of the job in the `JobKind` registry, passed to worker processes
+    const NAME: &'static str;
+
     /// The input data type for the map phase
     type Input: Send;
 
     /// The assignment type for mappers
-    type MapAssignment: Send + Sync + Clone;
+    /// Sized by its records, or its bytes when read from an input split
+    type MapAssignment: Send + Sync + Clone + TaskSize + BroadcastContext;
 
     /// The assignment type for reducers
-    type ReduceAssignment: Send + Sync + Clone;
+    /// Sized by its keys
+    type ReduceAssignment: Send + Sync + Clone + TaskSize + BroadcastContext;
 
     /// Problem-specific context (e.g., search targets, configuration)
     type Context: Clone + Send;
 
-    /// Create map assignments from input data
+    /// Create the context of a stage before its map phase, from the generated target words
+    /// (ignored by jobs without targets) or e.g. a sample of the stage's input
+    fn create_context(config: &Config, data: &Self::Input, targets: Vec<String>) -> Self::Context;
+
+    /// Keys present in the state before the map phase, reported even if nothing maps to them
+    fn initial_keys(_context: &Self::Context) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Job-wide data of the map tasks, broadcast to every mapper once per phase
+    /// instead of being sent with each assignment, along with the job's side inputs
+    fn map_context(
+        _context: &Self::Context,
+    ) -> <Self::MapAssignment as BroadcastContext>::JobContext {
+        Default::default()
+    }
+
+    /// Fraction of the map tasks run by the sampling pre-pass, against a scratch state
+    /// before the map phase (0 for jobs without a pre-pass)
+    fn sample_fraction(_config: &Config) -> f64 {
+        0.0
+    }
+
+    /// Number of values mapped to a key above which it is split into sub-keys over several reducers
+    /// (None for jobs whose results cannot be merged back with `merge_split_results`)
+    fn hot_key_threshold(config: &Config) -> Option<usize> {
+        config.hot_key_threshold
+    }
+
+    /// Final result of a split hot key from the results of its sub-keys:
+    /// by default the sum of scalars or the concatenation of lists
+    fn merge_split_results(parts: Vec<ResultValue>) -> ResultValue {
+        match parts.first() {
+            Some(ResultValue::List(_)) => ResultValue::List(
+                parts
+                    .into_iter()
+                    .flat_map(|part| match part {
+                        ResultValue::List(values) => values,
+                        ResultValue::Scalar(value) => vec![value],
+                    })
+                    .collect(),
+            ),
+            _ => ResultValue::Scalar(parts.iter().map(ResultValue::weight).sum::<i64>() as i32),
+        }
+    }
+
+    /// Complete the context from the state the sampling pre-pass wrote,
+    /// e.g. with the boundaries of range partitions
+    fn apply_sample(
+        _config: &Config,
+        _context: &mut Self::Context,
+        _sample: &HashMap<String, Vec<i32>>,
+    ) {
+    }
+
+    /// Secondary input hook: complete the context with the records of the smaller input of
+    /// a map-side join (`join_input`), loaded by the coordinator before the map phase,
+    /// e.g. into a table that `map_context` broadcasts to the mappers
+    fn apply_join_input(_config: &Config, _context: &mut Self::Context, _records: Vec<String>) {}
+
+    /// Create one map assignment per chunk of the input data, given as consecutive ranges of
+    /// its records: `partition_size` records each, or sized by adaptive chunk sizing
     fn create_map_assignments(
         data: Self::Input,
         context: Self::Context,
-        partition_size: usize,
+        chunks: Vec<Range<usize>>,
+    ) -> Vec<Self::MapAssignment>;
+
+    /// Create map assignments that read their records from input splits
+    fn create_split_assignments(
+        splits: Vec<InputSplit>,
+        context: Self::Context,
     ) -> Vec<Self::MapAssignment>;
 
-    /// Create reduce assignments from context
+    /// Partitioner assigning the keys found in the state after the map phase to reduce partitions,
+    /// by default by their hash
+    fn partitioner(_context: &Self::Context) -> Box<dyn Partitioner> {
+        Box::new(HashPartitioner)
+    }
+
+    /// Create one reduce assignment per partition, in partition order
     fn create_reduce_assignments(
         context: Self::Context,
-        keys_per_reducer: usize,
+        partitions: Vec<Vec<String>>,
     ) -> Vec<Self::ReduceAssignment>;
 
-    /// Execute map work for a given assignment
-    async fn map_work<S>(assignment: &Self::MapAssignment, state: &S)
-    where
+    /// Combine the values mapped so far for a key into a partial result
+    fn partial_aggregate(values: &[i32]) -> i32 {
+        values.iter().sum()
+    }
+
+    /// Final result of a key from the values its reducer replaced them with, as emitted by the reducer
+    fn result_value(values: &[i32]) -> ResultValue {
+        ResultValue::Scalar(values.first().copied().unwrap_or(0))
+    }
+
+    /// Order of the final results written to the output sinks, in which reducers report theirs
+    fn result_order() -> ResultOrder {
+        ResultOrder::ByWeight
+    }
+
+    /// Execute map work for a given assignment, with the job context of its phase
+    /// `counters` are reported with the task's completion, e.g. the records it processed
+    async fn map_work<S>(
+        assignment: &Self::MapAssignment,
+        context: &JobContextOf<Self::MapAssignment>,
+        state: &S,
+        counters: &TaskCounters,
+    ) where
         S: StateStore;
 
-    /// Execute reduce work for a given assignment
-    async fn reduce_work<S>(assignment: &Self::ReduceAssignment, state: &S)
-    where
+    /// Execute reduce work for a given assignment, with the job context of its phase,
+    /// emitting the final result of each of its keys to `output`,
+    /// which the reducer reports in the job's result order
+    async fn reduce_work<S>(
+        assignment: &Self::ReduceAssignment,
+        context: &JobContextOf<Self::ReduceAssignment>,
+        state: &S,
+        output: &ReduceOutput,
+        counters: &TaskCounters,
+    ) where
         S: StateStore;
 }
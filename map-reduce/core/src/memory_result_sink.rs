@@ -0,0 +1,52 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::reduce_output::KeyResult;
+use crate::result_sink::ResultSink;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Keeps the final results of each stage in memory, for programs embedding a coordinator
+/// and for checking the results of a job in tests
+/// Clones share the results, so a clone kept by the caller reads what the coordinator collected
+#[derive(Clone, Default)]
+pub struct MemoryResultSink {
+    stages: Arc<Mutex<BTreeMap<String, Vec<KeyResult>>>>,
+}
+
+impl MemoryResultSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Final results of a stage, if it was collected
+    pub fn results(&self, stage: &str) -> Option<Vec<KeyResult>> {
+        self.stages.lock().unwrap().get(stage).cloned()
+    }
+
+    /// Final results of every stage collected, by stage name
+    pub fn take(&self) -> BTreeMap<String, Vec<KeyResult>> {
+        std::mem::take(&mut *self.stages.lock().unwrap())
+    }
+}
+
+impl ResultSink for MemoryResultSink {
+    fn collect(&mut self, stage: &str, _config: &Config, results: Vec<KeyResult>) {
+        self.stages
+            .lock()
+            .unwrap()
+            .insert(stage.to_string(), results);
+    }
+
+    /// Nothing to write, the results stay in memory
+    fn report(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn discard(&mut self) {
+        self.stages.lock().unwrap().clear();
+    }
+}
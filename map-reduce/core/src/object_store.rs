@@ -0,0 +1,67 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::local_object_store::LocalObjectStore;
+use crate::s3_object_store::S3ObjectStore;
+use std::io;
+
+/// Object stored in a bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+}
+
+/// Trait for abstracting S3-compatible object storage (a single bucket)
+pub trait ObjectStore: Send + Sync {
+    /// List the objects whose key starts with `prefix`, sorted by key
+    fn list(&self, prefix: &str) -> io::Result<Vec<ObjectInfo>>;
+
+    /// Read `length` bytes of an object starting at `offset`
+    fn get_range(&self, key: &str, offset: u64, length: u64) -> io::Result<Vec<u8>>;
+
+    /// Create or overwrite an object
+    fn put(&self, key: &str, body: &[u8]) -> io::Result<()>;
+}
+
+/// Location of an object or key prefix: `s3://bucket/key` or `local://bucket/key`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectUri {
+    pub scheme: String,
+    pub bucket: String,
+    pub key: String,
+}
+
+impl ObjectUri {
+    /// Parse a URI, returning None for plain file paths
+    pub fn parse(uri: &str) -> Option<Self> {
+        let (scheme, rest) = uri.split_once("://")?;
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return None;
+        }
+        Some(Self {
+            scheme: scheme.to_string(),
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    /// URI of another object in the same bucket
+    pub fn with_key(&self, key: &str) -> String {
+        format!("{}://{}/{}", self.scheme, self.bucket, key)
+    }
+
+    /// Open the store holding this URI's bucket
+    pub fn open_store(&self) -> io::Result<Box<dyn ObjectStore>> {
+        match self.scheme.as_str() {
+            "s3" => Ok(Box::new(S3ObjectStore::from_env(&self.bucket)?)),
+            "local" => Ok(Box::new(LocalObjectStore::from_env(&self.bucket))),
+            scheme => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Unsupported object storage scheme: {}", scheme),
+            )),
+        }
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::input_source::{InputSource, InputSplit, RecordReader, SplitRecords};
+use crate::object_store::{ObjectStore, ObjectUri};
+use std::cmp::{max, min};
+use std::io::{self, BufRead, Cursor, Read};
+use std::sync::Arc;
+use std::thread;
+
+/// Size of the byte ranges a split is downloaded in
+pub const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Maximum number of byte ranges of a split downloaded concurrently
+const MAX_PARALLEL_PARTS: usize = 8;
+
+/// Bytes read at a time when looking for the end of a line
+const LINE_SCAN_SIZE: u64 = 4096;
+
+/// Input source backed by the objects under a prefix of an object storage bucket
+/// Large splits are downloaded as several byte ranges in parallel, or streamed one range at a time
+pub struct ObjectStoreInputSource {
+    uri: ObjectUri,
+    store: Arc<dyn ObjectStore>,
+    part_size: u64,
+}
+
+impl ObjectStoreInputSource {
+    /// Read the objects under a `s3://bucket/prefix` or `local://bucket/prefix` URI
+    pub fn new(uri: &str) -> io::Result<Self> {
+        let uri = ObjectUri::parse(uri).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid object storage URI: {}", uri),
+            )
+        })?;
+        let store = uri.open_store()?;
+        Ok(Self {
+            uri,
+            store: Arc::from(store),
+            part_size: DEFAULT_PART_SIZE,
+        })
+    }
+
+    pub fn with_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = max(part_size, 1);
+        self
+    }
+
+    /// Key of the object a split belongs to
+    fn object_key(split: &InputSplit) -> String {
+        match ObjectUri::parse(&split.path) {
+            Some(uri) => uri.key,
+            None => split.path.clone(),
+        }
+    }
+
+    /// Split a single object, moving each boundary forward to the end of its line
+    fn split_object(&self, key: &str, size: u64, split_size: u64) -> io::Result<Vec<InputSplit>> {
+        let mut splits = Vec::new();
+        let mut start = 0;
+
+        while start < size {
+            let mut end = min(start + split_size, size);
+            if end < size {
+                // Include the rest of the line the tentative boundary falls into
+                end = self.line_end(key, end - 1, size)?;
+            }
+
+            splits.push(InputSplit {
+                path: self.uri.with_key(key),
+                offset: start,
+                length: end - start,
+            });
+            start = end;
+        }
+
+        Ok(splits)
+    }
+
+    /// Offset just past the first newline at or after `from` (or the object size)
+    fn line_end(&self, key: &str, mut from: u64, size: u64) -> io::Result<u64> {
+        while from < size {
+            let chunk = self
+                .store
+                .get_range(key, from, min(LINE_SCAN_SIZE, size - from))?;
+            if chunk.is_empty() {
+                break;
+            }
+            if let Some(newline) = chunk.iter().position(|&b| b == b'\n') {
+                return Ok(from + newline as u64 + 1);
+            }
+            from += chunk.len() as u64;
+        }
+        Ok(size)
+    }
+
+    /// Download a byte range of an object as parts fetched in parallel
+    fn download(
+        store: &dyn ObjectStore,
+        key: &str,
+        offset: u64,
+        length: u64,
+        part_size: u64,
+    ) -> io::Result<Vec<u8>> {
+        if length <= part_size {
+            return store.get_range(key, offset, length);
+        }
+
+        let parts: Vec<(u64, u64)> = (0..length)
+            .step_by(part_size as usize)
+            .map(|start| (offset + start, min(part_size, length - start)))
+            .collect();
+
+        let mut bytes = Vec::with_capacity(length as usize);
+        for batch in parts.chunks(MAX_PARALLEL_PARTS) {
+            let downloaded: Vec<io::Result<Vec<u8>>> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|&(start, len)| scope.spawn(move || store.get_range(key, start, len)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("Part download thread panicked"))
+                    .collect()
+            });
+            for part in downloaded {
+                bytes.extend(part?);
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+impl InputSource for ObjectStoreInputSource {
+    fn splits(&self, split_size: usize) -> io::Result<Vec<InputSplit>> {
+        let split_size = max(split_size, 1) as u64;
+        let mut splits = Vec::new();
+        for object in self.store.list(&self.uri.key)? {
+            splits.extend(self.split_object(&object.key, object.size, split_size)?);
+        }
+        Ok(splits)
+    }
+
+    fn open_split(&self, split: &InputSplit) -> io::Result<RecordReader> {
+        Ok(RecordReader::new(RangeReader {
+            store: self.store.clone(),
+            key: Self::object_key(split),
+            next: split.offset,
+            end: split.offset + split.length,
+            part_size: self.part_size,
+            part: Cursor::new(Vec::new()),
+        }))
+    }
+
+    fn read_split(&self, split: &InputSplit) -> io::Result<SplitRecords> {
+        let bytes = Self::download(
+            self.store.as_ref(),
+            &Self::object_key(split),
+            split.offset,
+            split.length,
+            self.part_size,
+        )?;
+        RecordReader::new(Cursor::new(bytes)).read_all()
+    }
+}
+
+/// Reads a byte range of an object, downloading one part at a time as it is consumed
+struct RangeReader {
+    store: Arc<dyn ObjectStore>,
+    key: String,
+    /// Offset of the next part to download
+    next: u64,
+    end: u64,
+    part_size: u64,
+    part: Cursor<Vec<u8>>,
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.fill_buf()?.read(buf)?;
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for RangeReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.part.position() as usize >= self.part.get_ref().len() && self.next < self.end {
+            let length = min(self.part_size, self.end - self.next);
+            let part = self.store.get_range(&self.key, self.next, length)?;
+            if part.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("{} ended before offset {}", self.key, self.end),
+                ));
+            }
+            self.next += part.len() as u64;
+            self.part = Cursor::new(part);
+        }
+        self.part.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.part.consume(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_object_store::LocalObjectStore;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Bucket directory of its own for each test, removed when dropped
+    struct TempBucket(PathBuf);
+
+    impl TempBucket {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "object-store-input-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            Self(dir)
+        }
+
+        /// Input source over the objects under `prefix`, downloaded in parts of `part_size`
+        fn source(&self, prefix: &str, part_size: u64) -> ObjectStoreInputSource {
+            ObjectStoreInputSource {
+                uri: ObjectUri::parse(&format!("local://bucket/{}", prefix)).unwrap(),
+                store: Arc::new(LocalObjectStore::new(&self.0)),
+                part_size,
+            }
+        }
+
+        fn put(&self, key: &str, body: &[u8]) {
+            LocalObjectStore::new(&self.0).put(key, body).unwrap();
+        }
+    }
+
+    impl Drop for TempBucket {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Lines of varying length, some longer than the splits and parts they are read in
+    fn lines(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| format!("line {} {}", i, "x".repeat(i * 7 % 150)))
+            .collect()
+    }
+
+    fn body(lines: &[String]) -> Vec<u8> {
+        lines
+            .iter()
+            .flat_map(|line| format!("{}\n", line).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn splits_end_on_line_boundaries_and_cover_each_object() {
+        let bucket = TempBucket::new("boundaries");
+        let first = body(&lines(200));
+        let second = body(&lines(50));
+        bucket.put("input/a.txt", &first);
+        bucket.put("input/b.txt", &second);
+        bucket.put("other/c.txt", b"not listed\n");
+
+        let splits = bucket.source("input/", 16).splits(100).unwrap();
+        for (key, object) in [("a.txt", &first), ("b.txt", &second)] {
+            let path = format!("local://bucket/input/{}", key);
+            let mut next = 0;
+            for split in splits.iter().filter(|split| split.path == path) {
+                assert_eq!(split.offset, next);
+                assert_eq!(object[(split.offset + split.length - 1) as usize], b'\n');
+                next += split.length;
+            }
+            assert_eq!(next, object.len() as u64);
+        }
+    }
+
+    #[test]
+    fn reassembles_records_read_in_parallel_parts() {
+        let bucket = TempBucket::new("parallel");
+        let expected = lines(500);
+        bucket.put("input/a.txt", &body(&expected));
+
+        // More parts per split than are downloaded at once, with lines spanning parts
+        let source = bucket.source("input/", 13);
+        let mut records = Vec::new();
+        for split in source.splits(4096).unwrap() {
+            records.extend(source.read_split(&split).unwrap().records);
+        }
+        assert_eq!(records, expected);
+    }
+
+    #[test]
+    fn streams_records_one_part_at_a_time() {
+        let bucket = TempBucket::new("streamed");
+        let expected = lines(300);
+        bucket.put("input/a.txt", &body(&expected));
+
+        let source = bucket.source("input/", 5);
+        let mut records = Vec::new();
+        for split in source.splits(1000).unwrap() {
+            let mut reader = source.open_split(&split).unwrap();
+            records.extend(reader.by_ref());
+            assert_eq!(reader.finish().unwrap(), 0);
+        }
+        assert_eq!(records, expected);
+    }
+
+    #[test]
+    fn downloads_exactly_the_requested_range() {
+        let bucket = TempBucket::new("range");
+        let object = body(&lines(100));
+        bucket.put("a.txt", &object);
+
+        let store = LocalObjectStore::new(&bucket.0);
+        for (offset, length, part_size) in [(0, 1000, 7), (123, 2048, 100), (5, 3, 1)] {
+            let bytes =
+                ObjectStoreInputSource::download(&store, "a.txt", offset, length, part_size)
+                    .unwrap();
+            assert_eq!(bytes, &object[offset as usize..(offset + length) as usize]);
+        }
+    }
+}
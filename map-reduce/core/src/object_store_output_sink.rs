@@ -0,0 +1,37 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::jsonl_output_sink::write_records;
+use crate::object_store::ObjectUri;
+use crate::output_sink::OutputSink;
+use crate::result_value::ResultValue;
+use std::io;
+
+/// Uploads results as a JSONL object to object storage
+pub struct ObjectStoreOutputSink {
+    uri: String,
+}
+
+impl ObjectStoreOutputSink {
+    /// Write to a `s3://bucket/key` or `local://bucket/key` URI
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self { uri: uri.into() }
+    }
+}
+
+impl OutputSink for ObjectStoreOutputSink {
+    fn write(&mut self, results: &[(String, ResultValue)]) -> io::Result<()> {
+        let uri = ObjectUri::parse(&self.uri).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid object storage URI: {}", self.uri),
+            )
+        })?;
+        let mut body = Vec::new();
+        write_records(&mut body, results)?;
+        uri.open_store()?.put(&uri.key, &body)?;
+        println!("Wrote {} results to {}", results.len(), self.uri);
+        Ok(())
+    }
+}
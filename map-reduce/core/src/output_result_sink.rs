@@ -0,0 +1,45 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::{Config, OutputConfig};
+use crate::reduce_output::KeyResult;
+use crate::result_sink::ResultSink;
+use crate::utils::create_output_sinks;
+use std::io;
+
+/// Writes the final results of each stage to the outputs configured for it (stdout, files, objects)
+#[derive(Default)]
+pub struct OutputResultSink {
+    stages: Vec<(Vec<OutputConfig>, Vec<KeyResult>)>,
+}
+
+impl OutputResultSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ResultSink for OutputResultSink {
+    fn collect(&mut self, _stage: &str, config: &Config, results: Vec<KeyResult>) {
+        self.stages.push((config.outputs.clone(), results));
+    }
+
+    /// Write every stage's results to each of its outputs, going on past failed outputs
+    /// Fails with the first output that could not be written
+    fn report(&mut self) -> io::Result<()> {
+        let mut first_error = None;
+        for (outputs, results) in self.stages.drain(..) {
+            for mut sink in create_output_sinks(&outputs) {
+                if let Err(e) = sink.write(&results) {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn discard(&mut self) {
+        self.stages.clear();
+    }
+}
@@ -0,0 +1,13 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::result_value::ResultValue;
+use std::io;
+
+/// Trait for writing the final results of a job once the reduce phase is done
+/// Different implementations for files (JSONL, CSV), stdout, etc.
+pub trait OutputSink {
+    /// Write the final (key, value) pairs, in the job's result order
+    fn write(&mut self, results: &[(String, ResultValue)]) -> io::Result<()>;
+}
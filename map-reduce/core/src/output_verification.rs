@@ -0,0 +1,128 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Diverging keys printed by the verification report, the others are only counted
+const MAX_REPORTED_DIVERGENCES: usize = 10;
+
+/// Key whose values differ between the recorded run of a sampled map task and its re-execution
+#[derive(Debug)]
+pub struct Divergence {
+    /// Index of the map task among the stage's map assignments
+    pub task: usize,
+    pub key: String,
+    pub recorded: Vec<i32>,
+    pub reexecuted: Vec<i32>,
+}
+
+/// Split the map assignments into the sample to verify, `fraction` of them spread evenly
+/// (at least one if `fraction` is positive) along with their index, and the others
+pub fn split_verification_sample<A>(
+    assignments: Vec<A>,
+    fraction: f64,
+) -> (Vec<(usize, A)>, Vec<A>) {
+    if fraction <= 0.0 || assignments.is_empty() {
+        return (Vec::new(), assignments);
+    }
+
+    let count = ((assignments.len() as f64 * fraction).ceil() as usize).clamp(1, assignments.len());
+    let sampled: BTreeSet<usize> = (0..count).map(|i| i * assignments.len() / count).collect();
+    let (sample, others): (Vec<_>, Vec<_>) = assignments
+        .into_iter()
+        .enumerate()
+        .partition(|(index, _)| sampled.contains(index));
+    (sample, others.into_iter().map(|(_, a)| a).collect())
+}
+
+/// Values a single task appended to the state it started from, key by key
+pub fn task_contribution(
+    initial: &HashMap<String, Vec<i32>>,
+    after: HashMap<String, Vec<i32>>,
+) -> HashMap<String, Vec<i32>> {
+    after
+        .into_iter()
+        .filter_map(|(key, mut values)| {
+            let existing = initial.get(&key).map_or(0, Vec::len).min(values.len());
+            values.drain(..existing);
+            (!values.is_empty()).then_some((key, values))
+        })
+        .collect()
+}
+
+/// State the sampled tasks leave when run together: their contributions appended to the
+/// state they started from
+pub fn merge_contributions<'a>(
+    initial: &HashMap<String, Vec<i32>>,
+    contributions: impl IntoIterator<Item = &'a HashMap<String, Vec<i32>>>,
+) -> HashMap<String, Vec<i32>> {
+    let mut merged = initial.clone();
+    for contribution in contributions {
+        for (key, values) in contribution {
+            merged.entry(key.clone()).or_default().extend(values);
+        }
+    }
+    merged
+}
+
+/// Keys whose values differ between two runs of the same map task
+/// Values are compared regardless of their order
+pub fn compare_results(
+    task: usize,
+    recorded: &HashMap<String, Vec<i32>>,
+    reexecuted: &HashMap<String, Vec<i32>>,
+) -> Vec<Divergence> {
+    let keys: BTreeSet<&String> = recorded.keys().chain(reexecuted.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let mut recorded = recorded.get(key).cloned().unwrap_or_default();
+            let mut reexecuted = reexecuted.get(key).cloned().unwrap_or_default();
+            recorded.sort_unstable();
+            reexecuted.sort_unstable();
+            (recorded != reexecuted).then(|| Divergence {
+                task,
+                key: key.clone(),
+                recorded,
+                reexecuted,
+            })
+        })
+        .collect()
+}
+
+/// Print the outcome of the verification of `sampled` map tasks
+pub fn report_verification(sampled: usize, divergences: &[Divergence]) {
+    if divergences.is_empty() {
+        println!(
+            "🔍 Verified {} sampled map tasks: re-executed results match",
+            sampled
+        );
+        return;
+    }
+
+    let tasks: BTreeSet<usize> = divergences.iter().map(|d| d.task).collect();
+    eprintln!(
+        "⚠️  Verification of {} sampled map tasks found {} diverging keys in tasks {:?} \
+         (duplicate or partial writes to the shared state)",
+        sampled,
+        divergences.len(),
+        tasks
+    );
+    for divergence in divergences.iter().take(MAX_REPORTED_DIVERGENCES) {
+        eprintln!(
+            "  - task {}, {}: recorded {} values (sum {}), re-executed {} values (sum {})",
+            divergence.task,
+            divergence.key,
+            divergence.recorded.len(),
+            divergence.recorded.iter().map(|v| *v as i64).sum::<i64>(),
+            divergence.reexecuted.len(),
+            divergence.reexecuted.iter().map(|v| *v as i64).sum::<i64>()
+        );
+    }
+    if divergences.len() > MAX_REPORTED_DIVERGENCES {
+        eprintln!(
+            "  ... and {} more",
+            divergences.len() - MAX_REPORTED_DIVERGENCES
+        );
+    }
+}
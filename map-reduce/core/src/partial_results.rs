@@ -0,0 +1,26 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+/// Per-key aggregates of the map output produced so far
+#[derive(Debug, Clone)]
+pub struct PartialResults {
+    pub completed_tasks: usize,
+    pub total_tasks: usize,
+    /// Partial aggregates, sorted by descending value then key
+    pub aggregates: Vec<(String, i32)>,
+}
+
+/// Whether a phase keeps running after progress was reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamControl {
+    Continue,
+    Stop,
+}
+
+/// Trait for receiving partial results while the map phase is running
+/// Useful for progress dashboards, or to terminate early once a threshold is reached
+pub trait PartialResultsSubscriber: Send {
+    /// Called each time a map task completes
+    fn on_partial_results(&mut self, results: &PartialResults) -> StreamControl;
+}
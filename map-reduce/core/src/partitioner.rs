@@ -0,0 +1,72 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Assigns the keys found in the state after the map phase to reduce partitions
+/// Jobs pick theirs with `MapReduceJob::partitioner`, e.g. to keep ranges of keys together
+pub trait Partitioner: Send + Sync {
+    /// Number of reduce partitions, given the number the config asks for
+    /// (`reduce_partitions`, or enough for `keys_per_reducer` keys each)
+    fn num_partitions(&self, requested: usize) -> usize {
+        requested
+    }
+
+    /// Partition of `key`, below `num_partitions`
+    fn partition(&self, key: &str, num_partitions: usize) -> usize;
+}
+
+/// Default partitioner: spreads the keys over the partitions by their hash
+/// The hash is fixed, so a key lands in the same partition in every run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashPartitioner;
+
+impl Partitioner for HashPartitioner {
+    fn partition(&self, key: &str, num_partitions: usize) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % num_partitions as u64) as usize
+    }
+}
+
+/// Range partitioner: partition `i` holds the keys from `boundaries[i - 1]` (inclusive)
+/// to `boundaries[i]`, so consecutive partitions follow each other in key order
+#[derive(Debug, Clone, Default)]
+pub struct RangePartitioner {
+    boundaries: Vec<String>,
+}
+
+impl RangePartitioner {
+    /// Partitioner for sorted boundaries, e.g. the quantiles of a sampling pre-pass
+    pub fn new(boundaries: Vec<String>) -> Self {
+        Self { boundaries }
+    }
+}
+
+impl Partitioner for RangePartitioner {
+    /// One partition more than there are boundaries, whatever the config asks for
+    fn num_partitions(&self, _requested: usize) -> usize {
+        self.boundaries.len() + 1
+    }
+
+    fn partition(&self, key: &str, _num_partitions: usize) -> usize {
+        self.boundaries
+            .partition_point(|boundary| boundary.as_str() <= key)
+    }
+}
+
+/// Group the sorted keys into `num_partitions` reduce partitions, keeping them sorted in each
+/// Partitions left empty are kept, so a partition's index is the one the partitioner gave
+pub fn partition_keys(
+    partitioner: &dyn Partitioner,
+    keys: Vec<String>,
+    num_partitions: usize,
+) -> Vec<Vec<String>> {
+    let mut partitions = vec![Vec::new(); num_partitions];
+    for key in keys {
+        let partition = partitioner.partition(&key, num_partitions);
+        partitions[partition].push(key);
+    }
+    partitions
+}
@@ -0,0 +1,45 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::counters::Counters;
+use serde::{Deserialize, Serialize};
+
+/// Metrics collected by the executor while running a single phase
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhaseMetrics {
+    /// Stage of the job pipeline the phase belongs to
+    pub stage: String,
+    /// Phase name (Map, Reduce)
+    pub phase: String,
+    pub duration_ms: u64,
+    pub tasks: usize,
+    pub completed_tasks: usize,
+    /// Completed tasks per worker slot
+    pub tasks_per_worker: Vec<usize>,
+    /// Tasks requeued after a failure or timeout
+    pub retries: u32,
+    pub failures: u32,
+    pub timeouts: u32,
+    /// Workers that died while running a task, as opposed to reporting its failure
+    pub crashes: u32,
+    /// Running tasks flagged as much slower than the phase's median and retried
+    pub stragglers: u32,
+    pub dead_letters: usize,
+    /// Completion reports not accepted because their task was already completed or retried,
+    /// e.g. from stragglers or replaced workers, so every task is accounted for exactly once
+    pub duplicates_suppressed: u32,
+    /// Bytes sent to workers through their work channels
+    pub bytes_sent: u64,
+    /// Bytes of the same messages before compression, equal to `bytes_sent` when uncompressed
+    pub raw_bytes_sent: u64,
+    /// Deepest worker queue observed right after delivering an assignment
+    pub max_queue_depth: usize,
+    /// Completed task duration percentiles
+    pub task_p50_ms: u64,
+    pub task_p90_ms: u64,
+    pub task_p99_ms: u64,
+    pub task_max_ms: u64,
+    /// Counters summed over the completed tasks
+    pub counters: Counters,
+}
@@ -0,0 +1,117 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::worker_runtime::WorkerRuntime;
+use std::io;
+use std::marker::PhantomData;
+use std::process::{Command, ExitStatus};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Child;
+use tokio::sync::{oneshot, Mutex};
+
+/// Command line of the child process running a worker task
+pub trait WorkerCommand<Task>: Send + 'static {
+    /// Kind of worker started, e.g. `mapper`, for error messages
+    const ROLE: &'static str;
+
+    fn command(task: &Task) -> Command;
+
+    /// Bytes written to the process's piped stdin once it started, e.g. a secret kept off
+    /// its command line
+    fn stdin_preamble() -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Runs workers as child processes, started with the command `C` builds for their task
+/// The executor sees a process that exited through `exit_reason` and replaces its worker through
+/// the worker factory, which spawns a new process with the slot's next epoch (hence failure schedule)
+pub struct ProcessRuntime<C>(PhantomData<C>);
+
+impl<C> Clone for ProcessRuntime<C> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Worker process, killed when its handle is dropped, e.g. once its worker was replaced
+pub struct WorkerProcess {
+    child: Mutex<Child>,
+    /// Keeps the stdin the preamble was written to open, closing it once dropped
+    _stdin: Option<oneshot::Sender<()>>,
+}
+
+impl Drop for WorkerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.get_mut().start_kill();
+    }
+}
+
+impl WorkerProcess {
+    /// How the process ended, if it already did
+    pub fn exit_reason(&self) -> Option<String> {
+        let status = self.child.try_lock().ok()?.try_wait().ok()??;
+        Some(describe_exit(status))
+    }
+}
+
+impl<C, Task> WorkerRuntime<Task> for ProcessRuntime<C>
+where
+    C: WorkerCommand<Task>,
+    Task: Send + 'static,
+{
+    type Handle = WorkerProcess;
+    type Error = io::Error;
+
+    fn spawn(task: Task) -> Self::Handle {
+        let mut child = tokio::process::Command::from(C::command(&task))
+            .spawn()
+            .unwrap_or_else(|e| panic!("Failed to spawn {} process: {}", C::ROLE, e));
+        let stdin = C::stdin_preamble().and_then(|preamble| {
+            let mut stdin = child.stdin.take()?;
+            let (keep_open, closed) = oneshot::channel::<()>();
+            tokio::spawn(async move {
+                if stdin.write_all(&preamble).await.is_ok() {
+                    let _ = closed.await;
+                }
+            });
+            Some(keep_open)
+        });
+        WorkerProcess {
+            child: Mutex::new(child),
+            _stdin: stdin,
+        }
+    }
+
+    fn exit_reason(handle: &Self::Handle) -> Option<String> {
+        handle.exit_reason()
+    }
+
+    async fn join(handle: Self::Handle) -> Result<(), Self::Error> {
+        let mut child = handle.child.lock().await;
+        child.wait().await.map(|_| ())
+    }
+}
+
+/// Tell a crash apart from a worker that reported a failed task
+fn describe_exit(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        match status.signal() {
+            Some(libc::SIGKILL) => {
+                return "killed by SIGKILL, e.g. by the OOM killer".to_string();
+            }
+            Some(libc::SIGABRT) => {
+                return "aborted, e.g. an allocation beyond its memory limit".to_string();
+            }
+            Some(signal) => return format!("killed by signal {}", signal),
+            None => {}
+        }
+    }
+    match status.code() {
+        Some(code) => format!("process exited with code {}", code),
+        None => "process exited".to_string(),
+    }
+}
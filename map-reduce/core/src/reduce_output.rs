@@ -0,0 +1,137 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::hot_keys::is_sub_key;
+use crate::result_order::ResultOrder;
+use crate::result_value::ResultValue;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// Final result of a key, as emitted by its reducer
+pub type KeyResult = (String, ResultValue);
+
+/// Results a running reduce task emits, reported with its completion
+#[derive(Clone, Default)]
+pub struct ReduceOutput {
+    results: Arc<Mutex<Vec<KeyResult>>>,
+}
+
+impl ReduceOutput {
+    pub fn emit(&self, key: String, value: ResultValue) {
+        self.results.lock().unwrap().push((key, value));
+    }
+
+    /// Results emitted so far, resetting them
+    pub fn take(&self) -> Vec<KeyResult> {
+        mem::take(&mut *self.results.lock().unwrap())
+    }
+}
+
+/// Order of two results in `order`: heaviest first with ties by key, or by key
+pub fn result_ordering(order: ResultOrder, a: &KeyResult, b: &KeyResult) -> Ordering {
+    match order {
+        ResultOrder::ByWeight => b.1.weight().cmp(&a.1.weight()).then_with(|| a.0.cmp(&b.0)),
+        ResultOrder::ByKey => a.0.cmp(&b.0),
+    }
+}
+
+/// Sort the results of a reduce task in `order`, keeping only the first `top_k` if set
+/// The first `top_k` are selected before sorting, so only they are sorted
+/// Results of split hot keys' sub-keys are all kept, to be merged back before the final cut
+pub fn order_results(
+    results: Vec<KeyResult>,
+    order: ResultOrder,
+    top_k: Option<usize>,
+) -> Vec<KeyResult> {
+    let (sub_keys, mut results): (Vec<KeyResult>, Vec<KeyResult>) =
+        results.into_iter().partition(|(key, _)| is_sub_key(key));
+    if let Some(k) = top_k.filter(|&k| k < results.len()) {
+        results.select_nth_unstable_by(k, |a, b| result_ordering(order, a, b));
+        results.truncate(k);
+    }
+    results.extend(sub_keys);
+    results.sort_unstable_by(|a, b| result_ordering(order, a, b));
+    results
+}
+
+/// Next result of a reduce task, the heap's top being the first in the result order
+struct NextResult {
+    result: KeyResult,
+    task: usize,
+    order: ResultOrder,
+}
+
+impl Ord for NextResult {
+    fn cmp(&self, other: &Self) -> Ordering {
+        result_ordering(self.order, &other.result, &self.result)
+            .then_with(|| other.task.cmp(&self.task))
+    }
+}
+
+impl PartialOrd for NextResult {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for NextResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NextResult {}
+
+/// Merge the ordered results of the reduce tasks into the job's final results, the first `top_k`
+/// if set, without sorting them again: a heap bounded by the number of tasks holds the next result
+/// of each task, and the merge stops after `top_k` results
+/// Results of sub-keys do not count towards `top_k`, and those left once it is reached are kept last
+pub fn merge_results(
+    tasks: Vec<Vec<KeyResult>>,
+    order: ResultOrder,
+    top_k: Option<usize>,
+) -> Vec<KeyResult> {
+    let total: usize = tasks.iter().map(Vec::len).sum();
+    let limit = top_k.map_or(total, |k| k.min(total));
+    let mut tasks: Vec<_> = tasks.into_iter().map(Vec::into_iter).collect();
+    let mut heap: BinaryHeap<NextResult> = tasks
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(task, results)| {
+            results.next().map(|result| NextResult {
+                result,
+                task,
+                order,
+            })
+        })
+        .collect();
+
+    let mut merged = Vec::with_capacity(limit);
+    let mut kept = 0;
+    while kept < limit {
+        let Some(next) = heap.pop() else {
+            break;
+        };
+        if let Some(result) = tasks[next.task].next() {
+            heap.push(NextResult {
+                result,
+                task: next.task,
+                order,
+            });
+        }
+        if !is_sub_key(&next.result.0) {
+            kept += 1;
+        }
+        merged.push(next.result);
+    }
+    merged.extend(
+        heap.into_iter()
+            .map(|next| next.result)
+            .chain(tasks.into_iter().flatten())
+            .filter(|(key, _)| is_sub_key(key)),
+    );
+    merged
+}
@@ -2,21 +2,30 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::corrupted_state::{corrupt_results, CorruptedState};
+use crate::counters::{Counters, TaskCounters};
+use crate::failure_injection::{FailureInjector, InjectedFault};
+use crate::heartbeat::HeartbeatHandle;
+use crate::job_context::JobContextOf;
+use crate::kill_switch::ForcedFault;
+use crate::logging::task_span;
 use crate::map_reduce_job::MapReduceJob;
+use crate::reduce_output::{order_results, ReduceOutput};
 use crate::shutdown_signal::ShutdownSignal;
 use crate::state_store::StateStore;
 use crate::status_sender::StatusSender;
-use crate::work_receiver::WorkReceiver;
+use crate::work_receiver::{shutdown_and_drain, WorkReceiver};
 use crate::work_sender::WorkSender;
 use crate::worker_message::WorkerMessage;
 use crate::worker_runtime::{WorkerRuntime, WorkerTask};
 use async_trait::async_trait;
-use rand::Rng;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::marker::PhantomData;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::time::Duration;
+use tracing::{error, info, info_span, warn, Instrument};
 
 #[derive(Serialize, Deserialize)]
 #[serde(
@@ -27,9 +36,10 @@ pub struct ReducerTask<P, S, SD, WR, CS> {
     pub state: S,
     pub shutdown_signal: SD,
     pub work_rx: WR,
-    pub failure_probability: u32,
-    pub straggler_probability: u32,
-    pub straggler_delay_ms: u64,
+    pub failures: FailureInjector,
+    /// Results each task reports at most, the first in the job's result order (None = all)
+    #[serde(default)]
+    pub top_k: Option<usize>,
     #[serde(skip)]
     _phantom: PhantomData<(P, CS)>,
 }
@@ -45,68 +55,63 @@ where
 {
     type Output = ();
 
-    async fn run(mut self) -> Self::Output {
+    async fn run(self) -> Self::Output {
+        let span = info_span!("reducer", worker_id = self.id);
+        self.serve().instrument(span).await
+    }
+}
+
+impl<P, S, SD, WR, CS> ReducerTask<P, S, SD, WR, CS>
+where
+    P: MapReduceJob,
+    S: StateStore + Send + Sync + 'static,
+    SD: ShutdownSignal + Send + 'static,
+    WR: WorkReceiver<P::ReduceAssignment, CS> + 'static,
+    CS: StatusSender + 'static,
+{
+    async fn serve(mut self) {
+        let mut _heartbeat = None;
+        // Job context of the phase the reducer last registered for
+        let mut context = JobContextOf::<P::ReduceAssignment>::default();
+
         loop {
             // Check for shutdown
             if self.shutdown_signal.is_cancelled() {
                 break;
             }
+            if self.failures.kill_switch().forced() == Some(ForcedFault::Crash) {
+                error!("💀 Reducer crashed on demand!");
+                return;
+            }
 
             // Try to receive work with timeout
             let work_result =
                 tokio::time::timeout(Duration::from_millis(100), self.work_rx.recv()).await;
 
             match work_result {
-                Ok(Some(WorkerMessage::Initialize(token))) => {
+                Ok(Some(WorkerMessage::Initialize(token, phase_context))) => {
+                    context = phase_context;
                     if token.register(self.id).await {
-                        println!("Reducer {} registered", self.id);
+                        info!("Reducer registered");
                     } else {
-                        eprintln!("Reducer {} failed to register", self.id);
-                    }
-                }
-                Ok(Some(WorkerMessage::Work(assignment, completion_sender))) => {
-                    // Simulate random failure
-                    if self.failure_probability > 0 {
-                        let random_value = rand::rng().random_range(0..100);
-                        if random_value < self.failure_probability {
-                            eprintln!("💥 Reducer {} simulated failure!", self.id);
-                            completion_sender.send(Err(())).await;
-                            continue;
-                        }
+                        error!("Reducer failed to register");
                     }
 
-                    // Simulate straggler
-                    if self.straggler_probability > 0 {
-                        let random_value = rand::rng().random_range(0..100);
-                        if random_value < self.straggler_probability {
-                            let delay = rand::rng().random_range(1..=self.straggler_delay_ms);
-                            eprintln!(
-                                "🐌 Reducer {} is a straggler! Delaying {}ms",
-                                self.id, delay
-                            );
-                            tokio::time::sleep(Duration::from_millis(delay)).await;
-                        }
+                    // Keep the coordinator informed that this worker is alive
+                    if let Some(interval) = token.heartbeat_interval() {
+                        _heartbeat = Some(HeartbeatHandle::spawn(self.id, token.clone(), interval));
                     }
-
-                    // Execute work with error handling
-                    let state = &self.state;
-                    let result = catch_unwind(AssertUnwindSafe(|| async {
-                        P::reduce_work(&assignment, state).await;
-                    }));
-
-                    match result {
-                        Ok(future) => {
-                            future.await;
-                            if completion_sender.send(Ok(self.id)).await {
-                                println!("Reducer {} finished work", self.id);
-                            } else {
-                                // Failed to send completion - likely a zombie worker
-                            }
-                        }
-                        Err(_) => {
-                            eprintln!("❌ Reducer {} panicked during work!", self.id);
-                            let _ = completion_sender.send(Err(())).await;
-                        }
+                }
+                Ok(Some(WorkerMessage::Work(assignment, completion_sender))) => {
+                    let span = task_span(completion_sender.task());
+                    let alive = self
+                        .process(assignment, &context, completion_sender)
+                        .instrument(span)
+                        .await;
+                    if !alive {
+                        // A simulated crash ends the worker at once, reporting nothing and
+                        // leaving its queued work, as a dead process would
+                        return;
                     }
                 }
                 Ok(None) => {
@@ -118,6 +123,109 @@ where
                 }
             }
         }
+
+        // Stop listening, so a worker process outliving the job doesn't hold its port
+        let discarded = shutdown_and_drain(&mut self.work_rx).await;
+        if discarded > 0 {
+            warn!(
+                discarded,
+                "Reducer discarded queued assignments on shutdown"
+            );
+        }
+    }
+
+    /// Run a reduce task and report its outcome
+    /// Returns false if the worker was made to crash
+    async fn process(
+        &mut self,
+        assignment: P::ReduceAssignment,
+        context: &JobContextOf<P::ReduceAssignment>,
+        completion_sender: CS,
+    ) -> bool {
+        // Simulate a failure or a straggler, as drawn from the worker's failure seed
+        let mut wrong_result = false;
+        match self.failures.next_fault() {
+            Some(InjectedFault::Failure) => {
+                warn!(
+                    failure_seed = self.failures.seed(),
+                    "💥 Reducer simulated failure!"
+                );
+                completion_sender
+                    .send(Err(()), Counters::default(), Vec::new())
+                    .await;
+                return true;
+            }
+            Some(InjectedFault::Crash) => {
+                error!(
+                    failure_seed = self.failures.seed(),
+                    "💀 Reducer simulated crash!"
+                );
+                return false;
+            }
+            Some(InjectedFault::WrongResult) => {
+                warn!(
+                    failure_seed = self.failures.seed(),
+                    "🎭 Reducer simulated wrong result!"
+                );
+                wrong_result = true;
+            }
+            Some(InjectedFault::Straggler(delay)) => {
+                warn!(delay_ms = delay, "🐌 Reducer is a straggler!");
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+            Some(InjectedFault::Hang) => {
+                warn!("🧊 Reducer hanging on demand!");
+                let kill_switch = self.failures.kill_switch();
+                kill_switch.released().await;
+                match kill_switch.forced() {
+                    Some(ForcedFault::Crash) => {
+                        error!("💀 Reducer crashed on demand!");
+                        return false;
+                    }
+                    Some(ForcedFault::CorruptResults) => wrong_result = true,
+                    _ => {}
+                }
+            }
+            None => {}
+        }
+
+        // Execute work with error handling
+        let state = &self.state;
+        let output = ReduceOutput::default();
+        let counters = TaskCounters::default();
+        let result = catch_unwind(AssertUnwindSafe(|| async {
+            if wrong_result {
+                let corrupted = CorruptedState::new(state.clone());
+                P::reduce_work(&assignment, context, &corrupted, &output, &counters).await;
+            } else {
+                P::reduce_work(&assignment, context, state, &output, &counters).await;
+            }
+        }));
+
+        match result {
+            Ok(future) => {
+                future.await;
+                let mut results = order_results(output.take(), P::result_order(), self.top_k);
+                if wrong_result {
+                    results = corrupt_results(results);
+                }
+                if completion_sender
+                    .send(Ok(self.id), counters.take(), results)
+                    .await
+                {
+                    info!("Reducer finished work");
+                } else {
+                    // Failed to send completion - likely a zombie worker
+                }
+            }
+            Err(_) => {
+                error!("❌ Reducer panicked during work!");
+                let _ = completion_sender
+                    .send(Err(()), Counters::default(), Vec::new())
+                    .await;
+            }
+        }
+        true
     }
 }
 
@@ -147,25 +255,22 @@ where
     WR: WorkReceiver<P::ReduceAssignment, CS> + 'static,
     CS: StatusSender + 'static,
 {
-    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: usize,
         state: S,
         shutdown_signal: SD,
         work_rx: WR,
         work_channel: W,
-        failure_probability: u32,
-        straggler_probability: u32,
-        straggler_delay_ms: u64,
+        failures: FailureInjector,
+        top_k: Option<usize>,
     ) -> Self {
         let task = ReducerTask {
             id,
             state,
             shutdown_signal,
             work_rx,
-            failure_probability,
-            straggler_probability,
-            straggler_delay_ms,
+            failures,
+            top_k,
             _phantom: PhantomData,
         };
 
@@ -193,16 +298,38 @@ where
     WR: WorkReceiver<P::ReduceAssignment, CS> + 'static,
     CS: StatusSender + 'static,
 {
+    const PHASE: &'static str = "reduce";
+
     type Assignment = P::ReduceAssignment;
     type Completion = CS;
     type Error = R::Error;
 
-    fn initialize(&self, token: Self::Completion) {
-        self.work_channel.initialize(token);
+    fn initialize(&self, token: Self::Completion, context: JobContextOf<Self::Assignment>) {
+        self.work_channel.initialize(token, context);
+    }
+
+    fn send_work(
+        &self,
+        assignment: Self::Assignment,
+        complete_tx: Self::Completion,
+    ) -> impl Future<Output = Result<(), String>> + Send {
+        self.work_channel.send_work(assignment, complete_tx)
+    }
+
+    fn bytes_sent(&self) -> u64 {
+        self.work_channel.bytes_sent()
+    }
+
+    fn raw_bytes_sent(&self) -> u64 {
+        self.work_channel.raw_bytes_sent()
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.work_channel.queue_depth()
     }
 
-    fn send_work(&self, assignment: Self::Assignment, complete_tx: Self::Completion) {
-        self.work_channel.send_work(assignment, complete_tx);
+    fn exit_reason(&self) -> Option<String> {
+        R::exit_reason(&self.task_handle).or_else(|| self.work_channel.unhealthy_reason())
     }
 
     async fn wait(self) -> Result<(), Self::Error> {
@@ -0,0 +1,13 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+/// Order in which a job's final results are written to the output sinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResultOrder {
+    /// Heaviest first (largest scalar or longest list), ties by key
+    #[default]
+    ByWeight,
+    /// Ascending key, e.g. for a sort job
+    ByKey,
+}
@@ -0,0 +1,24 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::reduce_output::KeyResult;
+use std::io;
+
+/// Consumer of a job's final results, invoked by the coordinator of every variant:
+/// each final stage's merged results are collected once its reduce phase is done,
+/// and the report is written once every stage of the job ran
+/// Different implementations for the configured outputs, in-memory collection, etc.
+pub trait ResultSink: Send {
+    /// Take the final results of a stage, in the job's result order,
+    /// with the configuration the stage ran with
+    fn collect(&mut self, stage: &str, config: &Config, results: Vec<KeyResult>);
+
+    /// Write the report of the results collected for the job
+    fn report(&mut self) -> io::Result<()>;
+
+    /// Drop the results collected for a job that failed, e.g. with dead-lettered tasks,
+    /// writing none of them
+    fn discard(&mut self);
+}
@@ -0,0 +1,39 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Final value of a key, built by the job from the values its reducer left in the state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResultValue {
+    /// Single aggregate, e.g. a count
+    Scalar(i32),
+    /// Ordered list, e.g. the documents containing a word
+    List(Vec<i32>),
+}
+
+impl ResultValue {
+    /// Weight results are ranked by: the scalar itself, or the length of the list
+    pub fn weight(&self) -> i64 {
+        match self {
+            ResultValue::Scalar(value) => *value as i64,
+            ResultValue::List(values) => values.len() as i64,
+        }
+    }
+}
+
+/// Scalars as numbers, lists as space-separated numbers
+impl fmt::Display for ResultValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResultValue::Scalar(value) => write!(f, "{}", value),
+            ResultValue::List(values) => {
+                let values: Vec<String> = values.iter().map(i32::to_string).collect();
+                f.write_str(&values.join(" "))
+            }
+        }
+    }
+}
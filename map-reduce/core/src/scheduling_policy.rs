@@ -0,0 +1,410 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::failure_domains::FailureDomains;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// Amount of work in an assignment, e.g. its records or keys, for policies ordering tasks by size
+pub trait TaskSize {
+    fn task_size(&self) -> u64;
+}
+
+/// Order in which the executor hands pending tasks to idle workers
+pub trait SchedulingPolicy: Send {
+    /// Start a phase, queueing every task; `sizes` holds the size of each task by index
+    fn start(&mut self, sizes: &[u64]);
+
+    /// Queue a task again after a failed attempt
+    fn retry(&mut self, task_index: usize);
+
+    /// Next task to assign, None if none is pending
+    fn next_task(&mut self) -> Option<usize>;
+
+    /// Next task to assign to an idle worker slot, for policies preferring some tasks per worker
+    fn next_task_for(&mut self, _worker_id: usize) -> Option<usize> {
+        self.next_task()
+    }
+
+    /// A worker slot completed a task
+    fn task_completed(&mut self, _task_index: usize, _worker_id: usize) {}
+
+    /// A task failed or timed out on a worker slot, before it is queued again
+    fn task_failed(&mut self, _task_index: usize, _worker_id: usize) {}
+
+    /// The worker of a slot was replaced, e.g. after it failed or straggled
+    fn worker_replaced(&mut self, _worker_id: usize) {}
+
+    /// Keep a task of the current phase off a worker slot, once the phase started
+    fn exclude(&mut self, _task_index: usize, _worker_id: usize) {}
+}
+
+/// Built-in scheduling policies, selected with the `scheduling_policy` setting
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SchedulingPolicyKind {
+    /// Tasks in index order, retries after the tasks already pending
+    #[default]
+    Fifo,
+    /// Largest tasks first, so no long task starts last and holds up the end of the phase
+    LargestFirst,
+    /// Retries before the tasks not attempted yet, so failed tasks don't finish last
+    RetriesFirst,
+}
+
+impl SchedulingPolicyKind {
+    pub fn create(&self) -> Box<dyn SchedulingPolicy> {
+        match self {
+            SchedulingPolicyKind::Fifo => Box::new(FifoPolicy::default()),
+            SchedulingPolicyKind::LargestFirst => Box::new(LargestFirstPolicy::default()),
+            SchedulingPolicyKind::RetriesFirst => Box::new(RetriesFirstPolicy::default()),
+        }
+    }
+}
+
+impl fmt::Display for SchedulingPolicyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SchedulingPolicyKind::Fifo => "fifo",
+            SchedulingPolicyKind::LargestFirst => "largest-first",
+            SchedulingPolicyKind::RetriesFirst => "retries-first",
+        })
+    }
+}
+
+/// Tasks in index order, retries queued behind them
+#[derive(Default)]
+pub struct FifoPolicy {
+    pending: VecDeque<usize>,
+}
+
+impl SchedulingPolicy for FifoPolicy {
+    fn start(&mut self, sizes: &[u64]) {
+        self.pending = (0..sizes.len()).collect();
+    }
+
+    fn retry(&mut self, task_index: usize) {
+        self.pending.push_back(task_index);
+    }
+
+    fn next_task(&mut self) -> Option<usize> {
+        self.pending.pop_front()
+    }
+}
+
+/// Largest pending task first, retries included; ties in index order
+#[derive(Default)]
+pub struct LargestFirstPolicy {
+    sizes: Vec<u64>,
+    pending: BinaryHeap<(u64, Reverse<usize>)>,
+}
+
+impl SchedulingPolicy for LargestFirstPolicy {
+    fn start(&mut self, sizes: &[u64]) {
+        self.sizes = sizes.to_vec();
+        self.pending = sizes
+            .iter()
+            .enumerate()
+            .map(|(task_index, size)| (*size, Reverse(task_index)))
+            .collect();
+    }
+
+    fn retry(&mut self, task_index: usize) {
+        self.pending
+            .push((self.sizes[task_index], Reverse(task_index)));
+    }
+
+    fn next_task(&mut self) -> Option<usize> {
+        self.pending
+            .pop()
+            .map(|(_, Reverse(task_index))| task_index)
+    }
+}
+
+/// Retried tasks ahead of the tasks not attempted yet, which follow in index order
+#[derive(Default)]
+pub struct RetriesFirstPolicy {
+    pending: VecDeque<usize>,
+}
+
+impl SchedulingPolicy for RetriesFirstPolicy {
+    fn start(&mut self, sizes: &[u64]) {
+        self.pending = (0..sizes.len()).collect();
+    }
+
+    fn retry(&mut self, task_index: usize) {
+        self.pending.push_front(task_index);
+    }
+
+    fn next_task(&mut self) -> Option<usize> {
+        self.pending.pop_front()
+    }
+}
+
+/// Sends each task back to the worker slot that last completed it, e.g. the partition a reducer
+/// ran in the previous stage or job, and orders the other tasks with the wrapped policy
+/// A worker with none of its own tasks pending takes the wrapped policy's next task, then the
+/// tasks of the slot with the most of them left; the tasks of a replaced worker lose their owner
+pub struct AffinityPolicy {
+    inner: Box<dyn SchedulingPolicy>,
+    /// Worker slot that last completed each task
+    owners: HashMap<usize, usize>,
+    /// Pending tasks of each slot in the current phase
+    owned: BTreeMap<usize, VecDeque<usize>>,
+    /// Entries the wrapped policy holds for tasks served from `owned` instead, skipped
+    skipped: Vec<u32>,
+}
+
+impl AffinityPolicy {
+    pub fn new(inner: Box<dyn SchedulingPolicy>) -> Self {
+        Self {
+            inner,
+            owners: HashMap::new(),
+            owned: BTreeMap::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Next task of the wrapped policy that no slot owns
+    fn next_unowned(&mut self) -> Option<usize> {
+        while let Some(task_index) = self.inner.next_task() {
+            if self.skipped[task_index] == 0 {
+                return Some(task_index);
+            }
+            self.skipped[task_index] -= 1;
+        }
+        None
+    }
+
+    /// Last pending task of the slot with the most of them left
+    fn steal(&mut self) -> Option<usize> {
+        self.owned
+            .values_mut()
+            .max_by_key(|tasks| tasks.len())?
+            .pop_back()
+    }
+}
+
+impl SchedulingPolicy for AffinityPolicy {
+    fn start(&mut self, sizes: &[u64]) {
+        self.inner.start(sizes);
+        self.skipped = vec![0; sizes.len()];
+        self.owned.clear();
+
+        let mut owned: Vec<(usize, usize)> = self
+            .owners
+            .iter()
+            .filter(|(task_index, _)| **task_index < sizes.len())
+            .map(|(task_index, worker_id)| (*task_index, *worker_id))
+            .collect();
+        owned.sort_unstable();
+        for (task_index, worker_id) in owned {
+            self.owned
+                .entry(worker_id)
+                .or_default()
+                .push_back(task_index);
+            self.skipped[task_index] = 1;
+        }
+    }
+
+    fn retry(&mut self, task_index: usize) {
+        self.inner.retry(task_index);
+    }
+
+    fn next_task(&mut self) -> Option<usize> {
+        self.next_unowned().or_else(|| self.steal())
+    }
+
+    fn next_task_for(&mut self, worker_id: usize) -> Option<usize> {
+        match self.owned.get_mut(&worker_id).and_then(VecDeque::pop_front) {
+            Some(task_index) => Some(task_index),
+            None => self.next_task(),
+        }
+    }
+
+    fn task_completed(&mut self, task_index: usize, worker_id: usize) {
+        self.owners.insert(task_index, worker_id);
+    }
+
+    fn task_failed(&mut self, task_index: usize, worker_id: usize) {
+        self.inner.task_failed(task_index, worker_id);
+    }
+
+    fn worker_replaced(&mut self, worker_id: usize) {
+        // Its pending tasks go to any worker, in the wrapped policy's order
+        self.owners.retain(|_, owner| *owner != worker_id);
+        for task_index in self.owned.remove(&worker_id).unwrap_or_default() {
+            self.inner.retry(task_index);
+        }
+    }
+}
+
+/// Keeps retried tasks away from the failure domains they failed in, ordering tasks with the
+/// wrapped policy
+/// A worker whose domain a task failed in takes the next task that did not fail there instead,
+/// holding the skipped one for a worker of another domain; with nothing else pending it takes
+/// the held task anyway, so no task waits for a domain that is not available
+pub struct FailureDomainPolicy {
+    inner: Box<dyn SchedulingPolicy>,
+    domains: FailureDomains,
+    /// Domains each task of the current phase failed in
+    failed_in: HashMap<usize, HashSet<String>>,
+    /// Tasks taken from the wrapped policy but skipped, in its order
+    held: VecDeque<usize>,
+}
+
+impl FailureDomainPolicy {
+    pub fn new(inner: Box<dyn SchedulingPolicy>, domains: FailureDomains) -> Self {
+        Self {
+            inner,
+            domains,
+            failed_in: HashMap::new(),
+            held: VecDeque::new(),
+        }
+    }
+
+    /// Held tasks first, then the wrapped policy's choice for the worker
+    fn next_task_for_any(&mut self, worker_id: usize) -> Option<usize> {
+        self.held
+            .pop_front()
+            .or_else(|| self.inner.next_task_for(worker_id))
+    }
+
+    fn failed_in(&self, task_index: usize, domain: &str) -> bool {
+        self.failed_in
+            .get(&task_index)
+            .is_some_and(|domains| domains.contains(domain))
+    }
+}
+
+impl SchedulingPolicy for FailureDomainPolicy {
+    fn start(&mut self, sizes: &[u64]) {
+        self.inner.start(sizes);
+        self.failed_in.clear();
+        self.held.clear();
+    }
+
+    fn retry(&mut self, task_index: usize) {
+        self.inner.retry(task_index);
+    }
+
+    fn next_task(&mut self) -> Option<usize> {
+        self.held.pop_front().or_else(|| self.inner.next_task())
+    }
+
+    fn next_task_for(&mut self, worker_id: usize) -> Option<usize> {
+        if self.failed_in.is_empty() || !self.domains.spans_several() {
+            return self.next_task_for_any(worker_id);
+        }
+
+        let domain = self.domains.domain_of(worker_id);
+        if let Some(position) = self
+            .held
+            .iter()
+            .position(|task_index| !self.failed_in(*task_index, &domain))
+        {
+            return self.held.remove(position);
+        }
+        while let Some(task_index) = self.inner.next_task_for(worker_id) {
+            if !self.failed_in(task_index, &domain) {
+                return Some(task_index);
+            }
+            self.held.push_back(task_index);
+        }
+        self.held.pop_front()
+    }
+
+    fn task_completed(&mut self, task_index: usize, worker_id: usize) {
+        self.inner.task_completed(task_index, worker_id);
+    }
+
+    fn task_failed(&mut self, task_index: usize, worker_id: usize) {
+        self.failed_in
+            .entry(task_index)
+            .or_default()
+            .insert(self.domains.domain_of(worker_id));
+        self.inner.task_failed(task_index, worker_id);
+    }
+
+    fn worker_replaced(&mut self, worker_id: usize) {
+        self.inner.worker_replaced(worker_id);
+    }
+}
+
+/// Keeps tasks off the worker slots they are excluded from, e.g. a re-executed task off the
+/// worker that recorded it, ordering tasks with the wrapped policy
+/// An excluded slot takes the next task it is not excluded from, holding the skipped one for
+/// another slot; exclusions only hold for the phase they were made in
+pub struct ExclusionPolicy {
+    inner: Box<dyn SchedulingPolicy>,
+    /// Worker slot each task of the current phase is kept off
+    excluded: HashMap<usize, usize>,
+    /// Tasks taken from the wrapped policy but skipped, in its order
+    held: VecDeque<usize>,
+}
+
+impl ExclusionPolicy {
+    pub fn new(inner: Box<dyn SchedulingPolicy>) -> Self {
+        Self {
+            inner,
+            excluded: HashMap::new(),
+            held: VecDeque::new(),
+        }
+    }
+
+    fn excluded(&self, task_index: usize, worker_id: usize) -> bool {
+        self.excluded.get(&task_index) == Some(&worker_id)
+    }
+}
+
+impl SchedulingPolicy for ExclusionPolicy {
+    fn start(&mut self, sizes: &[u64]) {
+        self.inner.start(sizes);
+        self.excluded.clear();
+        self.held.clear();
+    }
+
+    fn retry(&mut self, task_index: usize) {
+        self.inner.retry(task_index);
+    }
+
+    fn next_task(&mut self) -> Option<usize> {
+        self.held.pop_front().or_else(|| self.inner.next_task())
+    }
+
+    fn next_task_for(&mut self, worker_id: usize) -> Option<usize> {
+        if let Some(position) = self
+            .held
+            .iter()
+            .position(|task_index| !self.excluded(*task_index, worker_id))
+        {
+            return self.held.remove(position);
+        }
+        while let Some(task_index) = self.inner.next_task_for(worker_id) {
+            if !self.excluded(task_index, worker_id) {
+                return Some(task_index);
+            }
+            self.held.push_back(task_index);
+        }
+        None
+    }
+
+    fn task_completed(&mut self, task_index: usize, worker_id: usize) {
+        self.inner.task_completed(task_index, worker_id);
+    }
+
+    fn task_failed(&mut self, task_index: usize, worker_id: usize) {
+        self.inner.task_failed(task_index, worker_id);
+    }
+
+    fn worker_replaced(&mut self, worker_id: usize) {
+        self.inner.worker_replaced(worker_id);
+    }
+
+    fn exclude(&mut self, task_index: usize, worker_id: usize) {
+        self.excluded.insert(task_index, worker_id);
+    }
+}
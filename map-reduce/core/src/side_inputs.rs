@@ -0,0 +1,77 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::Config;
+use crate::utils::read_all_records;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::sync::Arc;
+
+/// Named, read-only inputs of a job besides its map input, e.g. lookup tables or stop-word lists
+/// Loaded once by the coordinator and broadcast to every worker with the job context of each phase;
+/// clones share the records, so in-process workers do not copy them
+#[derive(Debug, Clone, Default)]
+pub struct SideInputs {
+    inputs: Arc<BTreeMap<String, Vec<String>>>,
+}
+
+impl SideInputs {
+    /// Read every side input the config names (`side_inputs`), files or object storage prefixes
+    pub fn load(config: &Config) -> io::Result<Self> {
+        let mut inputs = BTreeMap::new();
+        for (name, path) in &config.side_inputs {
+            let records = read_all_records(path, config.split_size_bytes)?;
+            println!(
+                "Loaded side input '{}': {} records from {}",
+                name,
+                records.len(),
+                path
+            );
+            inputs.insert(name.clone(), records);
+        }
+        Ok(Self {
+            inputs: Arc::new(inputs),
+        })
+    }
+
+    /// Add or replace a side input
+    pub fn insert(&mut self, name: impl Into<String>, records: Vec<String>) {
+        Arc::make_mut(&mut self.inputs).insert(name.into(), records);
+    }
+
+    /// Records of a side input, None if the job has no side input of that name
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.inputs.get(name).map(Vec::as_slice)
+    }
+
+    /// Distinct records of a side input, e.g. to look words up in a stop-word list
+    /// Empty if the job has no side input of that name
+    pub fn set(&self, name: &str) -> HashSet<&str> {
+        self.get(name)
+            .unwrap_or_default()
+            .iter()
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+// Serialized as the map of records, without the sharing
+impl Serialize for SideInputs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inputs.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SideInputs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BTreeMap::deserialize(deserializer).map(|inputs| Self {
+            inputs: Arc::new(inputs),
+        })
+    }
+}
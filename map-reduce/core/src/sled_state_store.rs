@@ -0,0 +1,205 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::in_memory_state_store::SharedStateMap;
+use crate::job_id::JobId;
+use crate::state_store::StateStore;
+use crate::task_key::TaskKey;
+use async_trait::async_trait;
+use sled::Transactional;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// State persisted to a sled database, so a restarted state server keeps its accumulated values
+/// Reads are served from an in-memory copy, every write goes to both
+#[derive(Clone)]
+pub struct SledStateStore {
+    map: SharedStateMap,
+    db: sled::Db,
+    /// Keys of the tasks whose updates were committed, persisted with the values
+    committed: sled::Tree,
+    /// Job whose task commits are accepted, any job's until one begins
+    job: Arc<Mutex<Option<JobId>>>,
+}
+
+impl SledStateStore {
+    /// Open (or create) the database at `path`, loading the values it already holds
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let db = sled::open(path.as_ref()).map_err(io::Error::other)?;
+        // Updates append the little-endian bytes of the value to the key's list
+        db.set_merge_operator(|_key: &[u8], old: Option<&[u8]>, appended: &[u8]| {
+            let mut values = old.map(<[u8]>::to_vec).unwrap_or_default();
+            values.extend_from_slice(appended);
+            Some(values)
+        });
+
+        let committed = db
+            .open_tree(COMMITTED_TASKS_TREE)
+            .map_err(io::Error::other)?;
+
+        let mut map = HashMap::new();
+        for entry in db.iter() {
+            let (key, values) = entry.map_err(io::Error::other)?;
+            map.insert(String::from_utf8_lossy(&key).into_owned(), decode(&values));
+        }
+
+        Ok(Self {
+            map: Arc::new(Mutex::new(map)),
+            db,
+            committed,
+            job: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Get the underlying map for result extraction
+    pub fn get_map(&self) -> SharedStateMap {
+        self.map.clone()
+    }
+
+    /// Number of keys recovered or written so far
+    pub fn len(&self) -> usize {
+        self.map.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all keys and committed tasks, e.g. before running the next stage of a pipeline
+    pub fn clear(&self) {
+        let mut map = self.map.lock().unwrap();
+        map.clear();
+        self.db.clear().expect("Failed to clear persisted state");
+        self.committed
+            .clear()
+            .expect("Failed to clear persisted state");
+    }
+
+    /// Start a job on a clear state, discarding the commits of other jobs' tasks from now on,
+    /// e.g. late ones of the previous job of a resident worker pool
+    pub fn begin_job(&self, job: JobId) {
+        self.clear();
+        *self.job.lock().unwrap() = Some(job);
+    }
+
+    /// Copy of all keys and values, e.g. to checkpoint the state at a phase boundary
+    pub fn snapshot(&self) -> HashMap<String, Vec<i32>> {
+        self.map.lock().unwrap().clone()
+    }
+
+    /// Replace all keys and values with a previously taken snapshot
+    pub fn restore(&self, snapshot: HashMap<String, Vec<i32>>) {
+        let mut map = self.map.lock().unwrap();
+        self.db.clear().expect("Failed to clear persisted state");
+        for (key, values) in &snapshot {
+            self.db
+                .insert(key.as_bytes(), encode(values))
+                .expect("Failed to persist state");
+        }
+        *map = snapshot;
+    }
+
+    /// Wait until every write so far is durable on disk
+    pub async fn flush(&self) -> io::Result<()> {
+        self.db.flush_async().await.map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+/// Tree of the committed task keys, apart from the default tree holding the values
+const COMMITTED_TASKS_TREE: &str = "committed_tasks";
+
+fn encode(values: &[i32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn decode(bytes: &[u8]) -> Vec<i32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+#[async_trait]
+impl StateStore for SledStateStore {
+    async fn update(&self, key: String, value: i32) {
+        let mut map = self.map.lock().unwrap();
+        self.db
+            .merge(key.as_bytes(), value.to_le_bytes())
+            .expect("Failed to persist state update");
+        map.entry(key).or_default().push(value);
+    }
+
+    /// The task's values and its key are written in one transaction,
+    /// so a crash leaves either all of them or none
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>) {
+        if self.job.lock().unwrap().is_some_and(|job| job != task.job) {
+            warn!(job_id = %task.job, "Discarded the updates of another job's task");
+            return;
+        }
+        let mut map = self.map.lock().unwrap();
+        let marker = task.to_bytes();
+        if self
+            .committed
+            .contains_key(marker)
+            .expect("Failed to read persisted state")
+        {
+            return;
+        }
+
+        let mut appended: HashMap<String, Vec<i32>> = HashMap::new();
+        for (key, value) in updates {
+            appended.entry(key).or_default().push(value);
+        }
+        let mut merged: HashMap<String, Vec<i32>> = HashMap::new();
+        let mut batch = sled::Batch::default();
+        for (key, values) in appended {
+            let mut all = map.get(&key).cloned().unwrap_or_default();
+            all.extend(values);
+            batch.insert(key.as_bytes(), encode(&all));
+            merged.insert(key, all);
+        }
+
+        (&*self.db, &self.committed)
+            .transaction(|(data, committed)| {
+                data.apply_batch(&batch)?;
+                committed.insert(&marker, &[])?;
+                Ok::<_, sled::transaction::ConflictableTransactionError<()>>(())
+            })
+            .expect("Failed to persist task updates");
+        map.extend(merged);
+    }
+
+    async fn replace(&self, key: String, values: Vec<i32>) {
+        let mut map = self.map.lock().unwrap();
+        self.db
+            .insert(key.as_bytes(), encode(&values))
+            .expect("Failed to persist state");
+        map.insert(key, values);
+    }
+
+    async fn get(&self, key: &str) -> Vec<i32> {
+        let map = self.map.lock().unwrap();
+        map.get(key).cloned().unwrap_or_default()
+    }
+
+    async fn initialize(&self, keys: Vec<String>) {
+        let mut map = self.map.lock().unwrap();
+        for key in keys {
+            if let Entry::Vacant(entry) = map.entry(key) {
+                self.db
+                    .insert(entry.key().as_bytes(), Vec::new())
+                    .expect("Failed to persist state");
+                entry.insert(Vec::new());
+            }
+        }
+    }
+}
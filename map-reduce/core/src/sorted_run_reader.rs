@@ -0,0 +1,89 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io;
+
+/// Values a run holds for a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunEntry {
+    pub key: String,
+    pub values: Vec<i32>,
+}
+
+/// Entries of a run in key order, read one at a time, each key at most once per run
+pub trait SortedRunReader {
+    /// Next entry of the run, None once it is exhausted
+    fn next_entry(&mut self) -> io::Result<Option<RunEntry>>;
+}
+
+impl<R: SortedRunReader + ?Sized> SortedRunReader for Box<R> {
+    fn next_entry(&mut self) -> io::Result<Option<RunEntry>> {
+        (**self).next_entry()
+    }
+}
+
+/// Run held in memory, e.g. the values not spilled yet
+pub struct MemoryRunReader {
+    entries: std::vec::IntoIter<RunEntry>,
+}
+
+impl MemoryRunReader {
+    /// Run of the entries, sorted here by key
+    pub fn new(mut entries: Vec<RunEntry>) -> Self {
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Self {
+            entries: entries.into_iter(),
+        }
+    }
+}
+
+impl SortedRunReader for MemoryRunReader {
+    fn next_entry(&mut self) -> io::Result<Option<RunEntry>> {
+        Ok(self.entries.next())
+    }
+}
+
+/// K-way merge of sorted runs restricted to `keys`, calling `visit` with the index of a key
+/// in `keys` and the values one run holds for it, run by run
+/// A key's values are never gathered, so a reducer can fold them as they stream by;
+/// runs are read from their first entry up to the last of the keys
+pub fn merge_runs<R: SortedRunReader>(
+    mut runs: Vec<R>,
+    keys: &[String],
+    mut visit: impl FnMut(usize, &[i32]),
+) -> io::Result<()> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by(|a, b| keys[*a].cmp(&keys[*b]));
+
+    let mut heap = BinaryHeap::new();
+    for (run, reader) in runs.iter_mut().enumerate() {
+        if let Some(entry) = reader.next_entry()? {
+            heap.push(Reverse((entry.key, run, entry.values)));
+        }
+    }
+
+    for index in order {
+        let key = &keys[index];
+        // Skip the keys of other partitions, and stop once the key is passed
+        while let Some(Reverse((next, run, _))) = heap.peek() {
+            if next > key {
+                break;
+            }
+            let run = *run;
+            let Reverse((next, _, values)) = heap.pop().unwrap();
+            if next == *key {
+                visit(index, &values);
+            }
+            if let Some(entry) = runs[run].next_entry()? {
+                heap.push(Reverse((entry.key, run, entry.values)));
+            }
+        }
+        if heap.is_empty() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,314 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::sorted_run_reader::{RunEntry, SortedRunReader};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Number of the next spill directory created by this process
+static NEXT_SPILL_DIR: AtomicU64 = AtomicU64::new(0);
+
+/// One line of a run file
+#[derive(Serialize, Deserialize)]
+struct SpillEntry {
+    key: String,
+    values: Vec<i32>,
+}
+
+/// Keys whose spilled values were removed, with the generation at the time:
+/// entries of runs of that generation or older are dead
+type Removed = Arc<RwLock<HashMap<String, u64>>>;
+
+/// Sorted run file
+struct Run {
+    path: PathBuf,
+    /// Runs written later have a higher generation
+    generation: u64,
+}
+
+/// Run produced by merging all spilled runs, indexed by key for lookups
+struct MergedRun {
+    run: Run,
+    offsets: HashMap<String, u64>,
+}
+
+/// Intermediate values spilled to disk as runs sorted by key
+/// Runs are merged into a single indexed run on the first lookup after a spill, i.e. once
+/// per reduce phase, so the reduce phase reads each key with a single seek
+pub struct SpillRuns {
+    dir: PathBuf,
+    memory_limit: usize,
+    buffered_values: usize,
+    runs: Vec<Run>,
+    merged: Option<MergedRun>,
+    removed: Removed,
+    next_file: u64,
+}
+
+impl SpillRuns {
+    /// Spill to a directory of its own under `dir` once `memory_limit` values are buffered,
+    /// so the states of concurrent jobs and processes never share run files
+    pub fn new(dir: impl AsRef<Path>, memory_limit: usize) -> io::Result<Self> {
+        let dir = dir.as_ref().join(format!(
+            "{}-{}",
+            std::process::id(),
+            NEXT_SPILL_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            memory_limit: memory_limit.max(1),
+            buffered_values: 0,
+            runs: Vec::new(),
+            merged: None,
+            removed: Arc::new(RwLock::new(HashMap::new())),
+            next_file: 0,
+        })
+    }
+
+    /// Account for one more value buffered in memory
+    /// Returns true once the memory limit is reached and the buffer should be spilled
+    pub fn buffer_value(&mut self) -> bool {
+        self.buffered_values += 1;
+        self.buffered_values >= self.memory_limit
+    }
+
+    /// Write the buffered values as a sorted run, leaving the keys with empty value lists
+    pub fn spill(&mut self, buffer: &mut HashMap<String, Vec<i32>>) -> io::Result<()> {
+        let mut entries: Vec<(&String, Vec<i32>)> = buffer
+            .iter_mut()
+            .filter(|(_, values)| !values.is_empty())
+            .map(|(key, values)| (key, std::mem::take(values)))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let (path, generation) = self.next_path("run");
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for (key, values) in entries {
+            write_entry(&mut writer, key.clone(), values)?;
+        }
+        writer.flush()?;
+
+        self.runs.push(Run { path, generation });
+        self.buffered_values = 0;
+        Ok(())
+    }
+
+    /// Values spilled for a key
+    pub fn get(&mut self, key: &str) -> io::Result<Vec<i32>> {
+        let entry = self.key_reader(&[key.to_string()])?.next_entry()?;
+        Ok(entry.map(|entry| entry.values).unwrap_or_default())
+    }
+
+    /// Reader of the values spilled for `keys`, in key order, seeking to each key's entry
+    /// in the merged run
+    pub fn key_reader(&mut self, keys: &[String]) -> io::Result<KeyRunReader> {
+        if !self.runs.is_empty() {
+            self.merge()?;
+        }
+
+        let Some(merged) = &self.merged else {
+            return Ok(KeyRunReader::empty());
+        };
+        let mut entries: Vec<(&String, u64)> = keys
+            .iter()
+            .filter(|key| !is_dead(&self.removed, key, merged.run.generation))
+            .filter_map(|key| Some((key, *merged.offsets.get(key)?)))
+            .collect();
+        if entries.is_empty() {
+            return Ok(KeyRunReader::empty());
+        }
+        entries.sort();
+        Ok(KeyRunReader {
+            reader: Some(BufReader::new(File::open(&merged.run.path)?)),
+            offsets: entries
+                .into_iter()
+                .map(|(_, offset)| offset)
+                .collect::<Vec<_>>()
+                .into_iter(),
+            line: String::new(),
+        })
+    }
+
+    /// Forget the values spilled for a key, e.g. after it was replaced by its reduced value
+    /// Values spilled for it later are kept
+    pub fn remove(&mut self, key: &str) {
+        self.removed
+            .write()
+            .unwrap()
+            .insert(key.to_string(), self.next_file);
+    }
+
+    /// Delete all runs
+    pub fn clear(&mut self) {
+        for run in self.runs.drain(..) {
+            let _ = fs::remove_file(run.path);
+        }
+        if let Some(merged) = self.merged.take() {
+            let _ = fs::remove_file(merged.run.path);
+        }
+        self.removed.write().unwrap().clear();
+        self.buffered_values = 0;
+    }
+
+    /// K-way merge of the runs (and any previous merge result) into one run,
+    /// concatenating the values of keys present in several runs
+    fn merge(&mut self) -> io::Result<()> {
+        let mut inputs: Vec<Run> = self.runs.drain(..).collect();
+        if let Some(previous) = self.merged.take() {
+            inputs.push(previous.run);
+        }
+
+        let mut readers = Vec::with_capacity(inputs.len());
+        let mut heap = BinaryHeap::new();
+        for (run, input) in inputs.iter().enumerate() {
+            let mut reader = SpillRunReader::open(input, self.removed.clone())?;
+            if let Some(entry) = reader.next_entry()? {
+                heap.push(Reverse((entry.key, run, entry.values)));
+            }
+            readers.push(reader);
+        }
+
+        let (path, generation) = self.next_path("merged");
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let mut offsets = HashMap::new();
+        let mut offset = 0u64;
+        while let Some(Reverse((key, run, mut values))) = heap.pop() {
+            if let Some(entry) = readers[run].next_entry()? {
+                heap.push(Reverse((entry.key, run, entry.values)));
+            }
+            // Gather the values of this key from the other runs
+            while heap
+                .peek()
+                .is_some_and(|Reverse((next, _, _))| *next == key)
+            {
+                let Reverse((_, other, more)) = heap.pop().unwrap();
+                values.extend(more);
+                if let Some(entry) = readers[other].next_entry()? {
+                    heap.push(Reverse((entry.key, other, entry.values)));
+                }
+            }
+
+            offsets.insert(key.clone(), offset);
+            offset += write_entry(&mut writer, key, values)?;
+        }
+        writer.flush()?;
+
+        for input in inputs {
+            fs::remove_file(input.path)?;
+        }
+        self.merged = Some(MergedRun {
+            run: Run { path, generation },
+            offsets,
+        });
+        Ok(())
+    }
+
+    /// Path of the next run file, and its generation
+    fn next_path(&mut self, kind: &str) -> (PathBuf, u64) {
+        self.next_file += 1;
+        (
+            self.dir.join(format!("{}-{}.jsonl", kind, self.next_file)),
+            self.next_file,
+        )
+    }
+}
+
+impl Drop for SpillRuns {
+    fn drop(&mut self) {
+        self.clear();
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+/// Streaming reader of a run file, skipping the entries removed since the run was written
+struct SpillRunReader {
+    reader: BufReader<File>,
+    generation: u64,
+    removed: Removed,
+    line: String,
+}
+
+impl SpillRunReader {
+    fn open(run: &Run, removed: Removed) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(&run.path)?),
+            generation: run.generation,
+            removed,
+            line: String::new(),
+        })
+    }
+}
+
+impl SortedRunReader for SpillRunReader {
+    fn next_entry(&mut self) -> io::Result<Option<RunEntry>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            let SpillEntry { key, values } = parse_entry(&self.line)?;
+            if !is_dead(&self.removed, &key, self.generation) {
+                return Ok(Some(RunEntry { key, values }));
+            }
+        }
+    }
+}
+
+/// Entries of chosen keys of the merged run, in key order, each read with a single seek
+pub struct KeyRunReader {
+    reader: Option<BufReader<File>>,
+    offsets: std::vec::IntoIter<u64>,
+    line: String,
+}
+
+impl KeyRunReader {
+    fn empty() -> Self {
+        Self {
+            reader: None,
+            offsets: Vec::new().into_iter(),
+            line: String::new(),
+        }
+    }
+}
+
+impl SortedRunReader for KeyRunReader {
+    fn next_entry(&mut self) -> io::Result<Option<RunEntry>> {
+        let (Some(reader), Some(offset)) = (&mut self.reader, self.offsets.next()) else {
+            return Ok(None);
+        };
+        reader.seek(SeekFrom::Start(offset))?;
+        self.line.clear();
+        reader.read_line(&mut self.line)?;
+        let SpillEntry { key, values } = parse_entry(&self.line)?;
+        Ok(Some(RunEntry { key, values }))
+    }
+}
+
+/// Whether the values a run of `generation` holds for `key` were removed since
+fn is_dead(removed: &Removed, key: &str, generation: u64) -> bool {
+    removed
+        .read()
+        .unwrap()
+        .get(key)
+        .is_some_and(|removed_at| *removed_at >= generation)
+}
+
+/// Write an entry as a line, returning the number of bytes written
+fn write_entry(writer: &mut impl Write, key: String, values: Vec<i32>) -> io::Result<u64> {
+    let mut line = serde_json::to_vec(&SpillEntry { key, values })?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    Ok(line.len() as u64)
+}
+
+fn parse_entry(line: &str) -> io::Result<SpillEntry> {
+    serde_json::from_str(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -2,8 +2,12 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::task_key::TaskKey;
 use async_trait::async_trait;
 
+/// Associative aggregate of a key's values, e.g. `MapReduceJob::partial_aggregate`
+pub type Aggregate = fn(&[i32]) -> i32;
+
 /// Trait for accessing shared state across workers
 /// Abstracts the storage mechanism (local, Redis, RPC, etc.)
 ///
@@ -18,9 +22,32 @@ pub trait StateStore: Clone + Send + Sync + 'static {
     /// Update a key with a value (append for mappers)
     async fn update(&self, key: String, value: i32);
 
-    /// Replace the entire value for a key (used by reducers)
-    async fn replace(&self, key: String, value: i32);
+    /// Apply several updates, returning once all of them are applied
+    /// Network implementations override it to pipeline the requests
+    async fn update_many(&self, updates: Vec<(String, i32)>) {
+        for (key, value) in updates {
+            self.update(key, value).await;
+        }
+    }
+
+    /// Apply the updates of a completed task atomically, at most once per task:
+    /// a task already committed, e.g. by an earlier attempt, is ignored
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>);
+
+    /// Replace all values of a key (used by reducers)
+    async fn replace(&self, key: String, values: Vec<i32>);
 
     /// Get all values for a key
     async fn get(&self, key: &str) -> Vec<i32>;
+
+    /// Reduce each of `keys` to the `aggregate` of its values, in the order of `keys`
+    /// Stores keeping sorted runs (spilled state) merge them, folding a key run by run instead of
+    /// gathering its values, so `aggregate` must be associative, e.g. a sum
+    async fn merge_reduce(&self, keys: &[String], aggregate: Aggregate) -> Vec<i32> {
+        let mut aggregates = Vec::with_capacity(keys.len());
+        for key in keys {
+            aggregates.push(aggregate(&self.get(key).await));
+        }
+        aggregates
+    }
 }
@@ -2,7 +2,11 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::counters::Counters;
+use crate::reduce_output::KeyResult;
+use crate::task_key::TaskKey;
 use async_trait::async_trait;
+use std::time::Duration;
 
 /// Trait for sending synchronization signals (readiness and completion) asynchronously
 #[async_trait]
@@ -11,7 +15,28 @@ pub trait StatusSender: Send + Clone + Sync {
     /// Returns true if the signal was sent successfully
     async fn register(&self, worker_id: usize) -> bool;
 
-    /// Send a completion signal (success or failure)
+    /// Send a completion signal (success or failure), with the counters the task incremented
+    /// and the results it emitted (reduce tasks only)
     /// Returns true if the signal was sent successfully, false otherwise
-    async fn send(&self, result: Result<usize, ()>) -> bool;
+    async fn send(
+        &self,
+        result: Result<usize, ()>,
+        counters: Counters,
+        results: Vec<KeyResult>,
+    ) -> bool;
+
+    /// Task whose outcome this sender reports, None for the sender handed over at initialization
+    fn task(&self) -> Option<TaskKey>;
+
+    /// Interval at which the worker should send liveness heartbeats
+    /// Returns None if the mechanism detects dead workers by other means
+    fn heartbeat_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Send a liveness heartbeat to the coordinator
+    /// Returns true if the heartbeat was delivered
+    async fn heartbeat(&self, _worker_id: usize) -> bool {
+        true
+    }
 }
@@ -0,0 +1,92 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::job_status::JobStatus;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+
+/// Largest request head read, longer requests are rejected
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Time a client gets to send its request and read the response before it is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, e.g. when out of file descriptors, before accepting again
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Serve the coordinator's status as JSON over HTTP (`GET /status`) on `addr`,
+/// returning the address bound, e.g. to learn the port picked for port 0
+pub async fn start_status_server(addr: &str, status: JobStatus) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!(error = %e, "Status server failed to accept a connection");
+                    tokio::time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            let status = status.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(REQUEST_TIMEOUT, respond(stream, &status)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => debug!(%peer, error = %e, "Status request failed"),
+                    Err(_) => debug!(%peer, "Status request timed out"),
+                }
+            });
+        }
+    });
+
+    Ok(local_addr)
+}
+
+/// Answer a single request, closing the connection afterwards
+async fn respond(mut stream: TcpStream, status: &JobStatus) -> io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+        if request.len() > MAX_REQUEST_BYTES {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", "").await;
+        }
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    match (method, path.split('?').next().unwrap_or_default()) {
+        ("GET", "/" | "/status") => {
+            let body = serde_json::to_string_pretty(&status.report())?;
+            write_response(&mut stream, "200 OK", &body).await
+        }
+        ("GET", _) => write_response(&mut stream, "404 Not Found", "").await,
+        _ => write_response(&mut stream, "405 Method Not Allowed", "").await,
+    }
+}
+
+async fn write_response(stream: &mut TcpStream, status_line: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status_line,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
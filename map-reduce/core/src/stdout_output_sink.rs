@@ -0,0 +1,42 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::output_sink::OutputSink;
+use crate::result_value::ResultValue;
+use std::io;
+
+/// Prints the top results and the total to stdout
+/// The total sums the scalars, or counts the entries of list results
+pub struct StdoutOutputSink {
+    limit: usize,
+}
+
+impl StdoutOutputSink {
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl Default for StdoutOutputSink {
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
+
+impl OutputSink for StdoutOutputSink {
+    fn write(&mut self, results: &[(String, ResultValue)]) -> io::Result<()> {
+        println!("\n=== RESULTS ===");
+        for (key, value) in results.iter().take(self.limit) {
+            println!("{}: {}", key, value);
+        }
+
+        if results.len() > self.limit {
+            println!("... ({} more words)", results.len() - self.limit);
+        }
+
+        let total: i64 = results.iter().map(|(_, value)| value.weight()).sum();
+        println!("\nTotal occurrences found: {}", total);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::partial_results::{PartialResults, PartialResultsSubscriber, StreamControl};
+
+/// Prints the leading partial aggregates to stdout and optionally stops
+/// the map phase once any key reaches a threshold
+pub struct StdoutPartialResults {
+    print: bool,
+    limit: usize,
+    threshold: Option<i32>,
+}
+
+impl StdoutPartialResults {
+    pub fn new(print: bool, threshold: Option<i32>) -> Self {
+        Self {
+            print,
+            limit: 5,
+            threshold,
+        }
+    }
+}
+
+impl PartialResultsSubscriber for StdoutPartialResults {
+    fn on_partial_results(&mut self, results: &PartialResults) -> StreamControl {
+        if self.print {
+            let leaders: Vec<String> = results
+                .aggregates
+                .iter()
+                .take(self.limit)
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            println!(
+                "📊 Partial results after {}/{} map tasks: {}",
+                results.completed_tasks,
+                results.total_tasks,
+                leaders.join(", ")
+            );
+        }
+
+        match (self.threshold, results.aggregates.first()) {
+            (Some(threshold), Some((key, value))) if *value >= threshold => {
+                println!(
+                    "🏁 Key '{}' reached the threshold of {} ({}), stopping the map phase early",
+                    key, threshold, value
+                );
+                StreamControl::Stop
+            }
+            _ => StreamControl::Continue,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::state_store::{Aggregate, StateStore};
+use crate::task_key::TaskKey;
+use async_trait::async_trait;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// State seen by one attempt of a map task: reads go to the shared state, updates are buffered
+/// and committed at once when the attempt succeeds, so a failed attempt leaves no partial updates
+/// Reads do not see the attempt's own buffered updates
+#[derive(Clone)]
+pub struct TaskContribution<S> {
+    state: S,
+    updates: Arc<Mutex<Vec<(String, i32)>>>,
+}
+
+impl<S: StateStore> TaskContribution<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            updates: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Merge the buffered updates into the shared state
+    /// Keyed by the task, so the state applies them once however many attempts of the task commit
+    /// Without a key (e.g. a worker handed work outside an executor) they are applied as they are
+    pub async fn commit(&self, task: Option<TaskKey>) {
+        let updates = mem::take(&mut *self.updates.lock().unwrap());
+        match task {
+            Some(task) => self.state.commit_task(task, updates).await,
+            None => self.state.update_many(updates).await,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: StateStore> StateStore for TaskContribution<S> {
+    async fn initialize(&self, keys: Vec<String>) {
+        self.state.initialize(keys).await;
+    }
+
+    async fn update(&self, key: String, value: i32) {
+        self.updates.lock().unwrap().push((key, value));
+    }
+
+    async fn update_many(&self, updates: Vec<(String, i32)>) {
+        self.updates.lock().unwrap().extend(updates);
+    }
+
+    async fn replace(&self, key: String, values: Vec<i32>) {
+        self.state.replace(key, values).await;
+    }
+
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>) {
+        self.state.commit_task(task, updates).await;
+    }
+
+    async fn get(&self, key: &str) -> Vec<i32> {
+        self.state.get(key).await
+    }
+
+    async fn merge_reduce(&self, keys: &[String], aggregate: Aggregate) -> Vec<i32> {
+        self.state.merge_reduce(keys, aggregate).await
+    }
+}
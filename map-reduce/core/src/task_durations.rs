@@ -0,0 +1,67 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of recent task durations the percentiles are computed over
+pub const DURATION_WINDOW: usize = 256;
+
+/// Completed tasks needed before running tasks are compared to the median
+pub const MIN_DURATION_SAMPLES: usize = 5;
+
+/// Tasks running for less than this are never flagged, scheduling jitter dominates below it
+pub const MIN_STRAGGLER_DURATION: Duration = Duration::from_millis(100);
+
+/// Rolling window of the durations of a phase's completed tasks
+#[derive(Debug, Clone, Default)]
+pub struct TaskDurations {
+    recent: VecDeque<Duration>,
+    longest: Duration,
+}
+
+impl TaskDurations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the duration of a completed task
+    pub fn record(&mut self, duration: Duration) {
+        if self.recent.len() == DURATION_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(duration);
+        self.longest = self.longest.max(duration);
+    }
+
+    /// Duration under which `percent` (0-100) of the recent tasks completed
+    pub fn percentile(&self, percent: u32) -> Option<Duration> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.recent.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (sorted.len() * percent.min(100) as usize).div_ceil(100);
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    pub fn median(&self) -> Option<Duration> {
+        self.percentile(50)
+    }
+
+    /// Longest completed task of the phase, including those out of the window
+    pub fn longest(&self) -> Duration {
+        self.longest
+    }
+
+    /// Running time above which a task is a straggler: `factor` times the median
+    /// None before `MIN_DURATION_SAMPLES` tasks have completed, or when `factor` is 0
+    pub fn straggler_threshold(&self, factor: f64) -> Option<Duration> {
+        if factor <= 0.0 || self.recent.len() < MIN_DURATION_SAMPLES {
+            return None;
+        }
+        self.median()
+            .map(|median| median.mul_f64(factor).max(MIN_STRAGGLER_DURATION))
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::job_id::JobId;
+use serde::{Deserialize, Serialize};
+
+/// Identity of a task, shared by all its attempts: its job, the phase run it belongs to and its index there
+/// Phase runs get a random id, so tasks of different phases, stages or jobs never share a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskKey {
+    pub job: JobId,
+    pub phase: u64,
+    pub index: usize,
+}
+
+impl TaskKey {
+    /// Key of task `index` in a new phase run of a job
+    pub fn new(job: JobId, phase: u64, index: usize) -> Self {
+        Self { job, phase, index }
+    }
+
+    /// Big-endian bytes of the job, the phase and the index, e.g. to persist the key
+    pub fn to_bytes(&self) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        bytes[..4].copy_from_slice(&self.job.0.to_be_bytes());
+        bytes[4..12].copy_from_slice(&self.phase.to_be_bytes());
+        bytes[12..].copy_from_slice(&(self.index as u64).to_be_bytes());
+        bytes
+    }
+}
@@ -2,12 +2,46 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
-use crate::config::Config;
+use crate::chunk_tuning::{ChunkTuner, PROBE_DIVISOR};
+use crate::config::{Config, OutputConfig};
+use crate::counters::{TaskCounters, BYTES_READ, MALFORMED_RECORDS_SKIPPED, RECORDS_PROCESSED};
+use crate::csv_output_sink::CsvOutputSink;
+use crate::data_chunk::DataChunk;
 use crate::executor::Executor;
+use crate::failure_domains::FailureDomains;
+use crate::file_input_source::FileInputSource;
+use crate::hot_keys::{detect_hot_keys, merge_sub_key_results, reduce_keys, HotKey};
+use crate::in_memory_state_store::{LocalStateAccess, SharedStateMap};
+use crate::input_source::{InputSource, InputSplit};
+use crate::job_dag::{JobDag, StageConfig};
+use crate::job_metrics::JobMetrics;
+use crate::job_status::JobStatus;
+use crate::jsonl_output_sink::JsonlOutputSink;
+use crate::map_reduce_job::MapReduceJob;
+use crate::object_store_input_source::ObjectStoreInputSource;
+use crate::object_store_output_sink::ObjectStoreOutputSink;
+use crate::output_sink::OutputSink;
+use crate::partial_results::{PartialResults, PartialResultsSubscriber, StreamControl};
+use crate::partitioner::partition_keys;
+use crate::reduce_output::{merge_results, order_results, KeyResult};
+use crate::result_sink::ResultSink;
+use crate::scheduling_policy::{
+    AffinityPolicy, ExclusionPolicy, FailureDomainPolicy, SchedulingPolicyKind,
+};
+use crate::side_inputs::SideInputs;
+use crate::status_server::start_status_server;
+use crate::stdout_output_sink::StdoutOutputSink;
+use crate::stdout_partial_results::StdoutPartialResults;
 use crate::worker::Worker;
 use crate::worker_factory::WorkerFactory;
+use crate::worker_pool::WorkerPool;
 use crate::worker_synchronization::WorkerSynchronization;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
 
 pub fn generate_random_string(rng: &mut impl Rng, max_length: usize) -> String {
     let length = rng.random_range(1..=max_length);
@@ -28,35 +62,335 @@ pub fn generate_target_word(rng: &mut impl Rng, length: usize) -> String {
         .collect()
 }
 
-pub fn generate_test_data(config: &Config) -> (Vec<String>, Vec<String>) {
+/// Generate the input strings, shared by the map assignments, and the target words
+pub fn generate_test_data(config: &Config) -> (Arc<[String]>, Vec<String>) {
     println!("\nGenerating data...");
-    let mut rng = rand::rng();
+    // A fixed seed generates the same data in every run, e.g. to compare variants on it
+    let mut rng = match config.data_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    };
 
-    // Generate random strings
-    let data: Vec<String> = (0..config.num_strings)
-        .map(|_| generate_random_string(&mut rng, config.max_string_length))
-        .collect();
+    // Generate random strings, unless the map input is read from files
+    let data: Arc<[String]> = if config.input_path.is_some() {
+        Arc::from([])
+    } else {
+        (0..config.num_strings)
+            .map(|_| generate_random_string(&mut rng, config.max_string_length))
+            .collect()
+    };
 
     println!("Generated {} strings", data.len());
 
-    // Generate random target words
-    let targets: Vec<String> = (0..config.num_target_words)
-        .map(|_| generate_target_word(&mut rng, config.target_word_length))
-        .collect();
-
-    println!("Generated {} target words", targets.len());
+    // Generate random target words, for the jobs searching for them
+    let mut targets = Vec::new();
+    if config.job.uses_targets() {
+        targets = (0..config.num_target_words)
+            .map(|_| generate_target_word(&mut rng, config.target_word_length))
+            .collect();
+        println!("Generated {} target words", targets.len());
+    }
 
     (data, targets)
 }
 
+/// Create map assignments from the configured input files, or from generated data if none
+/// With adaptive chunk sizing (`target_task_ms`), only the probe tasks are created, along with
+/// the tuner re-partitioning the rest of the input once they ran
+pub fn create_map_assignments<P>(
+    config: &Config,
+    data: Arc<[String]>,
+    context: P::Context,
+) -> (Vec<P::MapAssignment>, Option<ChunkTuner>)
+where
+    P: MapReduceJob<Input = Arc<[String]>>,
+{
+    let Some(target_ms) = config.target_task_ms else {
+        let assignments = match &config.input_path {
+            Some(path) => {
+                P::create_split_assignments(input_splits(path, config.split_size_bytes), context)
+            }
+            None => {
+                let chunks = DataChunk::ranges(data.len(), config.partition_size);
+                P::create_map_assignments(data, context, chunks)
+            }
+        };
+        return (assignments, None);
+    };
+
+    let tuner = match &config.input_path {
+        Some(path) => ChunkTuner::for_splits(
+            input_splits(path, config.split_size_bytes / PROBE_DIVISOR),
+            target_ms,
+            config.num_mappers,
+        ),
+        None => ChunkTuner::for_records(
+            data,
+            config.partition_size / PROBE_DIVISOR,
+            target_ms,
+            config.num_mappers,
+        ),
+    };
+    let probes = tuner.probe_assignments::<P>(context);
+    println!(
+        "Adaptive chunk sizing: {} probe tasks run first, the rest of the input is sized after them",
+        probes.len()
+    );
+    (probes, Some(tuner))
+}
+
+/// Split the input files at `path` into splits of about `split_size` bytes
+fn input_splits(path: &str, split_size: usize) -> Vec<InputSplit> {
+    let splits = open_input_source(path)
+        .and_then(|source| source.splits(split_size.max(1)))
+        .expect("Failed to split input files");
+    println!("Created {} input splits from {}", splits.len(), path);
+    splits
+}
+
+/// Read every record of the configured join input, None if the job has none
+pub fn load_join_input(config: &Config) -> Option<Vec<String>> {
+    let path = config.join_input.as_ref()?;
+    let records =
+        read_all_records(path, config.split_size_bytes).expect("Failed to read the join input");
+    println!("Loaded {} join records from {}", records.len(), path);
+    Some(records)
+}
+
+/// Read every record of a file, directory or object storage prefix, split by split
+pub fn read_all_records(path: &str, split_size_bytes: usize) -> io::Result<Vec<String>> {
+    let source = open_input_source(path)?;
+    let mut records = Vec::new();
+    for split in source.splits(split_size_bytes)? {
+        records.extend(source.read_split(&split)?.records);
+    }
+    Ok(records)
+}
+
+/// Load the side inputs of a job, broadcast to its workers with the job context of every phase
+pub fn load_side_inputs(config: &Config) -> SideInputs {
+    SideInputs::load(config).expect("Failed to read the side inputs")
+}
+
+/// Open the input source for a path: object storage for `scheme://` URIs, local files otherwise
+pub fn open_input_source(path: &str) -> io::Result<Box<dyn InputSource>> {
+    if path.contains("://") {
+        Ok(Box::new(ObjectStoreInputSource::new(path)?))
+    } else {
+        Ok(Box::new(FileInputSource::new(path)))
+    }
+}
+
+/// Stream the records of a map task's split through `process`, counting them, the bytes read
+/// and the malformed lines; fails if the split could not be read to its end
+pub fn read_split_records<T>(
+    split: &InputSplit,
+    counters: &TaskCounters,
+    process: impl FnOnce(&mut dyn Iterator<Item = String>) -> T,
+) -> io::Result<T> {
+    let mut reader = open_input_source(&split.path)?.open_split(split)?;
+    let mut records = 0;
+    let result = process(&mut reader.by_ref().inspect(|_| records += 1));
+    let malformed = reader.finish()?;
+    counters.increment(RECORDS_PROCESSED, records);
+    counters.increment(MALFORMED_RECORDS_SKIPPED, malformed as u64);
+    counters.increment(BYTES_READ, split.length);
+    Ok(result)
+}
+
+/// Create the coordinator's state store, spilling to disk if a memory limit is configured
+pub fn create_local_state(config: &Config) -> io::Result<LocalStateAccess> {
+    match config.state_memory_limit {
+        Some(limit) => {
+            LocalStateAccess::with_spilling(Path::new(&config.work_dir).join("spill"), limit)
+        }
+        None => Ok(LocalStateAccess::new()),
+    }
+}
+
+/// Create the output sinks of the configured outputs
+pub fn create_output_sinks(outputs: &[OutputConfig]) -> Vec<Box<dyn OutputSink>> {
+    outputs
+        .iter()
+        .map(|output| -> Box<dyn OutputSink> {
+            match output {
+                OutputConfig::Stdout => Box::new(StdoutOutputSink::default()),
+                OutputConfig::Jsonl { path } => Box::new(JsonlOutputSink::new(path)),
+                OutputConfig::Csv { path } => Box::new(CsvOutputSink::new(path)),
+                OutputConfig::Object { uri } => Box::new(ObjectStoreOutputSink::new(uri)),
+            }
+        })
+        .collect()
+}
+
+/// Keys of the state map to reduce after the map phase, sorted so reduce partitions are reproducible
+pub fn sorted_keys(map: &HashMap<String, Vec<i32>>) -> Vec<String> {
+    let mut keys = reduce_keys(map);
+    keys.sort_unstable();
+    keys
+}
+
+/// Hot keys of the state map after the map phase, if the job splits them
+pub fn find_hot_keys<P>(config: &Config, map: &HashMap<String, Vec<i32>>) -> Vec<HotKey>
+where
+    P: MapReduceJob,
+{
+    match P::hot_key_threshold(config) {
+        Some(threshold) => detect_hot_keys(map, threshold, config.num_reducers),
+        None => Vec::new(),
+    }
+}
+
+/// Partition the keys of the state after the map phase and create the reduce assignments
+pub fn create_reduce_assignments<P>(
+    config: &Config,
+    context: P::Context,
+    map: &HashMap<String, Vec<i32>>,
+) -> Vec<P::ReduceAssignment>
+where
+    P: MapReduceJob,
+{
+    let keys = sorted_keys(map);
+    let partitioner = P::partitioner(&context);
+    let num_partitions = partitioner.num_partitions(config.num_partitions(keys.len()));
+    let partitions = partition_keys(partitioner.as_ref(), keys, num_partitions);
+    println!(
+        "Partitioned the keys into {} reduce tasks for {} reducers",
+        partitions.len(),
+        config.num_reducers
+    );
+    P::create_reduce_assignments(context, partitions)
+}
+
+/// Merge the ordered results the reduce tasks reported into the job's final results,
+/// the first `top_k` if set
+pub fn collect_results<P>(config: &Config, tasks: Vec<Vec<KeyResult>>) -> Vec<KeyResult>
+where
+    P: MapReduceJob,
+{
+    let (mut tasks, merged) = merge_sub_key_results(tasks, P::merge_split_results);
+    if !merged.is_empty() {
+        tasks.push(order_results(merged, P::result_order(), None));
+    }
+    merge_results(tasks, P::result_order(), config.top_k)
+}
+
+/// Hand the results the reduce tasks of a stage reported on, merged into the stage's results:
+/// to the stages depending on it, right away, or to the job's result sink for final stages
+pub fn deliver_results<P>(
+    dag: &JobDag,
+    stage: &StageConfig,
+    stage_config: &Config,
+    tasks: Vec<Vec<KeyResult>>,
+    result_sink: &mut dyn ResultSink,
+) where
+    P: MapReduceJob,
+{
+    let results = collect_results::<P>(stage_config, tasks);
+    if dag.is_final(stage) {
+        result_sink.collect(&stage.name, stage_config, results);
+        return;
+    }
+    for mut sink in create_output_sinks(&stage_config.outputs) {
+        if let Err(e) = sink.write(&results) {
+            eprintln!("Failed to write the results of stage {}: {}", stage.name, e);
+        }
+    }
+}
+
+/// Write the report of the job's final results
+pub fn report_results(result_sink: &mut dyn ResultSink) -> Result<(), String> {
+    result_sink
+        .report()
+        .map_err(|e| format!("Failed to write results: {}", e))
+}
+
+/// Print the job metrics report and write it to the configured metrics file
+pub fn report_metrics(config: &Config, metrics: &mut JobMetrics) {
+    metrics.finish();
+    metrics.print_report();
+    if let Some(path) = &config.metrics_path {
+        if let Err(e) = metrics.write_json(path) {
+            eprintln!("Failed to write metrics: {}", e);
+        }
+    }
+}
+
+/// Serve the status of the coordinator's jobs over HTTP if the pool's config sets `status_addr`
+pub async fn serve_job_status(config: &Config, status: &JobStatus) {
+    if let Some(addr) = &config.status_addr {
+        match start_status_server(addr, status.clone()).await {
+            Ok(bound) => println!("📊 Status at http://{}/status", bound),
+            Err(e) => eprintln!("Failed to start the status endpoint on {}: {}", addr, e),
+        }
+    }
+}
+
+/// Create the partial results subscriber configured for the job, if any
+pub fn create_partial_results_subscriber(
+    config: &Config,
+) -> Option<Box<dyn PartialResultsSubscriber>> {
+    if config.stream_partial_results || config.early_stop_threshold.is_some() {
+        Some(Box::new(StdoutPartialResults::new(
+            config.stream_partial_results,
+            config.early_stop_threshold,
+        )))
+    } else {
+        None
+    }
+}
+
+/// Build a map phase progress callback that snapshots the state maps
+/// (one per shard, keys never span shards) and streams per-key partial aggregates to the subscriber
+pub fn stream_partial_results<'a, P>(
+    maps: Vec<SharedStateMap>,
+    mut subscriber: Option<&'a mut (dyn PartialResultsSubscriber + 'static)>,
+) -> impl FnMut(usize, usize) -> StreamControl + 'a
+where
+    P: MapReduceJob,
+{
+    move |completed_tasks, total_tasks| {
+        let Some(subscriber) = subscriber.as_deref_mut() else {
+            return StreamControl::Continue;
+        };
+
+        let mut aggregates: Vec<(String, i32)> = maps
+            .iter()
+            .flat_map(|map| {
+                map.lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(key, values)| (key.clone(), P::partial_aggregate(values)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        aggregates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        subscriber.on_partial_results(&PartialResults {
+            completed_tasks,
+            total_tasks,
+            aggregates,
+        })
+    }
+}
+
+/// Create the workers of a pool and the executor running the phases' tasks on them
+#[allow(clippy::too_many_arguments)]
 pub async fn initialize_phase<W, S, F>(
     num_workers: usize,
     mut factory: F,
     timeout_ms: u64,
-) -> (Vec<W>, Executor<W, S, F>)
+    max_retries: u32,
+    max_timeouts: u32,
+    straggler_factor: f64,
+    scheduling_policy: SchedulingPolicyKind,
+    affinity: bool,
+    failure_domains: &[String],
+) -> WorkerPool<W, S, F>
 where
     W: Worker,
     S: WorkerSynchronization,
+    W::Completion: From<S::StatusSender>,
     F: WorkerFactory<W>,
 {
     let mut workers = Vec::with_capacity(num_workers);
@@ -64,7 +398,19 @@ where
         workers.push(factory.create_worker(id).await);
     }
 
-    let executor = Executor::new(factory, timeout_ms);
+    let mut executor = Executor::new(factory, timeout_ms, max_retries);
+    executor.set_max_timeouts(max_timeouts);
+    executor.set_straggler_factor(straggler_factor);
+    let policy = scheduling_policy.create();
+    let policy = if affinity {
+        Box::new(AffinityPolicy::new(policy))
+    } else {
+        policy
+    };
+    let domains = FailureDomains::new(failure_domains.to_vec());
+    let policy = Box::new(FailureDomainPolicy::new(policy, domains.clone()));
+    executor.set_scheduling_policy(Box::new(ExclusionPolicy::new(policy)));
+    executor.set_failure_domains(domains);
 
-    (workers, executor)
+    WorkerPool::new(workers, executor)
 }
@@ -0,0 +1,52 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::io;
+
+/// zstd level of compressed work payloads, favouring speed over ratio
+pub const COMPRESSION_LEVEL: i32 = 3;
+
+/// Payloads shorter than this are sent raw even with compression enabled, as zstd's
+/// frame overhead outweighs what it saves on them
+pub const MIN_COMPRESSED_BYTES: usize = 256;
+
+/// Work payload as put on the wire
+pub struct WirePayload {
+    pub bytes: Vec<u8>,
+    /// Whether `bytes` is zstd-compressed, carried as a flag in the message envelope
+    pub compressed: bool,
+    /// Length of the payload before compression
+    pub raw_len: usize,
+}
+
+/// Compress `payload` if `enabled` and it is worth it, keeping it raw otherwise
+/// (also when compressing would not shrink it)
+pub fn compress_payload(payload: Vec<u8>, enabled: bool) -> WirePayload {
+    let raw_len = payload.len();
+    if enabled && raw_len >= MIN_COMPRESSED_BYTES {
+        if let Ok(compressed) = zstd::bulk::compress(&payload, COMPRESSION_LEVEL) {
+            if compressed.len() < raw_len {
+                return WirePayload {
+                    bytes: compressed,
+                    compressed: true,
+                    raw_len,
+                };
+            }
+        }
+    }
+    WirePayload {
+        bytes: payload,
+        compressed: false,
+        raw_len,
+    }
+}
+
+/// Undo `compress_payload`, given the envelope's compression flag
+pub fn decompress_payload(bytes: Vec<u8>, compressed: bool) -> io::Result<Vec<u8>> {
+    if compressed {
+        zstd::stream::decode_all(bytes.as_slice())
+    } else {
+        Ok(bytes)
+    }
+}
@@ -2,13 +2,35 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::job_context::BroadcastContext;
 use crate::worker_message::WorkerMessage;
 use async_trait::async_trait;
 
 /// Trait for receiving work assignments asynchronously
 #[async_trait]
-pub trait WorkReceiver<A, C>: Send {
+pub trait WorkReceiver<A: BroadcastContext, C>: Send {
     /// Receive the next message (initialization or work)
     /// Returns None if the channel is closed
     async fn recv(&mut self) -> Option<WorkerMessage<A, C>>;
+
+    /// Stop accepting messages and release the listener or connection, if any
+    /// Messages already queued are still returned by `recv`, which then returns None
+    async fn shutdown(&mut self);
+}
+
+/// Shut the receiver down and discard the messages it had queued
+/// Returns the number of discarded assignments, which the coordinator reassigns if it still runs
+pub async fn shutdown_and_drain<A, C, R>(receiver: &mut R) -> usize
+where
+    A: BroadcastContext,
+    R: WorkReceiver<A, C> + ?Sized,
+{
+    receiver.shutdown().await;
+    let mut discarded = 0;
+    while let Some(message) = receiver.recv().await {
+        if let WorkerMessage::Work(..) = message {
+            discarded += 1;
+        }
+    }
+    discarded
 }
@@ -2,12 +2,44 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::job_context::{BroadcastContext, JobContextOf};
+use std::future::Future;
+
+/// Messages a worker's work queue holds before `send_work` waits for the worker to catch up
+pub const WORK_QUEUE_CAPACITY: usize = 4;
+
 /// Trait for abstracting work distribution to workers
 /// Different implementations for mpsc, sockets, RPC, etc.
-pub trait WorkSender<A, C>: Clone + Send + 'static {
-    /// Send initialization sender to worker
-    fn initialize(&self, sender: C);
+pub trait WorkSender<A: BroadcastContext, C>: Clone + Send + 'static {
+    /// Send initialization sender and the phase's job context to worker
+    fn initialize(&self, sender: C, context: JobContextOf<A>);
 
     /// Send work assignment with completion sender
-    fn send_work(&self, assignment: A, completion: C);
+    /// Resolves once the worker's transport accepted the assignment,
+    /// or with the reason it could not be delivered
+    fn send_work(
+        &self,
+        assignment: A,
+        completion: C,
+    ) -> impl Future<Output = Result<(), String>> + Send;
+
+    /// Total bytes of messages sent through this channel (0 for in-process channels)
+    fn bytes_sent(&self) -> u64 {
+        0
+    }
+
+    /// Total bytes of the same messages before compression
+    fn raw_bytes_sent(&self) -> u64 {
+        self.bytes_sent()
+    }
+
+    /// Messages waiting in the worker's queue, as last observed by this sender
+    fn queue_depth(&self) -> usize {
+        0
+    }
+
+    /// Why the worker was found unhealthy, e.g. by failed health checks, if it was
+    fn unhealthy_reason(&self) -> Option<String> {
+        None
+    }
 }
@@ -2,20 +2,49 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::job_context::{BroadcastContext, JobContextOf};
 use std::fmt::Display;
 use std::future::Future;
 
 /// Trait for workers (mappers and reducers) to abstract communication mechanism
 pub trait Worker: Send {
-    type Assignment: Send;
+    /// Phase the worker runs tasks of (map, reduce), tagging the executor's log lines
+    const PHASE: &'static str;
+
+    type Assignment: Send + BroadcastContext;
     type Completion;
     type Error: Display;
 
-    /// Initialize the worker with a synchronization sender
-    fn initialize(&self, sender: Self::Completion);
+    /// Initialize the worker with a synchronization sender and the phase's job context
+    fn initialize(&self, sender: Self::Completion, context: JobContextOf<Self::Assignment>);
 
     /// Send a work assignment to this worker
-    fn send_work(&self, assignment: Self::Assignment, complete_tx: Self::Completion);
+    /// Fails if the assignment could not be delivered
+    fn send_work(
+        &self,
+        assignment: Self::Assignment,
+        complete_tx: Self::Completion,
+    ) -> impl Future<Output = Result<(), String>> + Send;
+
+    /// Total bytes of messages sent to this worker
+    fn bytes_sent(&self) -> u64 {
+        0
+    }
+
+    /// Total bytes of the messages sent to this worker before compression
+    fn raw_bytes_sent(&self) -> u64 {
+        self.bytes_sent()
+    }
+
+    /// Messages waiting in this worker's queue
+    fn queue_depth(&self) -> usize {
+        0
+    }
+
+    /// Why the worker died, if it is no longer running or no longer healthy
+    fn exit_reason(&self) -> Option<String> {
+        None
+    }
 
     /// Wait for the worker to shut down
     fn wait(self) -> impl Future<Output = Result<(), Self::Error>> + Send;
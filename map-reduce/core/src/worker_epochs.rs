@@ -0,0 +1,50 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Epoch of every worker slot, advanced by the coordinator each time the slot's worker is replaced
+/// Status messages carry the epoch of their sender, so late messages of a replaced worker
+/// are never credited to its replacement
+#[derive(Clone, Default)]
+pub struct WorkerEpochs {
+    epochs: Arc<Vec<AtomicU64>>,
+}
+
+impl WorkerEpochs {
+    pub fn new(num_workers: usize) -> Self {
+        Self {
+            epochs: Arc::new((0..num_workers).map(|_| AtomicU64::new(0)).collect()),
+        }
+    }
+
+    /// Epoch of the worker currently occupying the slot
+    pub fn current(&self, worker_id: usize) -> u64 {
+        self.epochs
+            .get(worker_id)
+            .map(|epoch| epoch.load(Ordering::SeqCst))
+            .unwrap_or(0)
+    }
+
+    /// Start a new epoch for the slot, returning it
+    pub fn advance(&self, worker_id: usize) -> u64 {
+        self.epochs
+            .get(worker_id)
+            .map(|epoch| epoch.fetch_add(1, Ordering::SeqCst) + 1)
+            .unwrap_or(0)
+    }
+
+    /// Start a new epoch for every slot, e.g. when the same workers are re-tasked for another phase
+    pub fn advance_all(&self) {
+        for epoch in self.epochs.iter() {
+            epoch.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Whether a message tagged with `epoch` comes from the slot's current worker
+    pub fn is_current(&self, worker_id: usize, epoch: u64) -> bool {
+        worker_id < self.epochs.len() && self.current(worker_id) == epoch
+    }
+}
@@ -2,13 +2,14 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::job_context::{BroadcastContext, JobContextOf};
 use serde::{Deserialize, Serialize};
 
 /// Message types received by workers
-#[derive(Serialize, Deserialize, Debug)]
-pub enum WorkerMessage<A, C> {
-    /// Initialization message containing the synchronization sender
-    Initialize(C),
+#[derive(Serialize, Deserialize)]
+pub enum WorkerMessage<A: BroadcastContext, C> {
+    /// Initialization message containing the synchronization sender and the phase's job context
+    Initialize(C, JobContextOf<A>),
     /// Work assignment
     Work(A, C),
 }
@@ -0,0 +1,66 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::executor::Executor;
+use crate::partial_results::StreamControl;
+use crate::scheduling_policy::TaskSize;
+use crate::shutdown_signal::ShutdownSignal;
+use crate::worker::Worker;
+use crate::worker_factory::WorkerFactory;
+use crate::worker_synchronization::WorkerSynchronization;
+use std::mem;
+
+/// Workers of one kind, e.g. the mappers, and the executor running the phases' tasks on them
+pub struct WorkerPool<W, CS, F>
+where
+    W: Worker,
+    CS: WorkerSynchronization,
+    F: WorkerFactory<W>,
+{
+    pub workers: Vec<W>,
+    pub executor: Executor<W, CS, F>,
+}
+
+impl<W, CS, F> WorkerPool<W, CS, F>
+where
+    W: Worker,
+    CS: WorkerSynchronization,
+    W::Completion: From<CS::StatusSender>,
+    F: WorkerFactory<W>,
+{
+    pub fn new(workers: Vec<W>, executor: Executor<W, CS, F>) -> Self {
+        Self { workers, executor }
+    }
+
+    /// Run a phase on the pool's workers, see `Executor::execute`
+    pub async fn execute<SD>(&mut self, assignments: Vec<W::Assignment>, shutdown_signal: &SD)
+    where
+        SD: ShutdownSignal + Sync,
+        W::Assignment: Clone + TaskSize,
+    {
+        let workers = mem::take(&mut self.workers);
+        self.workers = self
+            .executor
+            .execute(workers, assignments, shutdown_signal)
+            .await;
+    }
+
+    /// Run a phase on the pool's workers, see `Executor::execute_with_progress`
+    pub async fn execute_with_progress<SD, P>(
+        &mut self,
+        assignments: Vec<W::Assignment>,
+        shutdown_signal: &SD,
+        on_progress: P,
+    ) where
+        SD: ShutdownSignal + Sync,
+        W::Assignment: Clone + TaskSize,
+        P: FnMut(usize, usize) -> StreamControl,
+    {
+        let workers = mem::take(&mut self.workers);
+        self.workers = self
+            .executor
+            .execute_with_progress(workers, assignments, shutdown_signal, on_progress)
+            .await;
+    }
+}
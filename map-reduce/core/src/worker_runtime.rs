@@ -21,6 +21,11 @@ pub trait WorkerRuntime<Task>: Send + 'static {
     /// Spawn a worker task/thread/process
     fn spawn(task: Task) -> Self::Handle;
 
+    /// Why the worker exited, if it already did (None while it runs)
+    fn exit_reason(_handle: &Self::Handle) -> Option<String> {
+        None
+    }
+
     /// Wait for the worker to complete
     fn join(handle: Self::Handle) -> impl Future<Output = Result<(), Self::Error>> + Send;
 }
@@ -2,8 +2,35 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::counters::Counters;
+use crate::reduce_output::KeyResult;
+use crate::task_key::TaskKey;
 use std::future::Future;
 
+/// Status reported to the executor, tagged with the task the worker was running
+/// The task is None for reports not tied to an assignment, e.g. missed heartbeats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkerStatus {
+    Completed {
+        worker_id: usize,
+        task_id: Option<usize>,
+        /// Counters the task incremented
+        counters: Counters,
+        /// Results the task emitted, in the job's result order (reduce tasks only)
+        results: Vec<KeyResult>,
+    },
+    Failed {
+        worker_id: usize,
+        task_id: Option<usize>,
+    },
+    /// Completion reported by a worker that was since replaced, e.g. a straggler whose task was
+    /// retried elsewhere, never accepted but counted as a suppressed duplicate
+    Superseded {
+        worker_id: usize,
+        task_id: Option<usize>,
+    },
+}
+
 /// Trait for abstracting synchronization signaling mechanisms
 /// This allows different implementations for tasks, threads, and processes
 pub trait WorkerSynchronization: Send {
@@ -13,20 +40,31 @@ pub trait WorkerSynchronization: Send {
     /// Setup synchronization signaling for N workers
     fn setup(num_workers: usize) -> Self;
 
+    /// Start the next phase on the signaling of the previous one, re-tasking the same workers
+    /// without setting up new listeners
+    /// This advances every worker's epoch, so late messages of the previous phase are never
+    /// credited to the new one, until the workers register again with their new senders
+    fn switch_phase(&mut self);
+
     /// Get the synchronization sender for a specific worker
     fn get_status_sender(&self, worker_id: usize) -> Self::StatusSender;
 
+    /// Get the sender a worker reports the outcome of `task` with
+    fn get_task_status_sender(&self, worker_id: usize, task: TaskKey) -> Self::StatusSender;
+
     /// Wait for a specific worker to be ready
     /// Returns true if the worker is ready, false if it timed out or failed
     fn wait_for_worker_ready(&self, worker_id: usize) -> impl Future<Output = bool> + Send;
 
     /// Wait for the next worker to complete or fail
-    /// Returns Ok(worker_id) on success, Err(worker_id) on failure
+    /// Completions tagged with a stale worker epoch are reported as `Superseded`, other messages
+    /// of a replaced worker are discarded
     /// Returns None if all workers are done
-    fn wait_next(&mut self) -> impl Future<Output = Option<Result<usize, usize>>> + Send;
+    fn wait_next(&mut self) -> impl Future<Output = Option<WorkerStatus>> + Send;
 
     /// Reset the signaling mechanism for a specific worker
-    /// This drains any pending messages and returns a new sender for the new worker
+    /// This advances the worker's epoch, so messages of the replaced worker are never credited to its replacement,
+    /// and returns a sender for the new worker
     fn reset_worker(&mut self, worker_id: usize)
         -> impl Future<Output = Self::StatusSender> + Send;
 }
@@ -0,0 +1,211 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use async_trait::async_trait;
+use map_reduce_core::config::Config;
+use map_reduce_core::counters::{TaskCounters, KEYS_REDUCED};
+use map_reduce_core::data_chunk::DataChunk;
+use map_reduce_core::input_source::InputSplit;
+use map_reduce_core::job_context::{BroadcastContext, PhaseContext};
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::reduce_output::ReduceOutput;
+use map_reduce_core::result_value::ResultValue;
+use map_reduce_core::scheduling_policy::TaskSize;
+use map_reduce_core::state_store::StateStore;
+use map_reduce_core::utils::read_split_records;
+use map_reduce_word_count::tokenize;
+use std::collections::{BTreeSet, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+pub struct InvertedIndexProblem;
+
+/// Generated strings are one document each, numbered from `first_document`,
+/// while every line of a split belongs to the document of its input file
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct MapWorkAssignment {
+    pub chunk_id: usize,
+    pub data: DataChunk,
+    pub first_document: i32,
+    #[serde(default)]
+    pub split: Option<InputSplit>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReduceWorkAssignment {
+    pub keys: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct InvertedIndexContext;
+
+impl TaskSize for MapWorkAssignment {
+    fn task_size(&self) -> u64 {
+        match &self.split {
+            Some(split) => split.length,
+            None => self.data.len() as u64,
+        }
+    }
+}
+
+impl TaskSize for ReduceWorkAssignment {
+    fn task_size(&self) -> u64 {
+        self.keys.len() as u64
+    }
+}
+
+impl BroadcastContext for MapWorkAssignment {
+    type JobContext = ();
+}
+
+impl BroadcastContext for ReduceWorkAssignment {
+    type JobContext = ();
+}
+
+#[async_trait]
+impl MapReduceJob for InvertedIndexProblem {
+    type Input = Arc<[String]>;
+    type MapAssignment = MapWorkAssignment;
+    type ReduceAssignment = ReduceWorkAssignment;
+    type Context = InvertedIndexContext;
+
+    const NAME: &'static str = "inverted-index";
+
+    fn create_context(
+        _config: &Config,
+        _data: &Self::Input,
+        _targets: Vec<String>,
+    ) -> Self::Context {
+        InvertedIndexContext
+    }
+
+    fn create_map_assignments(
+        data: Self::Input,
+        _context: Self::Context,
+        chunks: Vec<Range<usize>>,
+    ) -> Vec<Self::MapAssignment> {
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, range)| MapWorkAssignment {
+                chunk_id,
+                first_document: range.start as i32,
+                data: DataChunk::new(data.clone(), range),
+                split: None,
+            })
+            .collect()
+    }
+
+    /// Files are numbered in the order the input source lists them
+    fn create_split_assignments(
+        splits: Vec<InputSplit>,
+        _context: Self::Context,
+    ) -> Vec<Self::MapAssignment> {
+        let mut documents: Vec<String> = Vec::new();
+        splits
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, split)| {
+                if documents.last() != Some(&split.path) {
+                    println!("Document {}: {}", documents.len(), split.path);
+                    documents.push(split.path.clone());
+                }
+                MapWorkAssignment {
+                    chunk_id,
+                    data: DataChunk::default(),
+                    first_document: documents.len() as i32 - 1,
+                    split: Some(split),
+                }
+            })
+            .collect()
+    }
+
+    fn create_reduce_assignments(
+        _context: Self::Context,
+        partitions: Vec<Vec<String>>,
+    ) -> Vec<Self::ReduceAssignment> {
+        partitions
+            .into_iter()
+            .map(|keys| ReduceWorkAssignment { keys })
+            .collect()
+    }
+
+    /// Number of distinct documents containing the word so far
+    fn partial_aggregate(values: &[i32]) -> i32 {
+        values.iter().collect::<HashSet<_>>().len() as i32
+    }
+
+    fn result_value(values: &[i32]) -> ResultValue {
+        ResultValue::List(values.to_vec())
+    }
+
+    /// The documents of a split word are the union of its sub-keys' documents, in order
+    fn merge_split_results(parts: Vec<ResultValue>) -> ResultValue {
+        let documents: BTreeSet<i32> = parts
+            .into_iter()
+            .flat_map(|part| match part {
+                ResultValue::List(documents) => documents,
+                ResultValue::Scalar(document) => vec![document],
+            })
+            .collect();
+        ResultValue::List(documents.into_iter().collect())
+    }
+
+    async fn map_work<S>(
+        assignment: &Self::MapAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        let postings = match &assignment.split {
+            Some(split) => match read_split_records(split, counters, |lines| {
+                let mut postings = HashSet::new();
+                for line in lines {
+                    postings.extend(tokenize(&line).map(|word| (word, assignment.first_document)));
+                }
+                postings
+            }) {
+                Ok(postings) => postings,
+                Err(e) => {
+                    eprintln!("Failed to read split {:?}: {}", split, e);
+                    return;
+                }
+            },
+            None => {
+                counters.records_read(&assignment.data);
+                assignment
+                    .data
+                    .iter()
+                    .zip(assignment.first_document..)
+                    .flat_map(|(line, document)| tokenize(line).map(move |word| (word, document)))
+                    .collect::<HashSet<(String, i32)>>()
+            }
+        };
+
+        // Write each (word, document) pair once per chunk
+        state.update_many(postings.into_iter().collect()).await;
+    }
+
+    /// Merge the posting lists of the mappers into a sorted list of distinct documents,
+    /// the same whatever order the map tasks ran in or how often they were retried
+    async fn reduce_work<S>(
+        assignment: &Self::ReduceAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        output: &ReduceOutput,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        counters.increment(KEYS_REDUCED, assignment.keys.len() as u64);
+        for key in &assignment.keys {
+            let documents: BTreeSet<i32> = state.get(key).await.into_iter().collect();
+            let documents: Vec<i32> = documents.into_iter().collect();
+            state.replace(key.clone(), documents.clone()).await;
+            output.emit(key.clone(), Self::result_value(&documents));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use async_trait::async_trait;
+use map_reduce_core::config::Config;
+use map_reduce_core::counters::{TaskCounters, KEYS_REDUCED};
+use map_reduce_core::data_chunk::DataChunk;
+use map_reduce_core::input_source::InputSplit;
+use map_reduce_core::job_context::{BroadcastContext, PhaseContext};
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::reduce_output::ReduceOutput;
+use map_reduce_core::result_order::ResultOrder;
+use map_reduce_core::result_value::ResultValue;
+use map_reduce_core::scheduling_policy::TaskSize;
+use map_reduce_core::state_store::StateStore;
+use map_reduce_core::utils::read_split_records;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Records of the larger input without a match in the joined table
+pub const RECORDS_UNMATCHED: &str = "records_unmatched";
+
+/// Map-side (broadcast) join: the smaller input, `join_input`, is broadcast to every mapper
+/// as a table, and the records of the larger one are joined against it as they are streamed
+pub struct JoinProblem;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct MapWorkAssignment {
+    pub chunk_id: usize,
+    pub data: DataChunk,
+    #[serde(default)]
+    pub split: Option<InputSplit>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReduceWorkAssignment {
+    pub keys: Vec<String>,
+}
+
+/// Records of the smaller input by join key, their first field, each with the rest of its line
+/// Broadcast to the mappers as their job context, rather than copied into every assignment
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct JoinContext {
+    pub table: HashMap<String, Vec<String>>,
+}
+
+impl TaskSize for MapWorkAssignment {
+    fn task_size(&self) -> u64 {
+        match &self.split {
+            Some(split) => split.length,
+            None => self.data.len() as u64,
+        }
+    }
+}
+
+impl TaskSize for ReduceWorkAssignment {
+    fn task_size(&self) -> u64 {
+        self.keys.len() as u64
+    }
+}
+
+impl BroadcastContext for MapWorkAssignment {
+    type JobContext = JoinContext;
+}
+
+impl BroadcastContext for ReduceWorkAssignment {
+    type JobContext = ();
+}
+
+#[async_trait]
+impl MapReduceJob for JoinProblem {
+    type Input = Arc<[String]>;
+    type MapAssignment = MapWorkAssignment;
+    type ReduceAssignment = ReduceWorkAssignment;
+    type Context = JoinContext;
+
+    const NAME: &'static str = "join";
+
+    /// The table is left to the join input, loaded by the coordinator
+    fn create_context(
+        _config: &Config,
+        _data: &Self::Input,
+        _targets: Vec<String>,
+    ) -> Self::Context {
+        JoinContext::default()
+    }
+
+    fn map_context(context: &Self::Context) -> JoinContext {
+        context.clone()
+    }
+
+    fn apply_join_input(_config: &Config, context: &mut Self::Context, records: Vec<String>) {
+        for record in records {
+            let (key, value) = split_record(&record);
+            if !key.is_empty() {
+                context
+                    .table
+                    .entry(key.to_string())
+                    .or_default()
+                    .push(value.to_string());
+            }
+        }
+        println!("Broadcasting a join table of {} keys", context.table.len());
+    }
+
+    fn create_map_assignments(
+        data: Self::Input,
+        _context: Self::Context,
+        chunks: Vec<Range<usize>>,
+    ) -> Vec<Self::MapAssignment> {
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, range)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::new(data.clone(), range),
+                split: None,
+            })
+            .collect()
+    }
+
+    fn create_split_assignments(
+        splits: Vec<InputSplit>,
+        _context: Self::Context,
+    ) -> Vec<Self::MapAssignment> {
+        splits
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, split)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::default(),
+                split: Some(split),
+            })
+            .collect()
+    }
+
+    fn create_reduce_assignments(
+        _context: Self::Context,
+        partitions: Vec<Vec<String>>,
+    ) -> Vec<Self::ReduceAssignment> {
+        partitions
+            .into_iter()
+            .map(|keys| ReduceWorkAssignment { keys })
+            .collect()
+    }
+
+    fn result_order() -> ResultOrder {
+        ResultOrder::ByKey
+    }
+
+    async fn map_work<S>(
+        assignment: &Self::MapAssignment,
+        context: &PhaseContext<JoinContext>,
+        state: &S,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        let (joined, unmatched) = match &assignment.split {
+            Some(split) => {
+                match read_split_records(split, counters, |lines| join_records(context, lines)) {
+                    Ok(joined) => joined,
+                    Err(e) => {
+                        eprintln!("Failed to read split {:?}: {}", split, e);
+                        return;
+                    }
+                }
+            }
+            None => {
+                counters.records_read(&assignment.data);
+                join_records(context, assignment.data.iter())
+            }
+        };
+        counters.increment(RECORDS_UNMATCHED, unmatched);
+
+        // Write the chunk's joined records to shared state
+        state.update_many(joined.into_iter().collect()).await;
+    }
+
+    /// Number of input records behind each joined record
+    async fn reduce_work<S>(
+        assignment: &Self::ReduceAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        output: &ReduceOutput,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        counters.increment(KEYS_REDUCED, assignment.keys.len() as u64);
+        let counts = state
+            .merge_reduce(&assignment.keys, Self::partial_aggregate)
+            .await;
+        for (key, count) in assignment.keys.iter().zip(counts) {
+            state.replace(key.clone(), vec![count]).await;
+            output.emit(key.clone(), ResultValue::Scalar(count));
+        }
+    }
+}
+
+/// Join key of a record, its first whitespace-separated field, and the rest of the line
+fn split_record(record: &str) -> (&str, &str) {
+    let record = record.trim();
+    match record.split_once(char::is_whitespace) {
+        Some((key, rest)) => (key, rest.trim_start()),
+        None => (record, ""),
+    }
+}
+
+/// Inner join of a chunk's records against the table: every match of a record yields the joined
+/// record `<key> <record rest> | <table rest>`, counted, along with the records without a match
+/// Empty lines are skipped
+fn join_records(
+    context: &JoinContext,
+    lines: impl Iterator<Item = impl AsRef<str>>,
+) -> (HashMap<String, i32>, u64) {
+    let mut joined = HashMap::new();
+    let mut unmatched = 0;
+    for line in lines {
+        let (key, rest) = split_record(line.as_ref());
+        if key.is_empty() {
+            continue;
+        }
+        let Some(matches) = context.table.get(key) else {
+            unmatched += 1;
+            continue;
+        };
+        let left = if rest.is_empty() {
+            key.to_string()
+        } else {
+            format!("{} {}", key, rest)
+        };
+        for value in matches {
+            let record = if value.is_empty() {
+                left.clone()
+            } else {
+                format!("{} | {}", left, value)
+            };
+            *joined.entry(record).or_insert(0) += 1;
+        }
+    }
+    (joined, unmatched)
+}
@@ -0,0 +1,75 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+//! Compares the size and encode/decode latency of map assignments
+//! encoded as JSON (the former wire format) and varint bincode (the current one)
+//!
+//! Run with: cargo run --release -p map-reduce-process-rpc --example work_message_encoding
+
+use bincode::Options;
+use map_reduce_core::utils::generate_random_string;
+use map_reduce_word_search::MapWorkAssignment;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 20;
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let mut rng = rand::rng();
+
+    println!(
+        "{:>10} | {:>12} {:>12} | {:>10} {:>10} | {:>10} {:>10}",
+        "strings", "json bytes", "bincode", "json enc", "bin enc", "json dec", "bin dec"
+    );
+
+    for chunk_size in [1_000, 10_000, 100_000] {
+        let assignment = MapWorkAssignment {
+            chunk_id: 0,
+            data: (0..chunk_size)
+                .map(|_| generate_random_string(&mut rng, 15))
+                .collect(),
+            split: None,
+        };
+
+        let json = serde_json::to_vec(&assignment).unwrap();
+        let binary = bincode::DefaultOptions::new()
+            .serialize(&assignment)
+            .unwrap();
+
+        let json_encode = measure(|| {
+            serde_json::to_vec(&assignment).unwrap();
+        });
+        let binary_encode = measure(|| {
+            bincode::DefaultOptions::new()
+                .serialize(&assignment)
+                .unwrap();
+        });
+        let json_decode = measure(|| {
+            serde_json::from_slice::<MapWorkAssignment>(&json).unwrap();
+        });
+        let binary_decode = measure(|| {
+            bincode::DefaultOptions::new()
+                .deserialize::<MapWorkAssignment>(&binary)
+                .unwrap();
+        });
+
+        println!(
+            "{:>10} | {:>12} {:>12} | {:>10.2?} {:>10.2?} | {:>10.2?} {:>10.2?}",
+            chunk_size,
+            json.len(),
+            binary.len(),
+            json_encode,
+            binary_encode,
+            json_decode,
+            binary_decode
+        );
+    }
+}
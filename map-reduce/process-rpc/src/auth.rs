@@ -0,0 +1,72 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::job_token;
+use map_reduce_core::config::JOB_TOKEN_ENV;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::Channel;
+use tonic::{Request, Status};
+
+/// Request metadata carrying the job token
+pub const JOB_TOKEN_HEADER: &str = "x-job-token";
+
+/// Channel whose requests carry the job token
+pub type AuthChannel = InterceptedService<Channel, AttachToken>;
+
+/// Random job token, issued by a coordinator started without one
+pub fn generate_token() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// Job token given in `JOB_TOKEN_ENV`, if any
+pub fn configured_token() -> Option<String> {
+    std::env::var(JOB_TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty())
+}
+
+/// Job token of a coordinator: the one given in `JOB_TOKEN_ENV`, so chaos tests can reach
+/// its workers' admin services, or a random one
+pub fn coordinator_token() -> String {
+    configured_token().unwrap_or_else(generate_token)
+}
+
+/// Client interceptor attaching the job token to every request
+#[derive(Clone, Copy)]
+pub struct AttachToken;
+
+impl Interceptor for AttachToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = job_token()
+            .parse()
+            .map_err(|_| Status::internal("Job token is not valid metadata"))?;
+        request.metadata_mut().insert(JOB_TOKEN_HEADER, token);
+        Ok(request)
+    }
+}
+
+/// Server interceptor rejecting requests without the job token
+#[derive(Clone, Copy)]
+pub struct CheckToken;
+
+impl Interceptor for CheckToken {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let presented = request
+            .metadata()
+            .get(JOB_TOKEN_HEADER)
+            .map(|value| value.as_bytes())
+            .unwrap_or_default();
+        if constant_time_eq(presented, job_token().as_bytes()) {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("Missing or invalid job token"))
+        }
+    }
+}
+
+/// Compare without returning early, so the time taken does not reveal a token's prefix
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
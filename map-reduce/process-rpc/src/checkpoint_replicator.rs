@@ -0,0 +1,116 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::{AttachToken, AuthChannel};
+use crate::config::{HEARTBEAT_INTERVAL_MS, WORK_MESSAGE_VERSION};
+use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format;
+use map_reduce_core::coordinator_checkpoint::{CheckpointUpdate, CoordinatorCheckpoint};
+use proto::coordinator_replication_service_client::CoordinatorReplicationServiceClient;
+use proto::{CheckpointUpdateMessage, LeaseRequest};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// Ships the primary coordinator's orchestrator state to a standby coordinator
+/// Updates are sent in order; a full snapshot is sent instead whenever the standby missed one
+pub struct CheckpointReplicator {
+    updates: UnboundedSender<CheckpointUpdate>,
+    handle: JoinHandle<()>,
+}
+
+impl CheckpointReplicator {
+    pub fn start(standby_addr: String) -> Self {
+        let (updates, rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(replicate(standby_addr, rx));
+        Self { updates, handle }
+    }
+
+    pub fn record(&self, update: CheckpointUpdate) {
+        let _ = self.updates.send(update);
+    }
+
+    /// Tell the standby the job is complete, waiting for pending updates to be shipped
+    pub async fn finish(self) {
+        self.record(CheckpointUpdate::JobFinished);
+        let _ = self.handle.await;
+    }
+}
+
+/// Forward updates to the standby, renewing the primary's lease while there are none
+async fn replicate(standby_addr: String, mut updates: UnboundedReceiver<CheckpointUpdate>) {
+    let channel = match tls::endpoint(&standby_addr) {
+        Ok(endpoint) => endpoint.connect_lazy(),
+        Err(e) => {
+            eprintln!("Invalid standby address {}: {}", standby_addr, e);
+            return;
+        }
+    };
+    let mut client = CoordinatorReplicationServiceClient::with_interceptor(channel, AttachToken);
+    let lease_interval = Duration::from_millis(HEARTBEAT_INTERVAL_MS);
+
+    // Local copy of the replicated state, used to resynchronize the standby
+    let mut checkpoint = CoordinatorCheckpoint::default();
+    let mut in_sync = false;
+    let mut connected = true;
+
+    loop {
+        match tokio::time::timeout(lease_interval, updates.recv()).await {
+            Ok(Some(update)) => {
+                let finished = matches!(update, CheckpointUpdate::JobFinished);
+                checkpoint.apply(update.clone());
+                let update = if in_sync {
+                    update
+                } else {
+                    CheckpointUpdate::Snapshot(checkpoint.clone())
+                };
+                in_sync = send_update(&mut client, &update, &mut connected).await;
+                if finished {
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(_) if in_sync => {
+                in_sync = client
+                    .renew_lease(tonic::Request::new(LeaseRequest {}))
+                    .await
+                    .is_ok();
+            }
+            Err(_) => {
+                let snapshot = CheckpointUpdate::Snapshot(checkpoint.clone());
+                in_sync = send_update(&mut client, &snapshot, &mut connected).await;
+            }
+        }
+    }
+}
+
+/// Send one update, warning once when the standby becomes unreachable
+async fn send_update(
+    client: &mut CoordinatorReplicationServiceClient<AuthChannel>,
+    update: &CheckpointUpdate,
+    connected: &mut bool,
+) -> bool {
+    let request = tonic::Request::new(CheckpointUpdateMessage {
+        version: WORK_MESSAGE_VERSION,
+        update: wire_format::encode(update),
+    });
+
+    match client.replicate(request).await {
+        Ok(_) => {
+            *connected = true;
+            true
+        }
+        Err(e) => {
+            if *connected {
+                eprintln!(
+                    "⚠️  Failed to replicate checkpoint to standby: {}",
+                    e.message()
+                );
+            }
+            *connected = false;
+            false
+        }
+    }
+}
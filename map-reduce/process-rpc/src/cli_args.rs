@@ -0,0 +1,244 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::{NetworkConfig, TlsConfig, WorkerLimits, MAPPER_BASE_PORT, REDUCER_BASE_PORT};
+use crate::work_transport::{UnaryTransport, WorkTransport};
+use clap::{Args, Subcommand};
+use map_reduce_core::job_kind::JobKind;
+
+/// Worker roles, started by the coordinator as
+/// `<exe> mapper|reducer --job .. --transport .. --task ..`,
+/// and the chaos client forcing faults onto running workers
+#[derive(Subcommand)]
+pub enum WorkerRole {
+    /// Run a mapper process
+    Mapper(WorkerArgs),
+    /// Run a reducer process
+    Reducer(WorkerArgs),
+    /// Force a fault onto a running worker through its admin service (unary transport)
+    Chaos(ChaosArgs),
+}
+
+#[derive(Args)]
+pub struct WorkerArgs {
+    /// Job whose task this worker runs
+    #[arg(long, default_value_t = JobKind::WordSearch)]
+    pub job: JobKind,
+
+    /// How work and status messages travel between coordinator and workers (unary, stream)
+    #[arg(long, default_value = UnaryTransport::NAME)]
+    pub transport: String,
+
+    /// Worker task generated by the coordinator (JSON)
+    #[arg(long)]
+    pub task: String,
+
+    #[command(flatten)]
+    pub limits: WorkerLimitsArgs,
+
+    #[command(flatten)]
+    pub tls: TlsArgs,
+}
+
+#[derive(Args)]
+pub struct ChaosArgs {
+    /// Work address of the worker, e.g. 127.0.0.1:30001
+    #[arg(long)]
+    pub worker: String,
+
+    /// Fault to force: crash, hang, corrupt-results, or clear to lift the forced fault
+    #[arg(long)]
+    pub fault: String,
+
+    #[command(flatten)]
+    pub tls: TlsArgs,
+}
+
+#[derive(Args)]
+pub struct CoordinatorArgs {
+    /// Built-in job to run (word-search, word-count, inverted-index, sort, join), overriding the configured one
+    #[arg(long)]
+    pub job: Option<JobKind>,
+
+    /// Failure seed logged by a previous run, replaying its injected failures and stragglers
+    #[arg(long)]
+    pub replay_seed: Option<u64>,
+
+    /// How work and status messages travel between coordinator and workers (unary, stream)
+    #[arg(long, default_value = UnaryTransport::NAME)]
+    pub transport: String,
+
+    /// Address of a standby coordinator receiving this coordinator's orchestrator state
+    #[arg(long)]
+    pub replicate_to: Option<String>,
+
+    /// Run as a standby coordinator listening on this address,
+    /// taking over the job if the primary goes silent
+    #[arg(long)]
+    pub standby: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    #[command(flatten)]
+    pub worker_limits: WorkerLimitsArgs,
+
+    #[command(flatten)]
+    pub tls: TlsArgs,
+}
+
+/// Addresses and ports of the coordinator and its workers
+#[derive(Args)]
+pub struct NetworkArgs {
+    /// Interface the coordinator's services listen on (e.g. 0.0.0.0 for remote workers)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind_host: String,
+
+    /// Host workers use to reach the coordinator
+    #[arg(long, default_value = "127.0.0.1")]
+    pub advertise_host: String,
+
+    /// Host the coordinator uses to reach the workers (unary transport)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub worker_host: String,
+
+    /// Work port of the first mapper, the others use the following ports (unary transport)
+    /// 0 lets every mapper bind an ephemeral port and announce it to the coordinator
+    #[arg(long, default_value_t = MAPPER_BASE_PORT)]
+    pub mapper_base_port: u16,
+
+    /// Work port of the first reducer, the others use the following ports (unary transport)
+    /// 0 lets every reducer bind an ephemeral port and announce it to the coordinator
+    #[arg(long, default_value_t = REDUCER_BASE_PORT)]
+    pub reducer_base_port: u16,
+
+    /// Port of the first state server, the other shards use the following ports (random if unset)
+    #[arg(long)]
+    pub state_port: Option<u16>,
+
+    /// Number of state servers the shared state is partitioned across by key hash
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub state_shards: u16,
+
+    /// Number of aggregators gathering the final results of the reduce tasks on their way to the
+    /// coordinator (0 = reducers report them with their completions)
+    #[arg(long, default_value_t = 0)]
+    pub aggregators: u16,
+
+    /// Remote shell command starting workers on the worker host, e.g. "ssh worker-1"
+    /// (the worker binary must be installed at the same path there)
+    #[arg(long)]
+    pub worker_launcher: Option<String>,
+}
+
+impl NetworkArgs {
+    pub fn to_config(&self) -> NetworkConfig {
+        NetworkConfig {
+            bind_host: self.bind_host.clone(),
+            advertise_host: self.advertise_host.clone(),
+            worker_host: self.worker_host.clone(),
+            mapper_base_port: self.mapper_base_port,
+            reducer_base_port: self.reducer_base_port,
+            state_port: self.state_port,
+            state_shards: self.state_shards as usize,
+            aggregators: self.aggregators as usize,
+            worker_launcher: self
+                .worker_launcher
+                .iter()
+                .flat_map(|launcher| launcher.split_whitespace().map(String::from))
+                .collect(),
+        }
+    }
+}
+
+/// Resource limits and isolation of the worker processes
+#[derive(Args)]
+pub struct WorkerLimitsArgs {
+    /// Address space limit of each worker process in MiB (Unix only)
+    #[arg(long)]
+    pub worker_memory_mb: Option<u64>,
+
+    /// CPU niceness of the worker processes, from -20 (favored) to 19 (Unix only)
+    #[arg(long, allow_hyphen_values = true, value_parser = clap::value_parser!(i32).range(-20..=19))]
+    pub worker_nice: Option<i32>,
+
+    /// Directory in which each worker process gets its own working directory
+    #[arg(long)]
+    pub worker_dir: Option<String>,
+}
+
+impl WorkerLimitsArgs {
+    pub fn to_limits(&self) -> WorkerLimits {
+        WorkerLimits {
+            memory_limit_mb: self.worker_memory_mb,
+            nice: self.worker_nice,
+            work_dir: self.worker_dir.clone(),
+        }
+    }
+
+    /// Command line arguments passing these limits on to a worker process
+    pub fn from_limits(limits: &WorkerLimits) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(memory_mb) = limits.memory_limit_mb {
+            args.extend(["--worker-memory-mb".to_string(), memory_mb.to_string()]);
+        }
+        if let Some(nice) = limits.nice {
+            args.extend(["--worker-nice".to_string(), nice.to_string()]);
+        }
+        if let Some(dir) = &limits.work_dir {
+            args.extend(["--worker-dir".to_string(), dir.clone()]);
+        }
+        args
+    }
+}
+
+/// TLS of the gRPC channels between the coordinator, its workers and its standby
+#[derive(Args)]
+pub struct TlsArgs {
+    /// CA certificate (PEM) the services' certificate is verified against, enables TLS
+    #[arg(long, requires_all = ["tls_cert", "tls_key"])]
+    pub tls_ca: Option<String>,
+
+    /// Certificate (PEM) presented by every service of the job, workers included
+    #[arg(long, requires = "tls_ca")]
+    pub tls_cert: Option<String>,
+
+    /// Private key (PEM) of the certificate
+    #[arg(long, requires = "tls_ca")]
+    pub tls_key: Option<String>,
+
+    /// Name the certificate is issued for, checked by every client
+    #[arg(long, default_value = "localhost")]
+    pub tls_domain: String,
+}
+
+impl TlsArgs {
+    pub fn to_config(&self) -> Option<TlsConfig> {
+        match (&self.tls_ca, &self.tls_cert, &self.tls_key) {
+            (Some(ca), Some(cert), Some(key)) => Some(TlsConfig {
+                ca_path: ca.clone(),
+                cert_path: cert.clone(),
+                key_path: key.clone(),
+                domain: self.tls_domain.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Command line arguments passing this configuration on to a worker process
+    pub fn from_config(config: Option<&TlsConfig>) -> Vec<String> {
+        let Some(config) = config else {
+            return Vec::new();
+        };
+        [
+            ("--tls-ca", &config.ca_path),
+            ("--tls-cert", &config.cert_path),
+            ("--tls-key", &config.key_path),
+            ("--tls-domain", &config.domain),
+        ]
+        .into_iter()
+        .flat_map(|(flag, value)| [flag.to_string(), value.clone()])
+        .collect()
+    }
+}
@@ -2,5 +2,211 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use map_reduce_core::kill_switch::KillSwitch;
+use std::sync::OnceLock;
+
 pub const MAPPER_BASE_PORT: u16 = 30000;
 pub const REDUCER_BASE_PORT: u16 = 40000;
+
+/// How often workers report liveness to the coordinator
+pub const HEARTBEAT_INTERVAL_MS: u64 = 500;
+/// Workers silent for longer than this are declared dead and their work reassigned
+pub const HEARTBEAT_TIMEOUT_MS: u64 = 3000;
+
+/// How long the coordinator waits for a new gRPC worker's health service to report it is serving
+pub const READINESS_TIMEOUT_MS: u64 = 5000;
+/// How long the coordinator waits for a worker on an ephemeral port to register its work port
+pub const WORK_PORT_TIMEOUT_MS: u64 = 5000;
+/// How long a new worker has to open its stream to the coordinator's stream hub,
+/// after which its queued commands are dropped and sending it work fails
+pub const STREAM_CONNECT_TIMEOUT_MS: u64 = 10000;
+/// How often the coordinator probes the health service of each gRPC worker once it is serving
+pub const HEALTH_CHECK_INTERVAL_MS: u64 = 1000;
+/// Workers failing this many health checks in a row are reported unhealthy and replaced
+pub const HEALTH_CHECK_FAILURES: u32 = 3;
+
+/// A standby coordinator takes over once the primary has been silent for this long
+pub const FAILOVER_TIMEOUT_MS: u64 = 3000;
+/// Largest checkpoint update a standby accepts, above tonic's 4MB default,
+/// as the state snapshot of a phase grows with the number of keys
+pub const MAX_CHECKPOINT_MESSAGE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Version of the binary encoding of work message payloads
+/// Bump whenever the layout of assignments, completion tokens or coordinator checkpoints changes
+pub const WORK_MESSAGE_VERSION: u32 = 9;
+
+/// Hosts, ports and worker launcher of a coordinator, so it can run with workers on other machines
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    /// Interface the coordinator's services listen on
+    pub bind_host: String,
+    /// Host workers use to reach the coordinator's services
+    pub advertise_host: String,
+    /// Host the coordinator uses to reach the workers' work receivers
+    pub worker_host: String,
+    /// Work port of the first mapper, 0 to let every mapper bind an ephemeral port
+    pub mapper_base_port: u16,
+    /// Work port of the first reducer, 0 to let every reducer bind an ephemeral port
+    pub reducer_base_port: u16,
+    /// Port of the first state server, the other shards use the following ports (None = random)
+    pub state_port: Option<u16>,
+    /// Number of state servers the shared state is partitioned across by key hash
+    pub state_shards: usize,
+    /// Number of aggregators the final results are gathered through (0 = reported with the
+    /// reducers' completions)
+    pub aggregators: usize,
+    /// Command prefix used to start worker processes, e.g. `ssh worker-1` (empty = run locally)
+    pub worker_launcher: Vec<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            bind_host: "127.0.0.1".to_string(),
+            advertise_host: "127.0.0.1".to_string(),
+            worker_host: "127.0.0.1".to_string(),
+            mapper_base_port: MAPPER_BASE_PORT,
+            reducer_base_port: REDUCER_BASE_PORT,
+            state_port: None,
+            state_shards: 1,
+            aggregators: 0,
+            worker_launcher: Vec::new(),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Work port of a mapper, 0 if mappers bind ephemeral ports
+    pub fn mapper_port(&self, id: usize) -> u16 {
+        worker_port(self.mapper_base_port, id)
+    }
+
+    /// Work port of a reducer, 0 if reducers bind ephemeral ports
+    pub fn reducer_port(&self, id: usize) -> u16 {
+        worker_port(self.reducer_base_port, id)
+    }
+}
+
+fn worker_port(base_port: u16, id: usize) -> u16 {
+    match base_port {
+        0 => 0,
+        base_port => base_port + id as u16,
+    }
+}
+
+static NETWORK: OnceLock<NetworkConfig> = OnceLock::new();
+
+/// Set the coordinator's network configuration, before any service is started
+pub fn set_network(config: NetworkConfig) {
+    NETWORK
+        .set(config)
+        .expect("Network configuration already set");
+}
+
+/// The coordinator's network configuration (local defaults if never set)
+pub fn network() -> &'static NetworkConfig {
+    NETWORK.get_or_init(NetworkConfig::default)
+}
+
+/// Resource limits and isolation applied to every worker process
+#[derive(Debug, Clone, Default)]
+pub struct WorkerLimits {
+    /// Address space limit of a worker in MiB (None = unlimited)
+    pub memory_limit_mb: Option<u64>,
+    /// CPU niceness of the workers (None = inherited from the coordinator)
+    pub nice: Option<i32>,
+    /// Directory holding a separate working directory per worker (None = inherited)
+    pub work_dir: Option<String>,
+}
+
+static WORKER_LIMITS: OnceLock<WorkerLimits> = OnceLock::new();
+
+/// Set the limits of the worker processes, before any worker is started
+pub fn set_worker_limits(limits: WorkerLimits) {
+    WORKER_LIMITS
+        .set(limits)
+        .expect("Worker limits already set");
+}
+
+/// The limits of the worker processes (none if never set)
+pub fn worker_limits() -> &'static WorkerLimits {
+    WORKER_LIMITS.get_or_init(WorkerLimits::default)
+}
+
+/// TLS securing every gRPC channel of the job: work, status, state and standby replication
+/// Every service presents the same certificate, which clients verify against the CA for `domain`
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// CA certificate (PEM) the services' certificate is verified against
+    pub ca_path: String,
+    /// Certificate (PEM) presented by every service
+    pub cert_path: String,
+    /// Private key (PEM) of the certificate
+    pub key_path: String,
+    /// Name the certificate is issued for
+    pub domain: String,
+}
+
+static TLS: OnceLock<Option<TlsConfig>> = OnceLock::new();
+
+/// Set the TLS configuration (None = plaintext), before any service is started or reached
+pub fn set_tls(config: Option<TlsConfig>) {
+    TLS.set(config).expect("TLS configuration already set");
+}
+
+/// The TLS configuration of the channels, if they are encrypted
+pub fn tls() -> Option<&'static TlsConfig> {
+    TLS.get_or_init(|| None).as_ref()
+}
+
+static WORK_COMPRESSION: OnceLock<bool> = OnceLock::new();
+
+/// Set whether assignments are compressed, before any work is sent
+pub fn set_work_compression(enabled: bool) {
+    WORK_COMPRESSION
+        .set(enabled)
+        .expect("Work compression already set");
+}
+
+/// Whether assignments sent to workers are compressed (off if never set)
+pub fn work_compression() -> bool {
+    *WORK_COMPRESSION.get_or_init(|| false)
+}
+
+static AGGREGATORS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Set the addresses of the aggregators of the final results, once they are started
+pub fn set_aggregators(addrs: Vec<String>) {
+    AGGREGATORS.set(addrs).expect("Aggregators already set");
+}
+
+/// Addresses of the aggregators reduce tasks hand their results to (none if never set)
+pub fn aggregators() -> &'static [String] {
+    AGGREGATORS.get_or_init(Vec::new)
+}
+
+static KILL_SWITCH: OnceLock<KillSwitch> = OnceLock::new();
+
+/// Set the kill switch of this worker's task, before its work service is started
+pub fn set_kill_switch(kill_switch: KillSwitch) {
+    KILL_SWITCH
+        .set(kill_switch)
+        .expect("Kill switch already set");
+}
+
+/// Kill switch the admin service forces faults through (none outside worker processes)
+pub fn kill_switch() -> Option<&'static KillSwitch> {
+    KILL_SWITCH.get()
+}
+
+static JOB_TOKEN: OnceLock<String> = OnceLock::new();
+
+/// Set the token authenticating the job's RPCs, before any service is started or reached
+pub fn set_job_token(token: String) {
+    JOB_TOKEN.set(token).expect("Job token already set");
+}
+
+/// Token every work, status and state RPC of the job carries
+pub fn job_token() -> &'static str {
+    JOB_TOKEN.get().expect("Job token not set")
+}
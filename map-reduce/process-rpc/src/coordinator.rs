@@ -0,0 +1,400 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::{configured_token, coordinator_token};
+use crate::checkpoint_replicator::CheckpointReplicator;
+use crate::cli_args::CoordinatorArgs;
+use crate::config::{
+    aggregators, network, set_aggregators, set_job_token, set_network, set_tls,
+    set_work_compression, set_worker_limits, worker_limits,
+};
+use crate::grpc_aggregator::{collect_aggregated, start_aggregator};
+use crate::grpc_shutdown_signal::GrpcShutdownSignal;
+use crate::grpc_state_server::start_state_server;
+use crate::grpc_state_store::GrpcStateStore;
+use crate::grpc_worker_runtime::{MapperProcessRuntime, ReducerProcessRuntime};
+use crate::mapper::{Mapper, MapperFactory};
+use crate::reducer::{Reducer, ReducerFactory};
+use crate::standby_coordinator::StandbyCoordinator;
+use crate::state_shards::StateShards;
+use crate::tls;
+use crate::work_transport::{StreamTransport, UnaryTransport, WireMessage, WorkTransport};
+use async_trait::async_trait;
+use map_reduce_core::config::{Config, JOB_TOKEN_ENV};
+use map_reduce_core::coordinator_checkpoint::{CheckpointUpdate, CoordinatorCheckpoint, JobPhase};
+use map_reduce_core::failure_injection::{resolve_failure_seed, FailureInjection};
+use map_reduce_core::job_control::JobControl;
+use map_reduce_core::job_id::JobId;
+use map_reduce_core::job_kind::JobKind;
+use map_reduce_core::job_queue::JobQueue;
+use map_reduce_core::job_runner::{CoordinatorHooks, JobRunner};
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::output_result_sink::OutputResultSink;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::result_order::ResultOrder;
+use map_reduce_core::utils::initialize_phase;
+use map_reduce_inverted_index::InvertedIndexProblem;
+use map_reduce_join::JoinProblem;
+use map_reduce_sort::SortProblem;
+use map_reduce_word_count::WordCountProblem;
+use map_reduce_word_search::WordSearchProblem;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info_span, Instrument};
+
+/// Run the coordinator as the primary, or as a standby taking over a silent primary
+pub async fn run_coordinator_role(mut config: Config, args: CoordinatorArgs) -> Result<(), String> {
+    if let Some(job) = args.job {
+        config.job = job;
+    }
+    if args.replay_seed.is_some() {
+        config.failure_seed = args.replay_seed;
+    }
+    set_network(args.network.to_config());
+    set_worker_limits(args.worker_limits.to_limits());
+    set_tls(args.tls.to_config());
+    if let Err(e) = tls::load_certificates() {
+        panic!("Failed to load TLS certificates: {}", e);
+    }
+    // The primary and its standby authenticate each other with the token, so they must share it
+    if (args.standby.is_some() || args.replicate_to.is_some()) && configured_token().is_none() {
+        return Err(format!(
+            "--standby and --replicate-to need the token shared with the other coordinator in {}",
+            JOB_TOKEN_ENV
+        ));
+    }
+    set_job_token(coordinator_token());
+
+    match args.transport.as_str() {
+        UnaryTransport::NAME => run_role::<UnaryTransport>(config, args).await,
+        StreamTransport::NAME => run_role::<StreamTransport>(config, args).await,
+        transport => panic!("Unknown transport: {}", transport),
+    }
+}
+
+/// Run the whole job on this machine, with worker processes talking over the given transport
+pub async fn run_local(config: Config, transport: &str) -> Result<(), String> {
+    set_job_token(coordinator_token());
+    let control = output_control(&config);
+    match transport {
+        UnaryTransport::NAME => {
+            run_coordinator::<UnaryTransport>(config, None, None, control).await
+        }
+        StreamTransport::NAME => {
+            run_coordinator::<StreamTransport>(config, None, None, control).await
+        }
+        transport => panic!("Unknown transport: {}", transport),
+    }
+}
+
+async fn run_role<T: WorkTransport>(config: Config, args: CoordinatorArgs) -> Result<(), String> {
+    match args.standby {
+        Some(addr) => run_standby::<T>(config, &addr).await,
+        None => {
+            let control = output_control(&config);
+            run_coordinator::<T>(config, args.replicate_to, None, control).await
+        }
+    }
+}
+
+async fn run_standby<T: WorkTransport>(config: Config, addr: &str) -> Result<(), String> {
+    let standby = StandbyCoordinator::start(addr)
+        .await
+        .expect("Failed to start standby coordinator");
+    println!("=== STANDBY COORDINATOR ({}) ===", addr);
+    println!("Waiting for the primary coordinator...");
+
+    match standby.wait_for_takeover().await {
+        Some(checkpoint) => {
+            eprintln!(
+                "🛟 Primary coordinator went silent, taking over job {} and rerunning its stage {} {:?} phase",
+                checkpoint.job_id, checkpoint.stage, checkpoint.phase
+            );
+            let control = output_control(&config);
+            run_coordinator::<T>(config, None, Some(checkpoint), control).await
+        }
+        None => {
+            println!("Primary coordinator completed the job, standing down");
+            Ok(())
+        }
+    }
+}
+
+/// Control writing the final results to the configured outputs, cancelling the jobs on Ctrl+C
+fn output_control(config: &Config) -> JobControl {
+    let control = JobControl::new(config, Box::new(OutputResultSink::new()));
+    let cancellation = control.cancellation.clone();
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl+C");
+        println!("\n\n=== Ctrl+C received, initiating shutdown ===");
+        cancellation.cancel();
+    });
+    control
+}
+
+async fn run_coordinator<T: WorkTransport>(
+    config: Config,
+    replicate_to: Option<String>,
+    resume: Option<CoordinatorCheckpoint>,
+    control: JobControl,
+) -> Result<(), String> {
+    set_work_compression(config.compress_work);
+    let span = info_span!("pool", job = %config.job);
+    async move {
+        match config.job {
+            JobKind::WordSearch => {
+                run_pool::<T, WordSearchProblem>(config, replicate_to, resume, control).await
+            }
+            JobKind::WordCount => {
+                run_pool::<T, WordCountProblem>(config, replicate_to, resume, control).await
+            }
+            JobKind::InvertedIndex => {
+                run_pool::<T, InvertedIndexProblem>(config, replicate_to, resume, control).await
+            }
+            JobKind::Sort => {
+                run_pool::<T, SortProblem>(config, replicate_to, resume, control).await
+            }
+            JobKind::Join => {
+                run_pool::<T, JoinProblem>(config, replicate_to, resume, control).await
+            }
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+/// Start a pool of workers and run the queued jobs on it, replicating the orchestrator state
+/// to a standby if one is given, or resuming the job of a checkpoint
+async fn run_pool<T, P>(
+    config: Config,
+    replicate_to: Option<String>,
+    resume: Option<CoordinatorCheckpoint>,
+    mut control: JobControl,
+) -> Result<(), String>
+where
+    T: WorkTransport,
+    P: MapReduceJob<Input = Arc<[String]>>,
+    P::MapAssignment: WireMessage,
+    P::ReduceAssignment: WireMessage,
+{
+    let start_time = Instant::now();
+
+    println!(
+        "=== MAP-REDUCE {} (Proto-RPC-Tonic/gRPC, {} transport) ===",
+        P::NAME.to_uppercase().replace('-', " "),
+        T::NAME
+    );
+    config.print_summary();
+    let failure_seed = resolve_failure_seed(&config);
+
+    let mut queue = JobQueue::from_config(&config).expect("Failed to queue jobs");
+    // Workers running in directories of their own resolve no path against ours
+    if worker_limits().work_dir.is_some() {
+        for job_config in queue.configs_mut() {
+            job_config
+                .absolutize_paths()
+                .expect("Failed to resolve the job's paths");
+        }
+    }
+
+    // Start one gRPC State Server per shard of the shared state
+    let local_state =
+        StateShards::new(&config, network().state_shards).expect("Failed to create state store");
+
+    // Use the configured state server port, or pick a random one
+    let state_port = network()
+        .state_port
+        .unwrap_or_else(|| rand::random::<u16>() % 10000 + 20000);
+    let mut _state_handles = Vec::new();
+    let mut shard_addrs = Vec::new();
+    for (shard, shard_state) in local_state.shards().iter().enumerate() {
+        let port = state_port + shard as u16;
+        _state_handles.push(
+            start_state_server(shard_state.clone(), port)
+                .await
+                .expect("Failed to start gRPC state server"),
+        );
+        shard_addrs.push(format!("{}:{}", network().advertise_host, port));
+    }
+    if shard_addrs.len() > 1 {
+        println!(
+            "State partitioned across {} servers (ports {}-{})",
+            shard_addrs.len(),
+            state_port,
+            state_port + shard_addrs.len() as u16 - 1
+        );
+    }
+
+    let grpc_state = GrpcStateStore::new(shard_addrs);
+
+    // Final results travel from the reducers through the aggregators, if any, to the coordinator
+    let mut aggregator_handles = Vec::new();
+    for _ in 0..network().aggregators {
+        aggregator_handles.push(
+            start_aggregator()
+                .await
+                .expect("Failed to start gRPC aggregator"),
+        );
+    }
+    if !aggregator_handles.is_empty() {
+        let addrs: Vec<String> = aggregator_handles
+            .iter()
+            .map(|handle| handle.addr.clone())
+            .collect();
+        println!("Final results gathered through {} aggregators", addrs.len());
+        set_aggregators(addrs);
+    }
+    let shutdown_signal = GrpcShutdownSignal::start().await;
+
+    // Cancelling the jobs cancels them on the whole cluster, including remote workers
+    let cancellation = control.cancellation.clone();
+    let cancelled_signal = shutdown_signal.clone();
+    let cancel_watch = tokio::spawn(async move {
+        cancellation.cancelled().await;
+        cancelled_signal.shutdown();
+    });
+
+    println!("\nStarting MapReduce with gRPC...");
+
+    // Create mapper factory
+    let mapper_factory =
+        MapperFactory::<P, GrpcStateStore, T, MapperProcessRuntime<T>, GrpcShutdownSignal>::new(
+            grpc_state.clone(),
+            shutdown_signal.clone(),
+            FailureInjection::mappers(&config, failure_seed),
+        );
+
+    // Initialize mapper phase
+    let mut mappers = initialize_phase::<
+        Mapper<P, GrpcStateStore, T, MapperProcessRuntime<T>, GrpcShutdownSignal>,
+        T::Synchronization,
+        _,
+    >(
+        config.num_mappers,
+        mapper_factory,
+        config.mapper_timeout_ms,
+        config.max_task_retries,
+        config.max_task_timeouts,
+        config.straggler_factor,
+        config.scheduling_policy,
+        false,
+        &config.failure_domains,
+    )
+    .await;
+
+    println!("Workers initialized, starting map phase...");
+
+    // Create reducer factory
+    let reducer_factory =
+        ReducerFactory::<P, GrpcStateStore, T, ReducerProcessRuntime<T>, GrpcShutdownSignal>::new(
+            grpc_state.clone(),
+            shutdown_signal.clone(),
+            FailureInjection::reducers(&config, failure_seed),
+            config.top_k,
+        );
+
+    // Initialize reducer phase
+    let mut reducers = initialize_phase::<
+        Reducer<P, GrpcStateStore, T, ReducerProcessRuntime<T>, GrpcShutdownSignal>,
+        T::Synchronization,
+        _,
+    >(
+        config.num_reducers,
+        reducer_factory,
+        config.reducer_timeout_ms,
+        config.max_task_retries,
+        config.max_task_timeouts,
+        config.straggler_factor,
+        config.scheduling_policy,
+        config.partition_affinity,
+        &config.failure_domains,
+    )
+    .await;
+
+    println!("Reducers initialized, starting reduce phase...");
+
+    let hooks = ClusterHooks {
+        replicator: replicate_to.map(CheckpointReplicator::start),
+    };
+    // Run the queued jobs on the same workers, each on a state of its own
+    let outcome = JobRunner {
+        variant: format!("process-rpc/{}", T::NAME),
+        state: &local_state,
+        shutdown_signal: &shutdown_signal,
+        hooks: &hooks,
+    }
+    .run::<P, _, _, _, _, _, _>(
+        &config,
+        queue,
+        resume,
+        &mut mappers,
+        &mut reducers,
+        &mut control,
+    )
+    .await;
+
+    // Stop the worker processes, including those started on other machines
+    println!("\n=== SHUTTING DOWN ===");
+    cancel_watch.abort();
+    shutdown_signal.shutdown_and_wait().await;
+
+    drop(mappers);
+    drop(reducers);
+
+    if let Some(replicator) = hooks.replicator {
+        replicator.finish().await;
+    }
+
+    let elapsed = start_time.elapsed();
+    println!("\n=== PROGRAM COMPLETE ===");
+    println!("Total time: {:.2}s", elapsed.as_secs_f64());
+    outcome
+}
+
+/// Job loop hooks of the cluster: progress replicated to the standby coordinator, if any,
+/// and final results gathered through the aggregators, if any
+struct ClusterHooks {
+    replicator: Option<CheckpointReplicator>,
+}
+
+#[async_trait]
+impl CoordinatorHooks for ClusterHooks {
+    fn job_started(&self, job_id: JobId, job_index: usize, data_seed: u64) {
+        if let Some(replicator) = &self.replicator {
+            replicator.record(CheckpointUpdate::JobStarted {
+                job_id,
+                job_index,
+                data_seed,
+            });
+        }
+    }
+
+    fn phase_started(
+        &self,
+        stage: usize,
+        phase: JobPhase,
+        snapshot: &dyn Fn() -> HashMap<String, Vec<i32>>,
+    ) {
+        if let Some(replicator) = &self.replicator {
+            replicator.record(CheckpointUpdate::PhaseStarted {
+                stage,
+                phase,
+                state: snapshot(),
+            });
+        }
+    }
+
+    async fn collect_results(
+        &self,
+        job: JobId,
+        phase_run: u64,
+        order: ResultOrder,
+        top_k: Option<usize>,
+    ) -> Vec<Vec<KeyResult>> {
+        collect_aggregated(aggregators(), job, phase_run, order, top_k).await
+    }
+}
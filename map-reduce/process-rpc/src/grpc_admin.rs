@@ -0,0 +1,75 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::AttachToken;
+use crate::rpc::proto;
+use crate::tls;
+use map_reduce_core::kill_switch::{ForcedFault, KillSwitch};
+use proto::admin_service_client::AdminServiceClient;
+use proto::admin_service_server::AdminService;
+use proto::{ForceFaultAck, ForceFaultRequest};
+use std::time::Duration;
+use tonic::{Request, Response, Status};
+
+/// Time left for the acknowledgement of a crash to reach the caller before the process exits
+const CRASH_GRACE_MS: u64 = 50;
+
+/// gRPC admin service of a worker, forcing faults onto its task through its kill switch
+pub struct GrpcAdmin {
+    kill_switch: KillSwitch,
+}
+
+impl GrpcAdmin {
+    pub fn new(kill_switch: KillSwitch) -> Self {
+        Self { kill_switch }
+    }
+}
+
+#[tonic::async_trait]
+impl AdminService for GrpcAdmin {
+    async fn force_fault(
+        &self,
+        request: Request<ForceFaultRequest>,
+    ) -> Result<Response<ForceFaultAck>, Status> {
+        let req = request.into_inner();
+        let fault = match req.fault.as_str() {
+            "" => None,
+            name => Some(
+                name.parse::<ForcedFault>()
+                    .map_err(Status::invalid_argument)?,
+            ),
+        };
+        match fault {
+            Some(fault) => println!("💉 Fault forced on demand: {}", fault),
+            None => println!("💉 Forced fault cleared"),
+        }
+        self.kill_switch.force(fault);
+
+        // A task busy in its map or reduce work would only notice the crash once done,
+        // so the whole process goes down instead
+        if fault == Some(ForcedFault::Crash) {
+            tokio::spawn(async {
+                tokio::time::sleep(Duration::from_millis(CRASH_GRACE_MS)).await;
+                eprintln!("💀 Worker crashed on demand!");
+                std::process::exit(1);
+            });
+        }
+        Ok(Response::new(ForceFaultAck {}))
+    }
+}
+
+/// Force a fault onto the worker serving work at `addr`, or clear it with None
+pub async fn force_fault(
+    addr: &str,
+    fault: Option<ForcedFault>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let channel = tls::endpoint(addr)?.connect().await?;
+    let mut client = AdminServiceClient::with_interceptor(channel, AttachToken);
+    client
+        .force_fault(ForceFaultRequest {
+            fault: fault.map(|fault| fault.to_string()).unwrap_or_default(),
+        })
+        .await?;
+    Ok(())
+}
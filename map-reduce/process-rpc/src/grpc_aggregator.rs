@@ -0,0 +1,209 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::{AttachToken, CheckToken};
+use crate::config::network;
+use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format::{decode_results, encode_results};
+use map_reduce_core::aggregator::{aggregator_for, Aggregator};
+use map_reduce_core::job_id::JobId;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::result_order::ResultOrder;
+use map_reduce_core::task_key::TaskKey;
+use proto::aggregation_service_client::AggregationServiceClient;
+use proto::aggregation_service_server::{AggregationService, AggregationServiceServer};
+use proto::{
+    CollectResultsRequest, CollectResultsResponse, SubmitResultsAck, SubmitResultsRequest,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tonic::{Request, Response, Status};
+
+/// Aggregators of the phase runs whose results are being gathered, and the phase runs
+/// already collected, whose late submissions are turned away
+#[derive(Default)]
+struct Phases {
+    open: HashMap<(u32, u64), Aggregator>,
+    collected: HashSet<(u32, u64)>,
+}
+
+/// gRPC aggregator: intermediate node between the reducers and the coordinator
+#[derive(Default)]
+pub struct GrpcAggregator {
+    phases: Mutex<Phases>,
+}
+
+#[tonic::async_trait]
+impl AggregationService for GrpcAggregator {
+    async fn submit_results(
+        &self,
+        request: Request<SubmitResultsRequest>,
+    ) -> Result<Response<SubmitResultsAck>, Status> {
+        let req = request.into_inner();
+        let phase = (req.job_id, req.phase);
+        let mut phases = self.phases.lock().unwrap();
+        let accepted = !phases.collected.contains(&phase)
+            && phases
+                .open
+                .entry(phase)
+                .or_default()
+                .add(req.task_id as usize, decode_results(req.results));
+        Ok(Response::new(SubmitResultsAck { accepted }))
+    }
+
+    async fn collect_results(
+        &self,
+        request: Request<CollectResultsRequest>,
+    ) -> Result<Response<CollectResultsResponse>, Status> {
+        let req = request.into_inner();
+        let phase = (req.job_id, req.phase);
+        let aggregator = {
+            let mut phases = self.phases.lock().unwrap();
+            phases.collected.insert(phase);
+            phases.open.remove(&phase).unwrap_or_default()
+        };
+
+        let order = if req.by_key {
+            ResultOrder::ByKey
+        } else {
+            ResultOrder::ByWeight
+        };
+        let tasks = aggregator.tasks() as u64;
+        let duplicates = aggregator.duplicates();
+        let results = aggregator.merge(order, req.top_k.map(|k| k as usize));
+        Ok(Response::new(CollectResultsResponse {
+            results: encode_results(results),
+            tasks,
+            duplicates,
+        }))
+    }
+}
+
+/// Manages the lifecycle of an aggregator
+pub struct AggregatorHandle {
+    /// Address the reducers and the coordinator reach it at
+    pub addr: String,
+    _shutdown_tx: tokio::sync::oneshot::Sender<()>,
+}
+
+/// Start an aggregator on a free port
+pub async fn start_aggregator() -> Result<AggregatorHandle, Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind((network().bind_host.as_str(), 0)).await?;
+    let port = listener.local_addr()?.port();
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        tls::server()
+            .add_service(AggregationServiceServer::with_interceptor(
+                GrpcAggregator::default(),
+                CheckToken,
+            ))
+            .serve_with_incoming_shutdown(incoming, async {
+                shutdown_rx.await.ok();
+            })
+            .await
+            .expect("gRPC aggregator failed");
+    });
+
+    Ok(AggregatorHandle {
+        addr: format!("{}:{}", network().advertise_host, port),
+        _shutdown_tx: shutdown_tx,
+    })
+}
+
+/// Hand the results of a task to its aggregator, if the job has any
+/// Returns the outcome and results to report to the coordinator: with aggregators, the results
+/// are left out of the report, and a task whose results could not be handed over failed
+pub async fn route_results(
+    aggregators: &[String],
+    task: Option<TaskKey>,
+    result: Result<usize, ()>,
+    results: Vec<KeyResult>,
+) -> (Result<usize, ()>, Vec<KeyResult>) {
+    let Some(task) = task.filter(|_| !aggregators.is_empty() && !results.is_empty()) else {
+        return (result, results);
+    };
+    if result.is_err() {
+        return (result, Vec::new());
+    }
+
+    let addr = &aggregators[aggregator_for(task.index, aggregators.len())];
+    match submit_results(addr, task, results).await {
+        Ok(()) => (result, Vec::new()),
+        Err(e) => {
+            eprintln!(
+                "Failed to hand the results of task {} to aggregator {}: {}",
+                task.index, addr, e
+            );
+            (Err(()), Vec::new())
+        }
+    }
+}
+
+async fn submit_results(
+    addr: &str,
+    task: TaskKey,
+    results: Vec<KeyResult>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let channel = tls::endpoint(addr)?.connect().await?;
+    let mut client = AggregationServiceClient::with_interceptor(channel, AttachToken);
+    // Results another attempt of the task already handed over are dropped, not an error
+    client
+        .submit_results(SubmitResultsRequest {
+            job_id: task.job.0,
+            phase: task.phase,
+            task_id: task.index as u64,
+            results: encode_results(results),
+        })
+        .await?;
+    Ok(())
+}
+
+/// Collect the merged results of a phase run from every aggregator, one list per aggregator
+pub async fn collect_aggregated(
+    aggregators: &[String],
+    job: JobId,
+    phase: u64,
+    order: ResultOrder,
+    top_k: Option<usize>,
+) -> Vec<Vec<KeyResult>> {
+    let mut collected = Vec::new();
+    for addr in aggregators {
+        match collect_results(addr, job, phase, order, top_k).await {
+            Ok(response) => {
+                if response.duplicates > 0 {
+                    println!(
+                        "Aggregator {} gathered {} tasks, dropped {} duplicate submissions",
+                        addr, response.tasks, response.duplicates
+                    );
+                }
+                collected.push(decode_results(response.results));
+            }
+            Err(e) => eprintln!("Failed to collect results from aggregator {}: {}", addr, e),
+        }
+    }
+    collected
+}
+
+async fn collect_results(
+    addr: &str,
+    job: JobId,
+    phase: u64,
+    order: ResultOrder,
+    top_k: Option<usize>,
+) -> Result<CollectResultsResponse, Box<dyn std::error::Error>> {
+    let channel = tls::endpoint(addr)?.connect().await?;
+    let mut client = AggregationServiceClient::with_interceptor(channel, AttachToken);
+    let response = client
+        .collect_results(CollectResultsRequest {
+            job_id: job.0,
+            phase,
+            by_key: order == ResultOrder::ByKey,
+            top_k: top_k.map(|k| k as u64),
+        })
+        .await?;
+    Ok(response.into_inner())
+}
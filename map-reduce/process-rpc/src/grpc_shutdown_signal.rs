@@ -2,14 +2,160 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::auth::{AttachToken, CheckToken};
+use crate::config::network;
+use crate::rpc::proto;
+use crate::tls;
 use map_reduce_core::shutdown_signal::ShutdownSignal;
+use proto::cancellation_service_client::CancellationServiceClient;
+use proto::cancellation_service_server::{CancellationService, CancellationServiceServer};
+use proto::{CancellationNotice, WatchCancellationRequest};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct DummyShutdownSignal;
+/// How long the coordinator waits for the cancellation to reach its workers
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(2);
 
-impl ShutdownSignal for DummyShutdownSignal {
+/// gRPC Cancellation Service implementation
+struct CancellationServiceImpl {
+    cancelled: watch::Receiver<bool>,
+}
+
+#[tonic::async_trait]
+impl CancellationService for CancellationServiceImpl {
+    type WatchCancellationStream = ReceiverStream<Result<CancellationNotice, Status>>;
+
+    async fn watch_cancellation(
+        &self,
+        _request: Request<WatchCancellationRequest>,
+    ) -> Result<Response<Self::WatchCancellationStream>, Status> {
+        let mut cancelled = self.cancelled.clone();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            if cancelled.wait_for(|cancelled| *cancelled).await.is_ok() {
+                let _ = tx.send(Ok(CancellationNotice { cancelled: true })).await;
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Coordinator end of the signal, notifying the watching workers
+struct CancellationServer {
+    notifier: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Cluster-wide shutdown signal
+/// The coordinator serves the cancellation, worker processes watch it and stop when it fires
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GrpcShutdownSignal {
+    coordinator_addr: String,
+    #[serde(skip)]
+    flag: Arc<AtomicBool>,
+    #[serde(skip)]
+    server: Option<Arc<CancellationServer>>,
+    /// Worker end, started on the first check
+    #[serde(skip)]
+    watching: Arc<OnceLock<()>>,
+}
+
+impl GrpcShutdownSignal {
+    /// Start the coordinator's cancellation service
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind((network().bind_host.as_str(), 0))
+            .await
+            .expect("Failed to bind cancellation listener");
+        let port = listener.local_addr().expect("No local address").port();
+
+        let (notifier, cancelled) = watch::channel(false);
+        let mut server_cancelled = cancelled.clone();
+        let service = CancellationServiceImpl { cancelled };
+        let task = tokio::spawn(async move {
+            let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+            // Once cancelled, the server finishes streaming the notices and stops
+            if let Err(e) = tls::server()
+                .add_service(CancellationServiceServer::with_interceptor(
+                    service, CheckToken,
+                ))
+                .serve_with_incoming_shutdown(incoming, async move {
+                    let _ = server_cancelled.wait_for(|cancelled| *cancelled).await;
+                })
+                .await
+            {
+                eprintln!("Cancellation service error: {}", e);
+            }
+        });
+
+        Self {
+            coordinator_addr: format!("{}:{}", network().advertise_host, port),
+            flag: Arc::new(AtomicBool::new(false)),
+            server: Some(Arc::new(CancellationServer {
+                notifier,
+                task: Mutex::new(Some(task)),
+            })),
+            watching: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Cancel the job on the coordinator and every worker process
+    pub fn shutdown(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+        if let Some(server) = &self.server {
+            server.notifier.send_replace(true);
+        }
+    }
+
+    /// Cancel the job and wait until the watching workers have been notified
+    pub async fn shutdown_and_wait(&self) {
+        self.shutdown();
+        let task = self
+            .server
+            .as_ref()
+            .and_then(|server| server.task.lock().unwrap().take());
+        if let Some(task) = task {
+            let _ = tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, task).await;
+        }
+    }
+}
+
+/// Follow the coordinator's cancellation, reconnecting while it is unreachable
+async fn watch_cancellation(coordinator_addr: String, flag: Arc<AtomicBool>) {
+    let endpoint = tls::endpoint(&coordinator_addr).expect("Invalid coordinator address");
+
+    loop {
+        if let Ok(channel) = endpoint.connect().await {
+            let mut client = CancellationServiceClient::with_interceptor(channel, AttachToken);
+            if let Ok(response) = client.watch_cancellation(WatchCancellationRequest {}).await {
+                let mut notices = response.into_inner();
+                if let Ok(Some(notice)) = notices.message().await {
+                    if notice.cancelled {
+                        flag.store(true, Ordering::SeqCst);
+                        return;
+                    }
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+impl ShutdownSignal for GrpcShutdownSignal {
     fn is_cancelled(&self) -> bool {
-        false
+        if self.server.is_none() {
+            self.watching.get_or_init(|| {
+                tokio::spawn(watch_cancellation(
+                    self.coordinator_addr.clone(),
+                    self.flag.clone(),
+                ));
+            });
+        }
+        self.flag.load(Ordering::SeqCst)
     }
 }
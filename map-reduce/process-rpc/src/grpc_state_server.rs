@@ -2,15 +2,20 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use map_reduce_core::job_id::JobId;
 use map_reduce_core::state_store::StateStore;
+use map_reduce_core::task_key::TaskKey;
 use std::sync::Arc;
-use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 
+use crate::auth::CheckToken;
+use crate::config::network;
 use crate::rpc::proto;
+use crate::tls;
 use proto::state_service_server::{StateService, StateServiceServer};
 use proto::{
-    GetRequest, GetResponse, InitializeRequest, ReplaceRequest, StateResponse, UpdateRequest,
+    CommitTaskRequest, GetRequest, GetResponse, InitializeRequest, ReplaceRequest, StateResponse,
+    UpdateRequest,
 };
 
 /// gRPC State Server that wraps any StateAccess implementation
@@ -57,7 +62,29 @@ impl<S: StateStore + Send + Sync + 'static> StateService for GrpcStateServer<S>
         request: Request<ReplaceRequest>,
     ) -> Result<Response<StateResponse>, Status> {
         let req = request.into_inner();
-        self.state.replace(req.key, req.value).await;
+        self.state.replace(req.key, req.values).await;
+        Ok(Response::new(StateResponse {
+            success: true,
+            error: String::new(),
+        }))
+    }
+
+    async fn commit_task(
+        &self,
+        request: Request<CommitTaskRequest>,
+    ) -> Result<Response<StateResponse>, Status> {
+        let req = request.into_inner();
+        if req.keys.len() != req.values.len() {
+            return Err(Status::invalid_argument(format!(
+                "Task {} has {} keys but {} values",
+                req.task,
+                req.keys.len(),
+                req.values.len()
+            )));
+        }
+        let task = TaskKey::new(JobId(req.job), req.phase, req.task as usize);
+        let updates = req.keys.into_iter().zip(req.values).collect();
+        self.state.commit_task(task, updates).await;
         Ok(Response::new(StateResponse {
             success: true,
             error: String::new(),
@@ -81,7 +108,7 @@ pub async fn start_state_server<S: StateStore + Send + Sync + 'static>(
     state: S,
     port: u16,
 ) -> Result<StateServerHandle, Box<dyn std::error::Error>> {
-    let addr: std::net::SocketAddr = format!("0.0.0.0:{}", port).parse()?;
+    let addr = (network().bind_host.as_str(), port);
     let server = GrpcStateServer::new(state);
 
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
@@ -91,8 +118,8 @@ pub async fn start_state_server<S: StateStore + Send + Sync + 'static>(
     let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
 
     tokio::spawn(async move {
-        Server::builder()
-            .add_service(StateServiceServer::new(server))
+        tls::server()
+            .add_service(StateServiceServer::with_interceptor(server, CheckToken))
             .serve_with_incoming_shutdown(incoming, async {
                 shutdown_rx.await.ok();
             })
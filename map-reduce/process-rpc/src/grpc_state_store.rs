@@ -4,81 +4,210 @@
 
 use async_trait::async_trait;
 use map_reduce_core::state_store::StateStore;
+use map_reduce_core::task_key::TaskKey;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tonic::transport::Channel;
+use tokio::task::JoinSet;
+use tonic::{Code, Response, Status};
 
+use crate::auth::{AttachToken, AuthChannel};
 use crate::rpc::proto;
+use crate::state_shards::shard_for;
+use crate::tls;
 use proto::state_service_client::StateServiceClient;
-use proto::{GetRequest, InitializeRequest, ReplaceRequest, UpdateRequest};
+use proto::{CommitTaskRequest, GetRequest, InitializeRequest, ReplaceRequest, UpdateRequest};
+use tracing::error;
+
+/// Attempts of a state call, the retries reconnecting first
+const CALL_ATTEMPTS: u32 = 2;
 
 /// gRPC client for StateAccess
 /// Native async implementation - no blocking required!
+/// Keys are routed by hash to the state server owning their shard
+/// Clones share one long-lived client per shard
 #[derive(Clone, Serialize, Deserialize)]
 pub struct GrpcStateStore {
-    server_addr: String,
+    shard_addrs: Vec<String>,
     #[serde(skip)]
-    client: Arc<Mutex<Option<StateServiceClient<Channel>>>>,
+    clients: Arc<Mutex<HashMap<usize, StateServiceClient<AuthChannel>>>>,
 }
 
 impl GrpcStateStore {
-    pub fn new(server_addr: String) -> Self {
+    pub fn new(shard_addrs: Vec<String>) -> Self {
+        assert!(
+            !shard_addrs.is_empty(),
+            "At least one state server is needed"
+        );
         Self {
-            server_addr,
-            client: Arc::new(Mutex::new(None)),
+            shard_addrs,
+            clients: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    async fn get_client(&self) -> Result<StateServiceClient<Channel>, tonic::transport::Error> {
-        let mut client_guard = self.client.lock().await;
+    async fn get_client(
+        &self,
+        shard: usize,
+    ) -> Result<StateServiceClient<AuthChannel>, tonic::transport::Error> {
+        let mut clients = self.clients.lock().await;
+
+        if let Some(client) = clients.get(&shard) {
+            return Ok(client.clone());
+        }
+        let channel = tls::endpoint(&self.shard_addrs[shard])?.connect().await?;
+        let client = StateServiceClient::with_interceptor(channel, AttachToken);
+        clients.insert(shard, client.clone());
+        Ok(client)
+    }
 
-        if client_guard.is_none() {
-            let endpoint = format!("http://{}", self.server_addr);
-            let channel = Channel::from_shared(endpoint).unwrap().connect().await?;
-            *client_guard = Some(StateServiceClient::new(channel));
+    /// Run a call on the cached client of a shard
+    /// A call failing because the server is unreachable drops the client and is retried once
+    /// on a fresh connection, so a bounced state server doesn't poison the cache
+    async fn call<T, F, Fut>(&self, shard: usize, call: F) -> Result<T, String>
+    where
+        F: Fn(StateServiceClient<AuthChannel>) -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match self.get_client(shard).await {
+                Ok(client) => match call(client).await {
+                    Ok(response) => return Ok(response.into_inner()),
+                    Err(status) if status.code() == Code::Unavailable => status.to_string(),
+                    Err(status) => return Err(status.to_string()),
+                },
+                Err(e) => e.to_string(),
+            };
+            if attempt == CALL_ATTEMPTS {
+                return Err(error);
+            }
+            attempt += 1;
+            self.clients.lock().await.remove(&shard);
         }
+    }
 
-        Ok(client_guard.as_ref().unwrap().clone())
+    fn shard_of(&self, key: &str) -> usize {
+        shard_for(key, self.shard_addrs.len())
     }
 }
 
 #[async_trait]
 impl StateStore for GrpcStateStore {
     async fn initialize(&self, keys: Vec<String>) {
-        if let Ok(mut client) = self.get_client().await {
-            let request = tonic::Request::new(InitializeRequest { keys });
-            let _ = client.initialize(request).await;
+        let mut keys_per_shard = vec![Vec::new(); self.shard_addrs.len()];
+        for key in keys {
+            keys_per_shard[self.shard_of(&key)].push(key);
         }
+
+        // Fan out to every shard concurrently
+        let mut requests = JoinSet::new();
+        for (shard, keys) in keys_per_shard.into_iter().enumerate() {
+            if keys.is_empty() {
+                continue;
+            }
+            let store = self.clone();
+            requests.spawn(async move {
+                let result = store
+                    .call(shard, |mut client| {
+                        let keys = keys.clone();
+                        async move { client.initialize(InitializeRequest { keys }).await }
+                    })
+                    .await;
+                if let Err(e) = result {
+                    error!(shard, error = %e, "State initialize error");
+                }
+            });
+        }
+        requests.join_all().await;
     }
 
     async fn update(&self, key: String, value: i32) {
-        if let Ok(mut client) = self.get_client().await {
-            let request = tonic::Request::new(UpdateRequest { key, value });
-            if let Err(e) = client.update(request).await {
-                eprintln!("State update error: {}", e);
-            }
+        let result = self
+            .call(self.shard_of(&key), |mut client| {
+                let key = key.clone();
+                async move { client.update(UpdateRequest { key, value }).await }
+            })
+            .await;
+        if let Err(e) = result {
+            error!(%key, error = %e, "State update error");
         }
     }
 
-    async fn replace(&self, key: String, value: i32) {
-        if let Ok(mut client) = self.get_client().await {
-            let request = tonic::Request::new(ReplaceRequest { key, value });
-            if let Err(e) = client.replace(request).await {
-                eprintln!("State replace error: {}", e);
+    /// Pipeline the updates: all requests are in flight at once over the shared connections
+    async fn update_many(&self, updates: Vec<(String, i32)>) {
+        let mut requests = JoinSet::new();
+        for (key, value) in updates {
+            let store = self.clone();
+            requests.spawn(async move { store.update(key, value).await });
+        }
+        requests.join_all().await;
+    }
+
+    /// Each shard commits its part of the updates under the task's key, concurrently
+    /// A retry after a partial commit only applies the parts not committed yet
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>) {
+        let mut updates_per_shard = vec![(Vec::new(), Vec::new()); self.shard_addrs.len()];
+        for (key, value) in updates {
+            let (keys, values) = &mut updates_per_shard[self.shard_of(&key)];
+            keys.push(key);
+            values.push(value);
+        }
+
+        let mut requests = JoinSet::new();
+        for (shard, (keys, values)) in updates_per_shard.into_iter().enumerate() {
+            if keys.is_empty() {
+                continue;
             }
+            let store = self.clone();
+            requests.spawn(async move {
+                let result = store
+                    .call(shard, |mut client| {
+                        let request = CommitTaskRequest {
+                            job: task.job.0,
+                            phase: task.phase,
+                            task: task.index as u64,
+                            keys: keys.clone(),
+                            values: values.clone(),
+                        };
+                        async move { client.commit_task(request).await }
+                    })
+                    .await;
+                if let Err(e) = result {
+                    error!(shard, chunk = task.index, error = %e, "State commit error");
+                }
+            });
+        }
+        requests.join_all().await;
+    }
+
+    async fn replace(&self, key: String, values: Vec<i32>) {
+        let result = self
+            .call(self.shard_of(&key), |mut client| {
+                let key = key.clone();
+                let values = values.clone();
+                async move { client.replace(ReplaceRequest { key, values }).await }
+            })
+            .await;
+        if let Err(e) = result {
+            error!(%key, error = %e, "State replace error");
         }
     }
 
     async fn get(&self, key: &str) -> Vec<i32> {
-        if let Ok(mut client) = self.get_client().await {
-            let request = tonic::Request::new(GetRequest {
-                key: key.to_string(),
-            });
-            if let Ok(response) = client.get(request).await {
-                return response.into_inner().values;
+        let result = self
+            .call(self.shard_of(key), |mut client| {
+                let key = key.to_string();
+                async move { client.get(GetRequest { key }).await }
+            })
+            .await;
+        match result {
+            Ok(response) => response.values,
+            Err(e) => {
+                error!(key, error = %e, "State get error");
+                Vec::new()
             }
         }
-        Vec::new()
     }
 }
@@ -2,37 +2,73 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::auth::AttachToken;
+use crate::config::HEARTBEAT_INTERVAL_MS;
+use crate::grpc_aggregator::route_results;
 use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format::encode_results;
 use async_trait::async_trait;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
 use map_reduce_core::status_sender::StatusSender;
+use map_reduce_core::task_key::TaskKey;
 use proto::synchronization_service_client::SynchronizationServiceClient;
-use proto::{CompletionMessage, RegisterWorkerRequest};
+use proto::{CompletionMessage, HeartbeatRequest, RegisterWorkerRequest};
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tonic::transport::Channel;
+use tracing::error;
 
 /// gRPC Synchronization Token
 /// Sent to workers to report completion back to coordinator
 #[derive(Clone, Serialize, Deserialize, Default)]
 pub struct GrpcStatusSender {
     pub server_addr: String,
+    /// Synchronization of the phase the reports belong to
+    pub sync_id: u64,
     pub worker_id: usize,
+    /// Epoch of the worker this token was handed to
+    pub epoch: u64,
+    /// Task whose outcome this token reports, None for the token handed over at initialization
+    pub task: Option<TaskKey>,
+    /// Aggregators the final results of reduce tasks are handed to, reported with the
+    /// completions if there are none
+    #[serde(default)]
+    pub aggregators: Vec<String>,
+    /// Channel the heartbeats go over, established on first use and shared by clones
+    #[serde(skip)]
+    pub(crate) heartbeat_channel: Arc<Mutex<Option<Channel>>>,
+}
+
+impl GrpcStatusSender {
+    /// Shared heartbeat channel, None if the coordinator address is invalid
+    fn heartbeat_channel(&self) -> Option<Channel> {
+        let mut channel = self.heartbeat_channel.lock().unwrap();
+        if channel.is_none() {
+            // Use connect_lazy so a heartbeat reconnects on its own after the channel is reset
+            *channel = Some(tls::endpoint(&self.server_addr).ok()?.connect_lazy());
+        }
+        channel.clone()
+    }
 }
 
 #[async_trait]
 impl StatusSender for GrpcStatusSender {
     async fn register(&self, _worker_id: usize) -> bool {
-        let endpoint = format!("http://{}", self.server_addr);
+        let endpoint = tls::endpoint(&self.server_addr).expect("Invalid coordinator address");
 
         // Retry logic for connecting to coordinator
         for _ in 0..5 {
-            if let Ok(channel) = Channel::from_shared(endpoint.clone())
-                .unwrap()
-                .connect()
-                .await
-            {
-                let mut client = SynchronizationServiceClient::new(channel);
+            if let Ok(channel) = endpoint.connect().await {
+                let mut client =
+                    SynchronizationServiceClient::with_interceptor(channel, AttachToken);
                 let request = tonic::Request::new(RegisterWorkerRequest {
                     worker_id: self.worker_id as u64,
+                    epoch: self.epoch,
+                    sync_id: self.sync_id,
+                    ..Default::default()
                 });
 
                 if client.register_worker(request).await.is_ok() {
@@ -44,20 +80,32 @@ impl StatusSender for GrpcStatusSender {
         false
     }
 
-    async fn send(&self, result: Result<usize, ()>) -> bool {
-        let endpoint = format!("http://{}", self.server_addr);
+    fn task(&self) -> Option<TaskKey> {
+        self.task
+    }
+
+    async fn send(
+        &self,
+        result: Result<usize, ()>,
+        counters: Counters,
+        results: Vec<KeyResult>,
+    ) -> bool {
+        let (result, results) = route_results(&self.aggregators, self.task, result, results).await;
+        let endpoint = tls::endpoint(&self.server_addr).expect("Invalid coordinator address");
 
         // Retry logic for connecting to coordinator
         for _ in 0..5 {
-            if let Ok(channel) = Channel::from_shared(endpoint.clone())
-                .unwrap()
-                .connect()
-                .await
-            {
-                let mut client = SynchronizationServiceClient::new(channel);
+            if let Ok(channel) = endpoint.connect().await {
+                let mut client =
+                    SynchronizationServiceClient::with_interceptor(channel, AttachToken);
                 let request = tonic::Request::new(CompletionMessage {
                     worker_id: self.worker_id as u64,
                     success: result.is_ok(),
+                    epoch: self.epoch,
+                    task_id: self.task.map(|task| task.index as u64),
+                    counters: counters.clone().into_iter().collect(),
+                    results: encode_results(results.clone()),
+                    sync_id: self.sync_id,
                 });
 
                 if client.report_completion(request).await.is_ok() {
@@ -68,4 +116,63 @@ impl StatusSender for GrpcStatusSender {
         }
         false
     }
+
+    fn heartbeat_interval(&self) -> Option<Duration> {
+        Some(Duration::from_millis(HEARTBEAT_INTERVAL_MS))
+    }
+
+    async fn heartbeat(&self, _worker_id: usize) -> bool {
+        let Some(channel) = self.heartbeat_channel() else {
+            return false;
+        };
+        let mut client = SynchronizationServiceClient::with_interceptor(channel, AttachToken);
+        let request = tonic::Request::new(HeartbeatRequest {
+            worker_id: self.worker_id as u64,
+            epoch: self.epoch,
+            sync_id: self.sync_id,
+        });
+        if client.heartbeat(request).await.is_ok() {
+            return true;
+        }
+        *self.heartbeat_channel.lock().unwrap() = None;
+        false
+    }
+}
+
+/// Where a worker serving work on an ephemeral port registers the port it bound
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkPortRegistration {
+    pub server_addr: String,
+    pub connection_id: u64,
+}
+
+impl WorkPortRegistration {
+    /// Tell the coordinator the port of the work service, retrying while it is unreachable
+    pub async fn register(&self, port: u16) -> bool {
+        let endpoint = match tls::endpoint(&self.server_addr) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                error!(addr = %self.server_addr, error = %e, "Invalid coordinator address");
+                return false;
+            }
+        };
+
+        for _ in 0..5 {
+            if let Ok(channel) = endpoint.connect().await {
+                let mut client =
+                    SynchronizationServiceClient::with_interceptor(channel, AttachToken);
+                let request = tonic::Request::new(RegisterWorkerRequest {
+                    work_port: Some(port as u32),
+                    connection_id: self.connection_id,
+                    ..Default::default()
+                });
+
+                if client.register_worker(request).await.is_ok() {
+                    return true;
+                }
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        false
+    }
 }
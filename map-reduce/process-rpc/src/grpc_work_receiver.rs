@@ -2,46 +2,74 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::auth::CheckToken;
+use crate::config::kill_switch;
+use crate::grpc_admin::GrpcAdmin;
+use crate::grpc_status_sender::WorkPortRegistration;
 use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format;
 use async_trait::async_trait;
+use map_reduce_core::job_context::BroadcastContext;
 use map_reduce_core::work_receiver::WorkReceiver;
+use map_reduce_core::work_sender::WORK_QUEUE_CAPACITY;
 use map_reduce_core::worker_message::WorkerMessage;
+use proto::admin_service_server::AdminServiceServer;
 use proto::work_service_server::{WorkService as WorkServiceTrait, WorkServiceServer};
 use proto::{InitializeWorkerRequest, WorkAck, WorkMessage};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
+use std::net::ToSocketAddrs;
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
-use tokio::sync::Mutex;
-use tonic::transport::Server;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
 use tonic::{Request, Response, Status};
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::error;
 
 type WorkerMessageReceiver<A, C> = Arc<Mutex<Option<Receiver<WorkerMessage<A, C>>>>>;
 
 /// gRPC Work Receiver
 /// Receives work assignments from coordinator
 #[derive(Serialize, Deserialize)]
-pub struct GrpcWorkReceiver<A, C> {
-    pub port: u16,
+pub struct GrpcWorkReceiver<A: BroadcastContext, C> {
+    /// Address the coordinator sends work to, bound by the worker (port 0 = ephemeral)
+    pub addr: String,
+    /// Where to register the port of the work service, when it binds an ephemeral one
+    pub registration: Option<WorkPortRegistration>,
     #[serde(skip, default = "default_rx")]
     pub rx: WorkerMessageReceiver<A, C>,
+    /// Work service started on first receive, with the trigger of its graceful shutdown
+    #[serde(skip)]
+    pub server: Option<(oneshot::Sender<()>, JoinHandle<()>)>,
+    /// Status of the standard health service served next to the work service
+    #[serde(skip)]
+    pub health: Option<HealthReporter>,
 }
 
-fn default_rx<A, C>() -> WorkerMessageReceiver<A, C> {
+fn default_rx<A: BroadcastContext, C>() -> WorkerMessageReceiver<A, C> {
     Arc::new(Mutex::new(None))
 }
 
-impl<A, C> GrpcWorkReceiver<A, C> {
-    // Receivers are now created via GrpcWorkChannel::create_pair()
-}
-
 /// gRPC Work Service implementation
-struct WorkServiceImpl<A, C> {
+struct WorkServiceImpl<A: BroadcastContext, C> {
     tx: tokio::sync::mpsc::Sender<WorkerMessage<A, C>>,
     _phantom: PhantomData<(A, C)>,
 }
 
-impl<A, C> Clone for WorkServiceImpl<A, C> {
+impl<A: BroadcastContext, C> WorkServiceImpl<A, C> {
+    /// Acknowledge a queued message, reporting the queue depth for the coordinator's metrics
+    fn ack(&self) -> WorkAck {
+        WorkAck {
+            received: true,
+            queue_depth: (self.tx.max_capacity() - self.tx.capacity()) as u32,
+        }
+    }
+}
+
+impl<A: BroadcastContext, C> Clone for WorkServiceImpl<A, C> {
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
@@ -53,7 +81,7 @@ impl<A, C> Clone for WorkServiceImpl<A, C> {
 #[tonic::async_trait]
 impl<A, C> WorkServiceTrait for WorkServiceImpl<A, C>
 where
-    A: Send + Sync + for<'de> Deserialize<'de> + 'static,
+    A: Send + Sync + for<'de> Deserialize<'de> + BroadcastContext + 'static,
     C: Send + Sync + for<'de> Deserialize<'de> + 'static,
 {
     async fn initialize_worker(
@@ -62,15 +90,19 @@ where
     ) -> Result<Response<WorkAck>, Status> {
         let msg = request.into_inner();
 
-        let token: C = serde_json::from_str(&msg.synchronization_token_json)
-            .map_err(|e| Status::invalid_argument(format!("Invalid token JSON: {}", e)))?;
+        let token: C = wire_format::decode(
+            msg.version,
+            &msg.synchronization_token,
+            "synchronization token",
+        )?;
+        let context = wire_format::decode(msg.version, &msg.context, "job context")?;
 
         self.tx
-            .send(WorkerMessage::Initialize(token))
+            .send(WorkerMessage::Initialize(token, context))
             .await
             .map_err(|_| Status::internal("Failed to queue initialization"))?;
 
-        Ok(Response::new(WorkAck { received: true }))
+        Ok(Response::new(self.ack()))
     }
 
     async fn receive_work(
@@ -79,25 +111,23 @@ where
     ) -> Result<Response<WorkAck>, Status> {
         let msg = request.into_inner();
 
-        let assignment: A = serde_json::from_str(&msg.assignment_json)
-            .map_err(|e| Status::invalid_argument(format!("Invalid assignment JSON: {}", e)))?;
-
-        let completion: C = serde_json::from_str(&msg.completion_json)
-            .map_err(|e| Status::invalid_argument(format!("Invalid completion JSON: {}", e)))?;
+        let assignment: A =
+            wire_format::decode_assignment(msg.version, msg.assignment, msg.compressed)?;
+        let completion: C = wire_format::decode(msg.version, &msg.completion, "completion token")?;
 
         self.tx
             .send(WorkerMessage::Work(assignment, completion))
             .await
             .map_err(|_| Status::internal("Failed to queue work"))?;
 
-        Ok(Response::new(WorkAck { received: true }))
+        Ok(Response::new(self.ack()))
     }
 }
 
 #[async_trait]
 impl<A, C> WorkReceiver<A, C> for GrpcWorkReceiver<A, C>
 where
-    A: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    A: Send + Sync + Serialize + for<'de> Deserialize<'de> + BroadcastContext + 'static,
     C: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
 {
     async fn recv(&mut self) -> Option<WorkerMessage<A, C>> {
@@ -105,18 +135,36 @@ where
 
         if rx_guard.is_none() {
             // Lazy initialization of the server
-            let (tx, rx) = tokio::sync::mpsc::channel(10);
+            // The queue is bounded, so the coordinator's calls wait while the worker is behind
+            let (tx, rx) = tokio::sync::mpsc::channel(WORK_QUEUE_CAPACITY);
             *rx_guard = Some(rx);
 
-            let port = self.port;
+            let addr = self.addr.clone();
+            let registration = self.registration.clone();
             let service = WorkServiceImpl::<A, C> {
                 tx,
                 _phantom: PhantomData,
             };
+            let (stop, stopped) = oneshot::channel::<()>();
+            // Serving from the start, the coordinator probes it before initializing the worker
+            let (health, health_service) = tonic_health::server::health_reporter();
+            self.health = Some(health);
+            // Chaos tests reach the worker's kill switch on the work port
+            let admin_service = kill_switch().map(|kill_switch| {
+                AdminServiceServer::with_interceptor(
+                    GrpcAdmin::new(kill_switch.clone()),
+                    CheckToken,
+                )
+            });
 
-            tokio::spawn(async move {
-                let addr_str = format!("127.0.0.1:{}", port);
-                let socket_addr: std::net::SocketAddr = addr_str.parse().unwrap();
+            let server = tokio::spawn(async move {
+                let socket_addr = match addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+                    Ok(Some(socket_addr)) => socket_addr,
+                    _ => {
+                        error!(%addr, "Failed to resolve work service address");
+                        return;
+                    }
+                };
 
                 // Use socket2 to enable SO_REUSEADDR
                 let domain = socket2::Domain::for_address(socket_addr);
@@ -127,48 +175,72 @@ where
                 ) {
                     Ok(s) => s,
                     Err(e) => {
-                        eprintln!("Failed to create socket: {}", e);
+                        error!(error = %e, "Failed to create socket");
                         return;
                     }
                 };
 
                 if let Err(e) = socket.set_reuse_address(true) {
-                    eprintln!("Failed to set reuse_address: {}", e);
+                    error!(error = %e, "Failed to set reuse_address");
                     return;
                 }
 
                 if let Err(e) = socket.bind(&socket_addr.into()) {
-                    eprintln!("Failed to bind work service to {}: {}", socket_addr, e);
+                    error!(%socket_addr, error = %e, "Failed to bind work service");
                     return;
                 }
 
                 if let Err(e) = socket.listen(1024) {
-                    eprintln!("Failed to listen on {}: {}", socket_addr, e);
+                    error!(%socket_addr, error = %e, "Failed to listen");
                     return;
                 }
 
                 let std_listener: std::net::TcpListener = socket.into();
                 if let Err(e) = std_listener.set_nonblocking(true) {
-                    eprintln!("Failed to set nonblocking: {}", e);
+                    error!(error = %e, "Failed to set nonblocking");
                     return;
                 }
 
+                let socket_addr = match std_listener.local_addr() {
+                    Ok(local_addr) => local_addr,
+                    Err(e) => {
+                        error!(%socket_addr, error = %e, "Failed to get the bound address");
+                        return;
+                    }
+                };
+                if let Some(registration) = registration {
+                    tokio::spawn(async move {
+                        if !registration.register(socket_addr.port()).await {
+                            error!(
+                                port = socket_addr.port(),
+                                coordinator = %registration.server_addr,
+                                "Failed to register work port"
+                            );
+                        }
+                    });
+                }
+
                 match tokio::net::TcpListener::from_std(std_listener) {
                     Ok(listener) => {
                         let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
-                        if let Err(e) = Server::builder()
-                            .add_service(WorkServiceServer::new(service))
-                            .serve_with_incoming(incoming)
+                        if let Err(e) = tls::server()
+                            .add_service(health_service)
+                            .add_service(WorkServiceServer::with_interceptor(service, CheckToken))
+                            .add_optional_service(admin_service)
+                            .serve_with_incoming_shutdown(incoming, async {
+                                let _ = stopped.await;
+                            })
                             .await
                         {
-                            eprintln!("Work service error on {}: {}", socket_addr, e);
+                            error!(%socket_addr, error = %e, "Work service error");
                         }
                     }
                     Err(e) => {
-                        eprintln!("Failed to create tokio listener on {}: {}", socket_addr, e);
+                        error!(%socket_addr, error = %e, "Failed to create tokio listener");
                     }
                 }
             });
+            self.server = Some((stop, server));
         }
 
         if let Some(rx) = rx_guard.as_mut() {
@@ -177,4 +249,27 @@ where
             None
         }
     }
+
+    async fn shutdown(&mut self) {
+        {
+            let mut rx_guard = self.rx.lock().await;
+            match rx_guard.as_mut() {
+                // Calls waiting for room in the queue fail now, so the server can drain
+                Some(rx) => rx.close(),
+                // Never started: later receives return None instead of starting the server
+                None => *rx_guard = Some(tokio::sync::mpsc::channel(1).1),
+            }
+        }
+
+        // Finish the calls in flight, then release the port
+        if let Some(health) = self.health.take() {
+            health
+                .set_service_status("", ServingStatus::NotServing)
+                .await;
+        }
+        if let Some((stop, server)) = self.server.take() {
+            let _ = stop.send(());
+            let _ = server.await;
+        }
+    }
 }
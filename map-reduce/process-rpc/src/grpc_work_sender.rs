@@ -2,41 +2,224 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::auth::{AttachToken, AuthChannel};
+use crate::config::{
+    network, HEALTH_CHECK_FAILURES, HEALTH_CHECK_INTERVAL_MS, READINESS_TIMEOUT_MS,
+    WORK_MESSAGE_VERSION, WORK_PORT_TIMEOUT_MS,
+};
+use crate::grpc_status_sender::WorkPortRegistration;
 use crate::grpc_work_receiver::GrpcWorkReceiver;
+use crate::grpc_worker_synchronization;
 use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format;
+use map_reduce_core::job_context::{BroadcastContext, JobContextOf};
 use map_reduce_core::work_sender::WorkSender;
 use proto::work_service_client::WorkServiceClient;
 use proto::{InitializeWorkerRequest, WorkMessage};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
 use tonic::transport::Channel;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+use tracing::error;
+
+/// Delay between the health probes of a worker that is not serving yet
+const READINESS_POLL: Duration = Duration::from_millis(50);
+
+/// Lazily-established gRPC channel to a worker, reused for every message
+/// and dropped after a failure so the next message reconnects
+struct WorkerConnection {
+    host: String,
+    /// Port the worker serves work on, None until a worker on an ephemeral port registers it
+    port: watch::Receiver<Option<u16>>,
+    channel: std::sync::Mutex<Option<Channel>>,
+    /// Whether the periodic health checks of the worker were started
+    monitored: AtomicBool,
+    /// Why the worker was found unhealthy, once it failed too many health checks in a row
+    unhealthy: std::sync::Mutex<Option<String>>,
+}
+
+impl WorkerConnection {
+    fn new(host: &str, port: watch::Receiver<Option<u16>>) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            channel: std::sync::Mutex::new(None),
+            monitored: AtomicBool::new(false),
+            unhealthy: std::sync::Mutex::new(None),
+        }
+    }
+
+    fn addr(&self) -> String {
+        match *self.port.borrow() {
+            Some(port) => format!("{}:{}", self.host, port),
+            None => format!("{}:<unregistered port>", self.host),
+        }
+    }
+
+    /// Shared channel, established on first use
+    /// Waits for a worker on an ephemeral port to register it
+    async fn channel(&self) -> Result<Channel, String> {
+        let mut port = self.port.clone();
+        let timeout = Duration::from_millis(WORK_PORT_TIMEOUT_MS);
+        let port = match tokio::time::timeout(timeout, port.wait_for(Option::is_some)).await {
+            Ok(Ok(port)) => port.unwrap_or_default(),
+            _ => {
+                return Err(format!(
+                    "Worker on {} did not register its work port within {}ms",
+                    self.host, WORK_PORT_TIMEOUT_MS
+                ))
+            }
+        };
+
+        let mut channel = self.channel.lock().unwrap();
+        if channel.is_none() {
+            // Use connect_lazy to let Tonic handle connection establishment and buffering
+            let addr = format!("{}:{}", self.host, port);
+            let endpoint = tls::endpoint(&addr)
+                .map_err(|e| format!("Invalid worker address {}: {}", addr, e))?;
+            *channel = Some(endpoint.connect_lazy());
+        }
+        Ok(channel.clone().unwrap())
+    }
+
+    /// Work service client over the shared channel
+    async fn client(&self) -> Result<WorkServiceClient<AuthChannel>, String> {
+        Ok(WorkServiceClient::with_interceptor(
+            self.channel().await?,
+            AttachToken,
+        ))
+    }
+
+    /// Ask the worker's health service whether it is serving, with the reason if it is not
+    async fn check_health(&self) -> Result<(), String> {
+        let mut client = HealthClient::new(self.channel().await?);
+        let request = HealthCheckRequest {
+            service: String::new(),
+        };
+        let timeout = Duration::from_millis(HEALTH_CHECK_INTERVAL_MS);
+        match tokio::time::timeout(timeout, client.check(request)).await {
+            Ok(Ok(response)) => match response.into_inner().status() {
+                ServingStatus::Serving => Ok(()),
+                status => Err(format!("health service reports {}", status.as_str_name())),
+            },
+            Ok(Err(e)) => {
+                // The worker may not be listening yet, reconnect on the next probe
+                self.reset();
+                Err(e.message().to_string())
+            }
+            Err(_) => Err(format!("no health check response within {:?}", timeout)),
+        }
+    }
+
+    /// Probe the worker's health service until it reports serving
+    async fn wait_until_serving(&self) -> Result<(), String> {
+        let deadline = Instant::now() + Duration::from_millis(READINESS_TIMEOUT_MS);
+        loop {
+            match self.check_health().await {
+                Ok(()) => return Ok(()),
+                Err(e) if Instant::now() >= deadline => {
+                    return Err(format!(
+                        "Worker {} not serving after {}ms: {}",
+                        self.addr(),
+                        READINESS_TIMEOUT_MS,
+                        e
+                    ))
+                }
+                Err(_) => tokio::time::sleep(READINESS_POLL).await,
+            }
+        }
+    }
+
+    /// Probe the worker's health periodically from now on, until it fails too many checks in a row
+    /// or the connection is dropped along with the worker
+    fn monitor_health(self: &Arc<Self>) {
+        if self.monitored.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let connection = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(HEALTH_CHECK_INTERVAL_MS));
+            let mut failures = 0;
+            loop {
+                ticker.tick().await;
+                let Some(connection) = Weak::upgrade(&connection) else {
+                    return;
+                };
+                match connection.check_health().await {
+                    Ok(()) => failures = 0,
+                    Err(e) => {
+                        failures += 1;
+                        if failures >= HEALTH_CHECK_FAILURES {
+                            *connection.unhealthy.lock().unwrap() =
+                                Some(format!("failed {} health checks in a row: {}", failures, e));
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Forget the shared channel after a failed call
+    fn reset(&self) {
+        *self.channel.lock().unwrap() = None;
+    }
+}
 
 #[derive(Clone)]
 pub struct GrpcWorkSender<A, C> {
-    worker_addr: String,
+    connection: Arc<WorkerConnection>,
+    bytes_sent: Arc<AtomicU64>,
+    raw_bytes_sent: Arc<AtomicU64>,
+    /// Queue depth reported by the worker's last acknowledgement
+    queue_depth: Arc<AtomicUsize>,
     _phantom: PhantomData<(A, C)>,
 }
 
 impl<A, C> GrpcWorkSender<A, C>
 where
-    A: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    A: Send + Sync + Serialize + for<'de> Deserialize<'de> + BroadcastContext + 'static,
     C: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
 {
     /// Create a work channel pair.
     /// The server is NOT started here. It is started lazily by the receiver (in the worker process).
+    /// With port 0 the receiver binds an ephemeral port and registers it with the coordinator's
+    /// synchronization server, so a replacement worker never waits for its predecessor's port
     pub async fn create_pair(port: u16) -> (Self, GrpcWorkReceiver<A, C>) {
-        let addr_str = format!("127.0.0.1:{}", port);
+        let host = &network().worker_host;
+        let (registration, work_port) = if port == 0 {
+            let server = grpc_worker_synchronization::server();
+            let (connection_id, work_port) = server.expect_work_port();
+            let registration = WorkPortRegistration {
+                server_addr: server.addr().to_string(),
+                connection_id,
+            };
+            (Some(registration), work_port)
+        } else {
+            (None, watch::channel(Some(port)).1)
+        };
 
         let channel = Self {
-            worker_addr: addr_str,
+            connection: Arc::new(WorkerConnection::new(host, work_port)),
+            bytes_sent: Arc::new(AtomicU64::new(0)),
+            raw_bytes_sent: Arc::new(AtomicU64::new(0)),
+            queue_depth: Arc::new(AtomicUsize::new(0)),
             _phantom: PhantomData,
         };
 
         let receiver = GrpcWorkReceiver {
-            port,
+            addr: format!("{}:{}", host, port),
+            registration,
             rx: Arc::new(Mutex::new(None)),
+            server: None,
+            health: None,
         };
 
         (channel, receiver)
@@ -45,79 +228,109 @@ where
 
 impl<A, C> WorkSender<A, C> for GrpcWorkSender<A, C>
 where
-    A: Clone + Send + Serialize + 'static,
-    C: Clone + Send + Serialize + 'static,
+    A: Clone + Send + Sync + Serialize + BroadcastContext + 'static,
+    C: Clone + Send + Sync + Serialize + 'static,
 {
-    fn initialize(&self, token: C) {
-        let addr = self.worker_addr.clone();
-        let synchronization_token_json = serde_json::to_string(&token).unwrap();
+    fn initialize(&self, token: C, context: JobContextOf<A>) {
+        let connection = self.connection.clone();
+        let synchronization_token = wire_format::encode(&token);
+        let context = wire_format::encode(&context);
+        let len = (synchronization_token.len() + context.len()) as u64;
+        let bytes_sent = self.bytes_sent.clone();
+        let raw_bytes_sent = self.raw_bytes_sent.clone();
 
         tokio::spawn(async move {
-            let endpoint = format!("http://{}", addr);
-            let max_retries = 50; // Try for up to 5 seconds (100ms * 50)
-            let retry_delay = std::time::Duration::from_millis(100);
-
-            for attempt in 1..=max_retries {
-                // Use connect_lazy to let Tonic handle connection establishment and buffering
-                let channel = match Channel::from_shared(endpoint.clone()) {
-                    Ok(c) => c.connect_lazy(),
-                    Err(e) => {
-                        eprintln!("Invalid URI {}: {}", endpoint, e);
-                        return;
-                    }
-                };
+            // Initialize the worker once its health service reports it is serving
+            if let Err(e) = connection.wait_until_serving().await {
+                error!(error = %e, "Worker never started serving");
+                return;
+            }
+            let mut client = match connection.client().await {
+                Ok(client) => client,
+                Err(e) => {
+                    error!(error = %e, "Failed to connect to worker");
+                    return;
+                }
+            };
 
-                let mut client = WorkServiceClient::new(channel);
-                let request = tonic::Request::new(InitializeWorkerRequest {
-                    synchronization_token_json: synchronization_token_json.clone(),
-                });
+            let request = tonic::Request::new(InitializeWorkerRequest {
+                version: WORK_MESSAGE_VERSION,
+                synchronization_token,
+                context,
+            });
 
-                match client.initialize_worker(request).await {
-                    Ok(_) => {
-                        // Success
-                        return;
-                    }
-                    Err(e) => {
-                        if attempt == max_retries {
-                            eprintln!(
-                                "Failed to initialize worker {} after {} attempts: {}",
-                                addr, max_retries, e
-                            );
-                        } else {
-                            tokio::time::sleep(retry_delay).await;
-                        }
-                    }
+            match client.initialize_worker(request).await {
+                Ok(_) => {
+                    bytes_sent.fetch_add(len, Ordering::Relaxed);
+                    raw_bytes_sent.fetch_add(len, Ordering::Relaxed);
+                    connection.monitor_health();
+                }
+                Err(e) => {
+                    connection.reset();
+                    error!(addr = %connection.addr(), error = %e, "Failed to initialize worker");
                 }
             }
         });
     }
 
-    fn send_work(&self, assignment: A, completion: C) {
-        let addr = self.worker_addr.clone();
-        let assignment_json = serde_json::to_string(&assignment).unwrap();
-        let completion_json = serde_json::to_string(&completion).unwrap();
-
-        tokio::spawn(async move {
-            let endpoint = format!("http://{}", addr);
+    async fn send_work(&self, assignment: A, completion: C) -> Result<(), String> {
+        let assignment = wire_format::encode_assignment(&assignment);
+        let completion = wire_format::encode(&completion);
+        let message_len = (assignment.bytes.len() + completion.len()) as u64;
+        let raw_len = (assignment.raw_len + completion.len()) as u64;
+        let max_attempts = 3;
+        let retry_delay = std::time::Duration::from_millis(100);
 
-            // Use connect_lazy to let Tonic handle connection establishment and buffering
-            let channel = match Channel::from_shared(endpoint.clone()) {
-                Ok(c) => c.connect_lazy(),
-                Err(e) => {
-                    eprintln!("Invalid URI {}: {}", endpoint, e);
-                    return;
-                }
-            };
+        let mut attempt = 1;
+        loop {
+            let mut client = self.connection.client().await?;
 
-            let mut client = WorkServiceClient::new(channel);
             let request = tonic::Request::new(WorkMessage {
-                assignment_json,
-                completion_json,
+                version: WORK_MESSAGE_VERSION,
+                assignment: assignment.bytes.clone(),
+                completion: completion.clone(),
+                compressed: assignment.compressed,
             });
 
-            if let Err(e) = client.receive_work(request).await {
-                eprintln!("Failed to send work to {}: {}", addr, e);
+            match client.receive_work(request).await {
+                Ok(ack) => {
+                    self.bytes_sent.fetch_add(message_len, Ordering::Relaxed);
+                    self.raw_bytes_sent.fetch_add(raw_len, Ordering::Relaxed);
+                    self.queue_depth
+                        .store(ack.into_inner().queue_depth as usize, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(e) => {
+                    // Reconnect on the next attempt
+                    self.connection.reset();
+                    if attempt == max_attempts {
+                        return Err(format!(
+                            "Failed to send work to {} after {} attempts: {}",
+                            self.connection.addr(),
+                            max_attempts,
+                            e
+                        ));
+                    }
+                    attempt += 1;
+                    tokio::time::sleep(retry_delay).await;
+                }
             }
-        });
+        }
+    }
+
+    fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    fn raw_bytes_sent(&self) -> u64 {
+        self.raw_bytes_sent.load(Ordering::Relaxed)
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    fn unhealthy_reason(&self) -> Option<String> {
+        self.connection.unhealthy.lock().unwrap().clone()
     }
 }
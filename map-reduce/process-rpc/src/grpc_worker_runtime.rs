@@ -2,81 +2,128 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
-use map_reduce_core::worker_runtime::{WorkerRuntime, WorkerTask};
-use serde::{de::DeserializeOwned, Serialize};
-use std::process::Stdio;
-use tokio::process::{Child, Command};
+use crate::cli_args::{TlsArgs, WorkerLimitsArgs};
+use crate::config::{job_token, network, tls, worker_limits};
+use crate::work_transport::WorkTransport;
+use map_reduce_core::config::JOB_TOKEN_ENV;
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::mapper::MapperTask;
+use map_reduce_core::process_runtime::{ProcessRuntime, WorkerCommand};
+use map_reduce_core::reducer::ReducerTask;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::process::{Command, Stdio};
 
-pub struct AutoKillChild(Child);
+/// Runs mappers as child processes talking over transport `T`
+pub type MapperProcessRuntime<T> = ProcessRuntime<MapperCommand<T>>;
 
-impl Drop for AutoKillChild {
-    fn drop(&mut self) {
-        let _ = self.0.start_kill();
-    }
-}
+/// Runs reducers as child processes talking over transport `T`
+pub type ReducerProcessRuntime<T> = ProcessRuntime<ReducerCommand<T>>;
 
-#[derive(Clone)]
-pub struct MapperProcessRuntime;
+/// Command line of a mapper process talking over transport `T`
+pub struct MapperCommand<T>(PhantomData<T>);
 
-impl<T> WorkerRuntime<T> for MapperProcessRuntime
+impl<T, P, S, SD, WR, CS> WorkerCommand<MapperTask<P, S, SD, WR, CS>> for MapperCommand<T>
 where
-    T: WorkerTask<Output = ()> + Serialize + DeserializeOwned + Send + 'static,
+    T: WorkTransport,
+    P: MapReduceJob,
+    MapperTask<P, S, SD, WR, CS>: Serialize,
 {
-    type Handle = AutoKillChild;
-    type Error = std::io::Error;
-
-    fn spawn(task: T) -> Self::Handle {
-        let exe = std::env::current_exe().expect("Failed to get current exe");
-        let task_json = serde_json::to_string(&task).expect("Failed to serialize task");
-
-        let child = Command::new(exe)
-            .arg("--worker")
-            .arg("--type")
-            .arg("mapper")
-            .arg("--task")
-            .arg(task_json)
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .expect("Failed to spawn mapper process");
-
-        AutoKillChild(child)
+    const ROLE: &'static str = "mapper";
+
+    fn command(task: &MapperTask<P, S, SD, WR, CS>) -> Command {
+        worker_command(Self::ROLE, P::NAME, T::NAME, task)
     }
 
-    async fn join(mut handle: Self::Handle) -> Result<(), Self::Error> {
-        handle.0.wait().await.map(|_| ())
+    fn stdin_preamble() -> Option<Vec<u8>> {
+        launcher_token()
     }
 }
 
-#[derive(Clone)]
-pub struct ReducerProcessRuntime;
+/// Command line of a reducer process talking over transport `T`
+pub struct ReducerCommand<T>(PhantomData<T>);
 
-impl<T> WorkerRuntime<T> for ReducerProcessRuntime
+impl<T, P, S, SD, WR, CS> WorkerCommand<ReducerTask<P, S, SD, WR, CS>> for ReducerCommand<T>
 where
-    T: WorkerTask<Output = ()> + Serialize + DeserializeOwned + Send + 'static,
+    T: WorkTransport,
+    P: MapReduceJob,
+    ReducerTask<P, S, SD, WR, CS>: Serialize,
 {
-    type Handle = AutoKillChild;
-    type Error = std::io::Error;
-
-    fn spawn(task: T) -> Self::Handle {
-        let exe = std::env::current_exe().expect("Failed to get current exe");
-        let task_json = serde_json::to_string(&task).expect("Failed to serialize task");
-
-        let child = Command::new(exe)
-            .arg("--worker")
-            .arg("--type")
-            .arg("reducer")
-            .arg("--task")
-            .arg(task_json)
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .expect("Failed to spawn reducer process");
-
-        AutoKillChild(child)
+    const ROLE: &'static str = "reducer";
+
+    fn command(task: &ReducerTask<P, S, SD, WR, CS>) -> Command {
+        worker_command(Self::ROLE, P::NAME, T::NAME, task)
     }
 
-    async fn join(mut handle: Self::Handle) -> Result<(), Self::Error> {
-        handle.0.wait().await.map(|_| ())
+    fn stdin_preamble() -> Option<Vec<u8>> {
+        launcher_token()
     }
 }
+
+/// Command starting a worker process running the given task over the given transport
+/// Workers start on this machine, or through the configured launcher (e.g. `ssh worker-1`)
+fn worker_command<K: Serialize>(
+    worker_type: &str,
+    job: &str,
+    transport: &str,
+    task: &K,
+) -> Command {
+    let exe = std::env::current_exe().expect("Failed to get current exe");
+    let task_json = serde_json::to_string(task).expect("Failed to serialize task");
+    let mut args: Vec<String> = [
+        worker_type,
+        "--job",
+        job,
+        "--transport",
+        transport,
+        "--task",
+        &task_json,
+    ]
+    .map(String::from)
+    .into();
+    args.extend(WorkerLimitsArgs::from_limits(worker_limits()));
+    args.extend(TlsArgs::from_config(tls()));
+
+    let mut command = match network().worker_launcher.split_first() {
+        // Remote shells run the worker's command line through a shell, so quote every word
+        // The job token is read from stdin (see `launcher_token`), so no command line shows it
+        Some((launcher, launcher_args)) => {
+            let mut command = Command::new(launcher);
+            command
+                .args(launcher_args)
+                .arg(format!(
+                    "read -r {token} && export {token} && exec",
+                    token = JOB_TOKEN_ENV
+                ))
+                .arg(shell_quote(&exe.to_string_lossy()))
+                .args(args.iter().map(|arg| shell_quote(arg)));
+            command
+        }
+        None => {
+            let mut command = Command::new(exe);
+            command.args(args).env(JOB_TOKEN_ENV, job_token());
+            command
+        }
+    };
+
+    // Workers exit once their stdin pipe closes, i.e. when the coordinator exits
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    command
+}
+
+/// Job token line written to the stdin of a worker started through the launcher, whose remote
+/// shell reads it into the worker's environment before running it
+fn launcher_token() -> Option<Vec<u8>> {
+    network()
+        .worker_launcher
+        .first()
+        .map(|_| format!("{}\n", job_token()).into_bytes())
+}
+
+/// Quote a word for a POSIX shell
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
@@ -2,20 +2,167 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::auth::CheckToken;
+use crate::config::{aggregators, network, HEARTBEAT_INTERVAL_MS, HEARTBEAT_TIMEOUT_MS};
 use crate::grpc_status_sender::GrpcStatusSender;
 use crate::rpc::proto;
-use map_reduce_core::worker_synchronization::WorkerSynchronization;
+use crate::tls;
+use crate::wire_format::decode_results;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::task_key::TaskKey;
+use map_reduce_core::worker_epochs::WorkerEpochs;
+use map_reduce_core::worker_synchronization::{WorkerStatus, WorkerSynchronization};
 use proto::synchronization_service_server::{SynchronizationService, SynchronizationServiceServer};
-use proto::{CompletionAck, CompletionMessage, RegisterWorkerRequest, RegisterWorkerResponse};
-use std::sync::Arc;
-use tokio::sync::Notify;
-use tonic::transport::Server;
+use proto::{
+    CompletionAck, CompletionMessage, HeartbeatAck, HeartbeatRequest, RegisterWorkerRequest,
+    RegisterWorkerResponse,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::{watch, Notify};
 use tonic::{Request, Response, Status};
+use tracing::{error, warn};
+
+/// Epoch and last time each registered worker was heard from
+pub type LastSeen = Arc<Mutex<HashMap<usize, (u64, Instant)>>>;
+
+/// Completion or failure of a worker, tagged with its epoch and the task it ran
+#[derive(Debug, Clone)]
+pub struct StatusEvent {
+    pub worker_id: usize,
+    pub epoch: u64,
+    /// None for failures not tied to a task, e.g. missed heartbeats
+    pub task_id: Option<usize>,
+    pub success: bool,
+    /// Counters the task incremented, empty for failures
+    pub counters: Counters,
+    /// Results the task emitted, in the job's result order (reduce tasks only)
+    pub results: Vec<KeyResult>,
+}
+
+/// Destination of the status events of one executor phase
+#[derive(Clone)]
+pub struct SyncRoute {
+    pub completion_tx: Sender<StatusEvent>,
+    pub readiness_notifiers: Arc<Vec<Arc<Notify>>>,
+    pub last_seen: LastSeen,
+    pub epochs: WorkerEpochs,
+}
+
+/// Coordinator endpoint of the synchronization service, shared by every phase
+/// Routes status messages to the synchronization of the phase they belong to and records
+/// the work ports workers serving on ephemeral ports register
+pub struct SynchronizationServer {
+    addr: String,
+    next_id: AtomicU64,
+    routes: Mutex<HashMap<u64, SyncRoute>>,
+    /// Work ports of workers that did not register them yet, by connection
+    work_ports: Mutex<HashMap<u64, watch::Sender<Option<u16>>>>,
+}
+
+static SERVER: OnceLock<Arc<SynchronizationServer>> = OnceLock::new();
+
+/// The coordinator's synchronization server, started on first use on an ephemeral port
+pub fn server() -> Arc<SynchronizationServer> {
+    SERVER.get_or_init(SynchronizationServer::start).clone()
+}
+
+impl SynchronizationServer {
+    fn start() -> Arc<Self> {
+        let std_listener = std::net::TcpListener::bind((network().bind_host.as_str(), 0))
+            .expect("Failed to bind synchronization listener");
+        std_listener
+            .set_nonblocking(true)
+            .expect("Failed to set nonblocking");
+        let addr = std_listener
+            .local_addr()
+            .expect("Failed to get local address");
+
+        let server = Arc::new(Self {
+            addr: format!("{}:{}", network().advertise_host, addr.port()),
+            next_id: AtomicU64::new(0),
+            routes: Mutex::new(HashMap::new()),
+            work_ports: Mutex::new(HashMap::new()),
+        });
+
+        let service = SynchronizationServiceImpl {
+            server: server.clone(),
+        };
+        tokio::spawn(async move {
+            let listener = tokio::net::TcpListener::from_std(std_listener)
+                .expect("Failed to convert to tokio listener");
+            let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+            if let Err(e) = tls::server()
+                .add_service(SynchronizationServiceServer::with_interceptor(
+                    service, CheckToken,
+                ))
+                .serve_with_incoming(incoming)
+                .await
+            {
+                error!(error = %e, "Synchronization service error");
+            }
+        });
+
+        server
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Register the synchronization of a phase, returning its id
+    pub fn add_route(&self, route: SyncRoute) -> u64 {
+        let sync_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.routes.lock().unwrap().insert(sync_id, route);
+        sync_id
+    }
+
+    pub fn remove_route(&self, sync_id: u64) {
+        self.routes.lock().unwrap().remove(&sync_id);
+    }
+
+    fn route(&self, sync_id: u64) -> Option<SyncRoute> {
+        self.routes.lock().unwrap().get(&sync_id).cloned()
+    }
+
+    /// Expect a new worker to register the ephemeral port it serves work on, returning the
+    /// connection it registers under and the port, None until it is registered
+    /// The expectation is dropped with the last receiver of the port, e.g. when the connection
+    /// to a worker that never registered is dropped
+    pub fn expect_work_port(self: &Arc<Self>) -> (u64, watch::Receiver<Option<u16>>) {
+        let connection_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = watch::channel(None);
+        let watcher = tx.clone();
+        self.work_ports.lock().unwrap().insert(connection_id, tx);
+        let server = Arc::downgrade(self);
+        tokio::spawn(async move {
+            watcher.closed().await;
+            if let Some(server) = server.upgrade() {
+                server.work_ports.lock().unwrap().remove(&connection_id);
+            }
+        });
+        (connection_id, rx)
+    }
+
+    /// Record the work port a worker registered, false if the connection is unknown
+    fn record_work_port(&self, connection_id: u64, port: u16) -> bool {
+        match self.work_ports.lock().unwrap().remove(&connection_id) {
+            Some(tx) => {
+                tx.send_replace(Some(port));
+                true
+            }
+            None => false,
+        }
+    }
+}
 
 /// gRPC Synchronization Service implementation
 struct SynchronizationServiceImpl {
-    completion_tx: tokio::sync::mpsc::Sender<(usize, bool)>,
-    readiness_notifiers: Arc<Vec<Arc<Notify>>>,
+    server: Arc<SynchronizationServer>,
 }
 
 #[tonic::async_trait]
@@ -25,12 +172,38 @@ impl SynchronizationService for SynchronizationServiceImpl {
         request: Request<RegisterWorkerRequest>,
     ) -> Result<Response<RegisterWorkerResponse>, Status> {
         let msg = request.into_inner();
-        let worker_id = msg.worker_id as usize;
 
-        if let Some(notify) = self.readiness_notifiers.get(worker_id) {
-            notify.notify_one();
-        } else {
-            eprintln!("Received registration for unknown worker {}", worker_id);
+        // A worker on an ephemeral port registers the port before it is initialized
+        if let Some(work_port) = msg.work_port {
+            let port = u16::try_from(work_port)
+                .map_err(|_| Status::invalid_argument(format!("Invalid port {}", work_port)))?;
+            if !self.server.record_work_port(msg.connection_id, port) {
+                return Err(Status::not_found(format!(
+                    "Unknown worker connection {}",
+                    msg.connection_id
+                )));
+            }
+            return Ok(Response::new(RegisterWorkerResponse {
+                success: true,
+                error: String::new(),
+            }));
+        }
+
+        let worker_id = msg.worker_id as usize;
+        if let Some(route) = self.server.route(msg.sync_id) {
+            if let Some(notify) = route.readiness_notifiers.get(worker_id) {
+                // A replaced worker registering late must not wake up its replacement's handshake
+                if route.epochs.is_current(worker_id, msg.epoch) {
+                    route
+                        .last_seen
+                        .lock()
+                        .unwrap()
+                        .insert(worker_id, (msg.epoch, Instant::now()));
+                    notify.notify_one();
+                }
+            } else {
+                warn!(worker_id, "Received registration for unknown worker");
+            }
         }
 
         Ok(Response::new(RegisterWorkerResponse {
@@ -45,21 +218,96 @@ impl SynchronizationService for SynchronizationServiceImpl {
     ) -> Result<Response<CompletionAck>, Status> {
         let msg = request.into_inner();
 
-        self.completion_tx
-            .send((msg.worker_id as usize, msg.success))
-            .await
-            .map_err(|_| Status::internal("Failed to queue completion"))?;
+        // Completions of phases that already ended are dropped
+        if let Some(route) = self.server.route(msg.sync_id) {
+            route
+                .completion_tx
+                .send(StatusEvent {
+                    worker_id: msg.worker_id as usize,
+                    epoch: msg.epoch,
+                    task_id: msg.task_id.map(|task_id| task_id as usize),
+                    success: msg.success,
+                    counters: msg.counters.into_iter().collect(),
+                    results: decode_results(msg.results),
+                })
+                .await
+                .map_err(|_| Status::internal("Failed to queue completion"))?;
+        }
 
         Ok(Response::new(CompletionAck { received: true }))
     }
+
+    async fn heartbeat(
+        &self,
+        request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatAck>, Status> {
+        let msg = request.into_inner();
+        let worker_id = msg.worker_id as usize;
+
+        if let Some(route) = self.server.route(msg.sync_id) {
+            if route.epochs.is_current(worker_id, msg.epoch) {
+                route
+                    .last_seen
+                    .lock()
+                    .unwrap()
+                    .insert(worker_id, (msg.epoch, Instant::now()));
+            }
+        }
+
+        Ok(Response::new(HeartbeatAck { received: true }))
+    }
+}
+
+/// Periodically check worker heartbeats and report silent workers as failed
+/// so the executor respawns them and reassigns their in-flight work
+pub async fn monitor_heartbeats(completion_tx: Sender<StatusEvent>, last_seen: LastSeen) {
+    let timeout = Duration::from_millis(HEARTBEAT_TIMEOUT_MS);
+    let mut ticker = tokio::time::interval(Duration::from_millis(HEARTBEAT_INTERVAL_MS));
+
+    while !completion_tx.is_closed() {
+        ticker.tick().await;
+
+        let dead_workers: Vec<(usize, u64)> = {
+            let mut last_seen = last_seen.lock().unwrap();
+            let dead: Vec<(usize, u64)> = last_seen
+                .iter()
+                .filter(|(_, (_, seen))| seen.elapsed() > timeout)
+                .map(|(worker_id, (epoch, _))| (*worker_id, *epoch))
+                .collect();
+            for (worker_id, _) in &dead {
+                last_seen.remove(worker_id);
+            }
+            dead
+        };
+
+        for (worker_id, epoch) in dead_workers {
+            warn!(
+                worker_id,
+                timeout_ms = HEARTBEAT_TIMEOUT_MS,
+                "💀 Worker missed heartbeats, declaring it dead!"
+            );
+            let event = StatusEvent {
+                worker_id,
+                epoch,
+                task_id: None,
+                success: false,
+                counters: Counters::default(),
+                results: Vec::new(),
+            };
+            if completion_tx.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
 }
 
 /// gRPC Synchronization Signaling
-/// Coordinator receives completion notifications from workers
+/// Coordinator receives the completion notifications the synchronization server routes to this phase
 pub struct GrpcWorkerSynchronization {
-    completion_rx: tokio::sync::mpsc::Receiver<(usize, bool)>,
+    sync_id: u64,
+    completion_rx: tokio::sync::mpsc::Receiver<StatusEvent>,
     readiness_notifiers: Arc<Vec<Arc<Notify>>>,
-    server_addr: String,
+    epochs: WorkerEpochs,
 }
 
 impl WorkerSynchronization for GrpcWorkerSynchronization {
@@ -67,55 +315,52 @@ impl WorkerSynchronization for GrpcWorkerSynchronization {
 
     fn setup(num_workers: usize) -> Self {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let (port_tx, port_rx) = std::sync::mpsc::channel();
 
         let mut notifiers = Vec::with_capacity(num_workers);
         for _ in 0..num_workers {
             notifiers.push(Arc::new(Notify::new()));
         }
         let notifiers = Arc::new(notifiers);
-        let service_notifiers = notifiers.clone();
-
-        tokio::spawn(async move {
-            // Bind to a random available port
-            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
-                .await
-                .expect("Failed to bind synchronization listener");
-
-            let addr = listener.local_addr().expect("No local address");
-            port_tx.send(addr.port()).expect("Failed to send port");
-
-            let service = SynchronizationServiceImpl {
-                completion_tx: tx,
-                readiness_notifiers: service_notifiers,
-            };
+        let last_seen: LastSeen = Arc::new(Mutex::new(HashMap::new()));
+        let epochs = WorkerEpochs::new(num_workers);
 
-            // Use the listener directly instead of binding again
-            let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+        tokio::spawn(monitor_heartbeats(tx.clone(), last_seen.clone()));
 
-            if let Err(e) = Server::builder()
-                .add_service(SynchronizationServiceServer::new(service))
-                .serve_with_incoming(incoming)
-                .await
-            {
-                eprintln!("Synchronization service error: {}", e);
-            }
+        let sync_id = server().add_route(SyncRoute {
+            completion_tx: tx,
+            readiness_notifiers: notifiers.clone(),
+            last_seen,
+            epochs: epochs.clone(),
         });
 
-        let port = port_rx.recv().expect("Failed to receive port");
-        let server_addr = format!("127.0.0.1:{}", port);
-
         Self {
+            sync_id,
             completion_rx: rx,
             readiness_notifiers: notifiers,
-            server_addr,
+            epochs,
         }
     }
 
+    fn switch_phase(&mut self) {
+        self.epochs.advance_all();
+    }
+
     fn get_status_sender(&self, worker_id: usize) -> Self::StatusSender {
         GrpcStatusSender {
-            server_addr: self.server_addr.clone(),
+            server_addr: server().addr().to_string(),
+            sync_id: self.sync_id,
             worker_id,
+            epoch: self.epochs.current(worker_id),
+            task: None,
+            aggregators: aggregators().to_vec(),
+            heartbeat_channel: Default::default(),
+        }
+    }
+
+    fn get_task_status_sender(&self, worker_id: usize, task: TaskKey) -> Self::StatusSender {
+        GrpcStatusSender {
+            task: Some(task),
+            ..self.get_status_sender(worker_id)
         }
     }
 
@@ -128,18 +373,58 @@ impl WorkerSynchronization for GrpcWorkerSynchronization {
         }
     }
 
-    async fn wait_next(&mut self) -> Option<Result<usize, usize>> {
-        self.completion_rx.recv().await.map(|(worker_id, success)| {
-            if success {
-                Ok(worker_id)
-            } else {
-                Err(worker_id)
-            }
-        })
+    async fn wait_next(&mut self) -> Option<WorkerStatus> {
+        next_status(&mut self.completion_rx, &self.epochs).await
     }
 
     async fn reset_worker(&mut self, worker_id: usize) -> Self::StatusSender {
         // No explicit reset needed for Notify as it consumes the permit on wait
+        self.epochs.advance(worker_id);
         self.get_status_sender(worker_id)
     }
 }
+
+impl Drop for GrpcWorkerSynchronization {
+    fn drop(&mut self) {
+        server().remove_route(self.sync_id);
+    }
+}
+
+/// Receive the next status event of a current worker, discarding those of replaced workers
+/// other than their completions, reported as superseded
+pub async fn next_status(
+    completion_rx: &mut Receiver<StatusEvent>,
+    epochs: &WorkerEpochs,
+) -> Option<WorkerStatus> {
+    while let Some(event) = completion_rx.recv().await {
+        // Late message of a replaced worker, only its completions are accounted for
+        if !epochs.is_current(event.worker_id, event.epoch) {
+            if event.success {
+                return Some(WorkerStatus::Superseded {
+                    worker_id: event.worker_id,
+                    task_id: event.task_id,
+                });
+            }
+            continue;
+        }
+        let StatusEvent {
+            worker_id,
+            task_id,
+            success,
+            counters,
+            results,
+            ..
+        } = event;
+        return Some(if success {
+            WorkerStatus::Completed {
+                worker_id,
+                task_id,
+                counters,
+                results,
+            }
+        } else {
+            WorkerStatus::Failed { worker_id, task_id }
+        });
+    }
+    None
+}
@@ -0,0 +1,34 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+mod auth;
+mod checkpoint_replicator;
+pub mod cli_args;
+pub mod config;
+pub mod coordinator;
+mod grpc_admin;
+mod grpc_aggregator;
+mod grpc_shutdown_signal;
+mod grpc_state_server;
+mod grpc_state_store;
+mod grpc_status_sender;
+mod grpc_work_receiver;
+mod grpc_work_sender;
+mod grpc_worker_runtime;
+mod grpc_worker_synchronization;
+mod mapper;
+mod reducer;
+pub mod rpc;
+mod shard_store;
+mod standby_coordinator;
+mod state_shards;
+mod stream_hub;
+mod stream_status_sender;
+mod stream_work_receiver;
+mod stream_work_sender;
+mod stream_worker_synchronization;
+mod tls;
+mod wire_format;
+mod work_transport;
+pub mod worker;
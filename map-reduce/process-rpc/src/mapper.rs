@@ -2,9 +2,9 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
-use crate::grpc_work_sender::GrpcWorkSender;
-use crate::{grpc_status_sender::GrpcStatusSender, grpc_work_receiver::GrpcWorkReceiver};
+use crate::work_transport::{WireMessage, WorkTransport};
 use async_trait::async_trait;
+use map_reduce_core::failure_injection::FailureInjection;
 use map_reduce_core::map_reduce_job::MapReduceJob;
 use map_reduce_core::mapper::MapperTask;
 use map_reduce_core::shutdown_signal::ShutdownSignal;
@@ -12,76 +12,69 @@ use map_reduce_core::state_store::StateStore;
 use map_reduce_core::worker_factory::WorkerFactory;
 use map_reduce_core::worker_runtime::WorkerRuntime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
-pub type Mapper<P, S, W, R, SD> = map_reduce_core::mapper::Mapper<
+pub type Mapper<P, S, T, R, SD> = map_reduce_core::mapper::Mapper<
     P,
     S,
-    W,
+    <T as WorkTransport>::Sender<<P as MapReduceJob>::MapAssignment>,
     R,
     SD,
-    GrpcWorkReceiver<<P as MapReduceJob>::MapAssignment, GrpcStatusSender>,
-    GrpcStatusSender,
+    <T as WorkTransport>::Receiver<<P as MapReduceJob>::MapAssignment>,
+    <T as WorkTransport>::StatusSender,
 >;
 
-pub struct MapperFactory<P, S, R, SD> {
+pub struct MapperFactory<P, S, T, R, SD> {
     state: S,
     shutdown: SD,
-    failure_prob: u32,
-    straggler_prob: u32,
-    straggler_delay: u64,
-    _phantom: PhantomData<(P, R)>,
+    failures: FailureInjection,
+    /// Workers created so far in each slot, the epoch of the next one
+    epochs: HashMap<usize, u64>,
+    _phantom: PhantomData<(P, T, R)>,
 }
 
-impl<P, S, R, SD> MapperFactory<P, S, R, SD> {
-    pub fn new(
-        state: S,
-        shutdown: SD,
-        failure_prob: u32,
-        straggler_prob: u32,
-        straggler_delay: u64,
-    ) -> Self {
+impl<P, S, T, R, SD> MapperFactory<P, S, T, R, SD> {
+    pub fn new(state: S, shutdown: SD, failures: FailureInjection) -> Self {
         Self {
             state,
             shutdown,
-            failure_prob,
-            straggler_prob,
-            straggler_delay,
+            failures,
+            epochs: HashMap::new(),
             _phantom: PhantomData,
         }
     }
 }
 
 #[async_trait]
-impl<P, S, R, SD>
-    WorkerFactory<
-        Mapper<P, S, GrpcWorkSender<<P as MapReduceJob>::MapAssignment, GrpcStatusSender>, R, SD>,
-    > for MapperFactory<P, S, R, SD>
+impl<P, S, T, R, SD> WorkerFactory<Mapper<P, S, T, R, SD>> for MapperFactory<P, S, T, R, SD>
 where
     P: MapReduceJob + 'static,
     S: StateStore + Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
     SD: ShutdownSignal + Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
-    P::MapAssignment: Send + Clone + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    P::MapAssignment: WireMessage,
+    T: WorkTransport,
     R: WorkerRuntime<
             MapperTask<
                 P,
                 S,
                 SD,
-                GrpcWorkReceiver<<P as MapReduceJob>::MapAssignment, GrpcStatusSender>,
-                GrpcStatusSender,
+                <T as WorkTransport>::Receiver<<P as MapReduceJob>::MapAssignment>,
+                <T as WorkTransport>::StatusSender,
             >,
         > + Clone
         + Send
         + Sync
         + 'static,
 {
-    async fn create_worker(
-        &mut self,
-        id: usize,
-    ) -> Mapper<P, S, GrpcWorkSender<<P as MapReduceJob>::MapAssignment, GrpcStatusSender>, R, SD>
-    {
-        let port = crate::config::MAPPER_BASE_PORT + id as u16;
-        let (work_channel, work_rx) = GrpcWorkSender::create_pair(port).await;
+    async fn create_worker(&mut self, id: usize) -> Mapper<P, S, T, R, SD> {
+        let port = crate::config::network().mapper_port(id);
+        let (work_channel, work_rx) = T::create_pair(port).await;
+
+        // Replacements of a worker get a fresh epoch, hence a failure schedule of their own
+        let epoch = self.epochs.entry(id).or_default();
+        let failures = self.failures.for_worker(id, *epoch);
+        *epoch += 1;
 
         map_reduce_core::mapper::Mapper::new(
             id,
@@ -89,9 +82,7 @@ where
             self.shutdown.clone(),
             work_rx,
             work_channel,
-            self.failure_prob,
-            self.straggler_prob,
-            self.straggler_delay,
+            failures,
         )
     }
 }
@@ -2,9 +2,9 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
-use crate::grpc_work_sender::GrpcWorkSender;
-use crate::{grpc_status_sender::GrpcStatusSender, grpc_work_receiver::GrpcWorkReceiver};
+use crate::work_transport::{WireMessage, WorkTransport};
 use async_trait::async_trait;
+use map_reduce_core::failure_injection::FailureInjection;
 use map_reduce_core::map_reduce_job::MapReduceJob;
 use map_reduce_core::reducer::ReducerTask;
 use map_reduce_core::shutdown_signal::ShutdownSignal;
@@ -12,82 +12,72 @@ use map_reduce_core::state_store::StateStore;
 use map_reduce_core::worker_factory::WorkerFactory;
 use map_reduce_core::worker_runtime::WorkerRuntime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
-pub type Reducer<P, S, W, R, SD> = map_reduce_core::reducer::Reducer<
+pub type Reducer<P, S, T, R, SD> = map_reduce_core::reducer::Reducer<
     P,
     S,
-    W,
+    <T as WorkTransport>::Sender<<P as MapReduceJob>::ReduceAssignment>,
     R,
     SD,
-    GrpcWorkReceiver<<P as MapReduceJob>::ReduceAssignment, GrpcStatusSender>,
-    GrpcStatusSender,
+    <T as WorkTransport>::Receiver<<P as MapReduceJob>::ReduceAssignment>,
+    <T as WorkTransport>::StatusSender,
 >;
 
-pub struct ReducerFactory<P, S, R, SD> {
+pub struct ReducerFactory<P, S, T, R, SD> {
     state: S,
     shutdown: SD,
-    failure_prob: u32,
-    straggler_prob: u32,
-    straggler_delay: u64,
-    _phantom: PhantomData<(P, R)>,
+    failures: FailureInjection,
+    /// Results each reduce task reports at most
+    top_k: Option<usize>,
+    /// Workers created so far in each slot, the epoch of the next one
+    epochs: HashMap<usize, u64>,
+    _phantom: PhantomData<(P, T, R)>,
 }
 
-impl<P, S, R, SD> ReducerFactory<P, S, R, SD> {
-    pub fn new(
-        state: S,
-        shutdown: SD,
-        failure_prob: u32,
-        straggler_prob: u32,
-        straggler_delay: u64,
-    ) -> Self {
+impl<P, S, T, R, SD> ReducerFactory<P, S, T, R, SD> {
+    pub fn new(state: S, shutdown: SD, failures: FailureInjection, top_k: Option<usize>) -> Self {
         Self {
             state,
             shutdown,
-            failure_prob,
-            straggler_prob,
-            straggler_delay,
+            failures,
+            top_k,
+            epochs: HashMap::new(),
             _phantom: PhantomData,
         }
     }
 }
 
 #[async_trait]
-impl<P, S, R, SD>
-    WorkerFactory<
-        Reducer<
-            P,
-            S,
-            GrpcWorkSender<<P as MapReduceJob>::ReduceAssignment, GrpcStatusSender>,
-            R,
-            SD,
-        >,
-    > for ReducerFactory<P, S, R, SD>
+impl<P, S, T, R, SD> WorkerFactory<Reducer<P, S, T, R, SD>> for ReducerFactory<P, S, T, R, SD>
 where
     P: MapReduceJob + 'static,
     S: StateStore + Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
     SD: ShutdownSignal + Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
-    P::ReduceAssignment: Send + Clone + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    P::ReduceAssignment: WireMessage,
+    T: WorkTransport,
     R: WorkerRuntime<
             ReducerTask<
                 P,
                 S,
                 SD,
-                GrpcWorkReceiver<<P as MapReduceJob>::ReduceAssignment, GrpcStatusSender>,
-                GrpcStatusSender,
+                <T as WorkTransport>::Receiver<<P as MapReduceJob>::ReduceAssignment>,
+                <T as WorkTransport>::StatusSender,
             >,
         > + Clone
         + Send
         + Sync
         + 'static,
 {
-    async fn create_worker(
-        &mut self,
-        id: usize,
-    ) -> Reducer<P, S, GrpcWorkSender<<P as MapReduceJob>::ReduceAssignment, GrpcStatusSender>, R, SD>
-    {
-        let port = crate::config::REDUCER_BASE_PORT + id as u16;
-        let (work_channel, work_rx) = GrpcWorkSender::create_pair(port).await;
+    async fn create_worker(&mut self, id: usize) -> Reducer<P, S, T, R, SD> {
+        let port = crate::config::network().reducer_port(id);
+        let (work_channel, work_rx) = T::create_pair(port).await;
+
+        // Replacements of a worker get a fresh epoch, hence a failure schedule of their own
+        let epoch = self.epochs.entry(id).or_default();
+        let failures = self.failures.for_worker(id, *epoch);
+        *epoch += 1;
 
         map_reduce_core::reducer::Reducer::new(
             id,
@@ -95,9 +85,8 @@ where
             self.shutdown.clone(),
             work_rx,
             work_channel,
-            self.failure_prob,
-            self.straggler_prob,
-            self.straggler_delay,
+            failures,
+            self.top_k,
         )
     }
 }
@@ -0,0 +1,109 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use async_trait::async_trait;
+use map_reduce_core::in_memory_state_store::{LocalStateAccess, SharedStateMap};
+use map_reduce_core::job_id::JobId;
+use map_reduce_core::sled_state_store::SledStateStore;
+use map_reduce_core::state_store::{Aggregate, StateStore};
+use map_reduce_core::task_key::TaskKey;
+use std::collections::HashMap;
+use std::io;
+
+/// Storage behind a single state server: in memory, or persisted to sled
+#[derive(Clone)]
+pub enum ShardStore {
+    Memory(LocalStateAccess),
+    Sled(SledStateStore),
+}
+
+impl ShardStore {
+    pub fn get_map(&self) -> SharedStateMap {
+        match self {
+            ShardStore::Memory(store) => store.get_map(),
+            ShardStore::Sled(store) => store.get_map(),
+        }
+    }
+
+    pub fn clear(&self) {
+        match self {
+            ShardStore::Memory(store) => store.clear(),
+            ShardStore::Sled(store) => store.clear(),
+        }
+    }
+
+    pub fn begin_job(&self, job: JobId) {
+        match self {
+            ShardStore::Memory(store) => store.begin_job(job),
+            ShardStore::Sled(store) => store.begin_job(job),
+        }
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, Vec<i32>> {
+        match self {
+            ShardStore::Memory(store) => store.snapshot(),
+            ShardStore::Sled(store) => store.snapshot(),
+        }
+    }
+
+    pub fn restore(&self, snapshot: HashMap<String, Vec<i32>>) {
+        match self {
+            ShardStore::Memory(store) => store.restore(snapshot),
+            ShardStore::Sled(store) => store.restore(snapshot),
+        }
+    }
+
+    /// Make the persisted state durable, a no-op in memory
+    pub async fn flush(&self) -> io::Result<()> {
+        match self {
+            ShardStore::Memory(_) => Ok(()),
+            ShardStore::Sled(store) => store.flush().await,
+        }
+    }
+}
+
+#[async_trait]
+impl StateStore for ShardStore {
+    async fn initialize(&self, keys: Vec<String>) {
+        match self {
+            ShardStore::Memory(store) => store.initialize(keys).await,
+            ShardStore::Sled(store) => store.initialize(keys).await,
+        }
+    }
+
+    async fn update(&self, key: String, value: i32) {
+        match self {
+            ShardStore::Memory(store) => store.update(key, value).await,
+            ShardStore::Sled(store) => store.update(key, value).await,
+        }
+    }
+
+    async fn commit_task(&self, task: TaskKey, updates: Vec<(String, i32)>) {
+        match self {
+            ShardStore::Memory(store) => store.commit_task(task, updates).await,
+            ShardStore::Sled(store) => store.commit_task(task, updates).await,
+        }
+    }
+
+    async fn replace(&self, key: String, values: Vec<i32>) {
+        match self {
+            ShardStore::Memory(store) => store.replace(key, values).await,
+            ShardStore::Sled(store) => store.replace(key, values).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Vec<i32> {
+        match self {
+            ShardStore::Memory(store) => store.get(key).await,
+            ShardStore::Sled(store) => store.get(key).await,
+        }
+    }
+
+    async fn merge_reduce(&self, keys: &[String], aggregate: Aggregate) -> Vec<i32> {
+        match self {
+            ShardStore::Memory(store) => store.merge_reduce(keys, aggregate).await,
+            ShardStore::Sled(store) => store.merge_reduce(keys, aggregate).await,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::CheckToken;
+use crate::config::{FAILOVER_TIMEOUT_MS, HEARTBEAT_INTERVAL_MS, MAX_CHECKPOINT_MESSAGE_BYTES};
+use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format;
+use map_reduce_core::coordinator_checkpoint::{CheckpointUpdate, CoordinatorCheckpoint};
+use proto::coordinator_replication_service_server::{
+    CoordinatorReplicationService, CoordinatorReplicationServiceServer,
+};
+use proto::{CheckpointUpdateMessage, LeaseRequest, ReplicationAck};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status};
+
+/// Replicated orchestrator state and the last time the primary was heard from
+#[derive(Default)]
+struct ReplicaState {
+    checkpoint: CoordinatorCheckpoint,
+    last_contact: Option<Instant>,
+}
+
+/// gRPC Coordinator Replication Service implementation
+struct CoordinatorReplicationServiceImpl {
+    replica: Arc<Mutex<ReplicaState>>,
+}
+
+#[tonic::async_trait]
+impl CoordinatorReplicationService for CoordinatorReplicationServiceImpl {
+    async fn replicate(
+        &self,
+        request: Request<CheckpointUpdateMessage>,
+    ) -> Result<Response<ReplicationAck>, Status> {
+        let msg = request.into_inner();
+        let update: CheckpointUpdate =
+            wire_format::decode(msg.version, &msg.update, "checkpoint update")?;
+
+        let mut replica = self.replica.lock().unwrap();
+        replica.checkpoint.apply(update);
+        replica.last_contact = Some(Instant::now());
+
+        Ok(Response::new(ReplicationAck { received: true }))
+    }
+
+    async fn renew_lease(
+        &self,
+        _request: Request<LeaseRequest>,
+    ) -> Result<Response<ReplicationAck>, Status> {
+        self.replica.lock().unwrap().last_contact = Some(Instant::now());
+        Ok(Response::new(ReplicationAck { received: true }))
+    }
+}
+
+/// Coordinator replica receiving the primary's orchestrator state,
+/// ready to resume the job if the primary goes silent
+pub struct StandbyCoordinator {
+    replica: Arc<Mutex<ReplicaState>>,
+}
+
+impl StandbyCoordinator {
+    /// Start serving replication requests on `addr`
+    pub async fn start(addr: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let addr: std::net::SocketAddr = addr.parse()?;
+        let replica = Arc::new(Mutex::new(ReplicaState::default()));
+        let service = CoordinatorReplicationServiceImpl {
+            replica: replica.clone(),
+        };
+
+        // Bind first to ensure the address is available
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+
+        tokio::spawn(async move {
+            if let Err(e) = tls::server()
+                .add_service(InterceptedService::new(
+                    CoordinatorReplicationServiceServer::new(service)
+                        .max_decoding_message_size(MAX_CHECKPOINT_MESSAGE_BYTES),
+                    CheckToken,
+                ))
+                .serve_with_incoming(incoming)
+                .await
+            {
+                eprintln!("Coordinator replication service error: {}", e);
+            }
+        });
+
+        Ok(Self { replica })
+    }
+
+    /// Wait until the primary either finishes the job (None)
+    /// or stops renewing its lease, returning the state to resume from
+    pub async fn wait_for_takeover(&self) -> Option<CoordinatorCheckpoint> {
+        let timeout = Duration::from_millis(FAILOVER_TIMEOUT_MS);
+        let mut ticker = tokio::time::interval(Duration::from_millis(HEARTBEAT_INTERVAL_MS));
+
+        loop {
+            ticker.tick().await;
+
+            let replica = self.replica.lock().unwrap();
+            if replica.checkpoint.finished {
+                return None;
+            }
+            // Nothing to take over until a primary has started a job
+            if replica
+                .last_contact
+                .is_some_and(|seen| seen.elapsed() > timeout)
+            {
+                return Some(replica.checkpoint.clone());
+            }
+        }
+    }
+}
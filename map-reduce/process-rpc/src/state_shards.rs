@@ -0,0 +1,189 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::shard_store::ShardStore;
+use async_trait::async_trait;
+use map_reduce_core::config::Config;
+use map_reduce_core::coordinator_state::CoordinatorState;
+use map_reduce_core::hot_keys::HotKey;
+use map_reduce_core::in_memory_state_store::{LocalStateAccess, SharedStateMap};
+use map_reduce_core::job_id::JobId;
+use map_reduce_core::sled_state_store::SledStateStore;
+use map_reduce_core::state_store::StateStore;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// Shard owning a key
+/// FNV-1a, so the coordinator and every worker process agree on the routing
+pub fn shard_for(key: &str, shards: usize) -> usize {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    (hash % shards as u64) as usize
+}
+
+/// Coordinator's view of the shared state, partitioned across the state servers by key hash
+#[derive(Clone)]
+pub struct StateShards {
+    shards: Vec<ShardStore>,
+}
+
+impl StateShards {
+    /// `count` shards, persisted under `state_path` when set,
+    /// otherwise in memory, each spilling to its own directory when a memory limit is set
+    pub fn new(config: &Config, count: usize) -> io::Result<Self> {
+        let shard_dir = |base: &Path, shard: usize| {
+            if count == 1 {
+                base.to_path_buf()
+            } else {
+                base.join(format!("shard-{}", shard))
+            }
+        };
+
+        let shards = (0..count)
+            .map(
+                |shard| match (&config.state_path, config.state_memory_limit) {
+                    (Some(path), _) => {
+                        let path = shard_dir(Path::new(path), shard);
+                        let store = SledStateStore::open(&path)?;
+                        if !store.is_empty() {
+                            println!("Recovered {} keys from {}", store.len(), path.display());
+                        }
+                        Ok(ShardStore::Sled(store))
+                    }
+                    (None, Some(limit)) => LocalStateAccess::with_spilling(
+                        shard_dir(&Path::new(&config.work_dir).join("spill"), shard),
+                        limit,
+                    )
+                    .map(ShardStore::Memory),
+                    (None, None) => Ok(ShardStore::Memory(LocalStateAccess::new())),
+                },
+            )
+            .collect::<io::Result<_>>()?;
+        Ok(Self { shards })
+    }
+
+    pub fn shards(&self) -> &[ShardStore] {
+        &self.shards
+    }
+
+    /// In-memory maps of every shard, e.g. to stream partial results
+    pub fn maps(&self) -> Vec<SharedStateMap> {
+        self.shards.iter().map(|shard| shard.get_map()).collect()
+    }
+
+    /// Keys and values of all shards in a single map
+    pub fn merged_map(&self) -> HashMap<String, Vec<i32>> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.get_map().lock().unwrap().clone())
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.clear();
+        }
+    }
+
+    /// Start a job on every shard, see `LocalStateAccess::begin_job`
+    pub fn begin_job(&self, job: JobId) {
+        for shard in &self.shards {
+            shard.begin_job(job);
+        }
+    }
+
+    /// Initialize each key on the shard owning it
+    pub async fn initialize(&self, keys: Vec<String>) {
+        let mut keys_per_shard = vec![Vec::new(); self.shards.len()];
+        for key in keys {
+            keys_per_shard[shard_for(&key, self.shards.len())].push(key);
+        }
+        for (shard, keys) in self.shards.iter().zip(keys_per_shard) {
+            shard.initialize(keys).await;
+        }
+    }
+
+    /// Copy of all keys and values of every shard
+    pub fn snapshot(&self) -> HashMap<String, Vec<i32>> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.snapshot())
+            .collect()
+    }
+
+    /// Spread the values of each hot key over its sub-keys, each on the shard owning it,
+    /// see `hot_keys::split_hot_keys`
+    pub async fn split_hot_keys(&self, hot_keys: &[HotKey]) {
+        for hot_key in hot_keys {
+            let owner = &self.shards[shard_for(&hot_key.key, self.shards.len())];
+            let values = owner.get(&hot_key.key).await;
+            for (key, values) in hot_key.shares(values) {
+                self.shards[shard_for(&key, self.shards.len())]
+                    .replace(key, values)
+                    .await;
+            }
+            println!("🔥 Split hot key {}", hot_key);
+        }
+    }
+
+    /// Make the persisted state of every shard durable
+    pub async fn flush(&self) -> io::Result<()> {
+        for shard in &self.shards {
+            shard.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Replace all shards with a previously taken snapshot, routing each key to its shard
+    pub fn restore(&self, snapshot: HashMap<String, Vec<i32>>) {
+        let mut per_shard = vec![HashMap::new(); self.shards.len()];
+        for (key, values) in snapshot {
+            per_shard[shard_for(&key, self.shards.len())].insert(key, values);
+        }
+        for (shard, snapshot) in self.shards.iter().zip(per_shard) {
+            shard.restore(snapshot);
+        }
+    }
+}
+
+#[async_trait]
+impl CoordinatorState for StateShards {
+    fn begin_job(&self, job: JobId) {
+        StateShards::begin_job(self, job);
+    }
+
+    fn clear(&self) {
+        StateShards::clear(self);
+    }
+
+    async fn initialize(&self, keys: Vec<String>) {
+        StateShards::initialize(self, keys).await;
+    }
+
+    fn snapshot(&self) -> HashMap<String, Vec<i32>> {
+        StateShards::snapshot(self)
+    }
+
+    fn restore(&self, snapshot: HashMap<String, Vec<i32>>) {
+        StateShards::restore(self, snapshot);
+    }
+
+    fn with_map<R>(&self, f: impl FnOnce(&HashMap<String, Vec<i32>>) -> R) -> R {
+        f(&self.merged_map())
+    }
+
+    fn maps(&self) -> Vec<SharedStateMap> {
+        StateShards::maps(self)
+    }
+
+    async fn split_hot_keys(&self, hot_keys: &[HotKey]) {
+        StateShards::split_hot_keys(self, hot_keys).await;
+    }
+
+    async fn flush(&self) -> io::Result<()> {
+        StateShards::flush(self).await
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::CheckToken;
+use crate::config::{network, STREAM_CONNECT_TIMEOUT_MS};
+use crate::grpc_worker_synchronization::{StatusEvent, SyncRoute};
+use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format::decode_results;
+use map_reduce_core::work_sender::WORK_QUEUE_CAPACITY;
+use proto::worker_event::Event;
+use proto::worker_stream_service_server::{WorkerStreamService, WorkerStreamServiceServer};
+use proto::{WorkerCommand, WorkerEvent};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{error, warn};
+
+/// Coordinator endpoint of the worker streams
+/// Queues commands until the worker connects and routes worker events
+/// to the synchronization of the phase they belong to
+pub struct StreamHub {
+    addr: String,
+    next_id: AtomicU64,
+    /// Command queues of workers whose stream is not connected yet
+    pending: Mutex<HashMap<u64, Receiver<WorkerCommand>>>,
+    routes: Mutex<HashMap<u64, SyncRoute>>,
+}
+
+static HUB: OnceLock<Arc<StreamHub>> = OnceLock::new();
+
+/// The coordinator's hub, started on first use on an ephemeral port
+pub fn hub() -> Arc<StreamHub> {
+    HUB.get_or_init(StreamHub::start).clone()
+}
+
+impl StreamHub {
+    fn start() -> Arc<Self> {
+        let std_listener = std::net::TcpListener::bind((network().bind_host.as_str(), 0))
+            .expect("Failed to bind worker stream listener");
+        std_listener
+            .set_nonblocking(true)
+            .expect("Failed to set nonblocking");
+        let addr = std_listener
+            .local_addr()
+            .expect("Failed to get local address");
+
+        let hub = Arc::new(Self {
+            addr: format!("{}:{}", network().advertise_host, addr.port()),
+            next_id: AtomicU64::new(0),
+            pending: Mutex::new(HashMap::new()),
+            routes: Mutex::new(HashMap::new()),
+        });
+
+        let service = WorkerStreamServiceImpl { hub: hub.clone() };
+        tokio::spawn(async move {
+            let listener = tokio::net::TcpListener::from_std(std_listener)
+                .expect("Failed to convert to tokio listener");
+            let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+            if let Err(e) = tls::server()
+                .add_service(WorkerStreamServiceServer::with_interceptor(
+                    service, CheckToken,
+                ))
+                .serve_with_incoming(incoming)
+                .await
+            {
+                error!(error = %e, "Worker stream service error");
+            }
+        });
+
+        hub
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Create the bounded command queue of a new worker connection
+    /// The queue is dropped if the worker does not connect within `STREAM_CONNECT_TIMEOUT_MS`,
+    /// e.g. as it never started or was replaced first, so sending it commands fails
+    pub fn open_connection(&self) -> (u64, Sender<WorkerCommand>) {
+        let connection_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel(WORK_QUEUE_CAPACITY);
+        self.pending.lock().unwrap().insert(connection_id, rx);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(STREAM_CONNECT_TIMEOUT_MS)).await;
+            hub().pending.lock().unwrap().remove(&connection_id);
+        });
+        (connection_id, tx)
+    }
+
+    /// Register the synchronization of a phase, returning its id
+    pub fn add_route(&self, route: SyncRoute) -> u64 {
+        let sync_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.routes.lock().unwrap().insert(sync_id, route);
+        sync_id
+    }
+
+    pub fn remove_route(&self, sync_id: u64) {
+        self.routes.lock().unwrap().remove(&sync_id);
+    }
+
+    fn route(&self, sync_id: u64) -> Option<SyncRoute> {
+        self.routes.lock().unwrap().get(&sync_id).cloned()
+    }
+
+    /// Deliver a worker event to the phase it belongs to
+    /// Events of phases that already ended are dropped
+    async fn dispatch(&self, event: Event) {
+        match event {
+            Event::Hello(_) => warn!("Unexpected hello on an established worker stream"),
+            Event::Register(status) => {
+                let worker_id = status.worker_id as usize;
+                if let Some(route) = self.route(status.sync_id) {
+                    if let Some(notify) = route.readiness_notifiers.get(worker_id) {
+                        // A replaced worker registering late must not wake up its replacement's handshake
+                        if route.epochs.is_current(worker_id, status.epoch) {
+                            route
+                                .last_seen
+                                .lock()
+                                .unwrap()
+                                .insert(worker_id, (status.epoch, Instant::now()));
+                            notify.notify_one();
+                        }
+                    } else {
+                        warn!(worker_id, "Received registration for unknown worker");
+                    }
+                }
+            }
+            Event::Completion(completion) => {
+                if let Some(route) = self.route(completion.sync_id) {
+                    let _ = route
+                        .completion_tx
+                        .send(StatusEvent {
+                            worker_id: completion.worker_id as usize,
+                            epoch: completion.epoch,
+                            task_id: completion.task_id.map(|task_id| task_id as usize),
+                            success: completion.success,
+                            counters: completion.counters.into_iter().collect(),
+                            results: decode_results(completion.results),
+                        })
+                        .await;
+                }
+            }
+            Event::Heartbeat(status) => {
+                if let Some(route) = self.route(status.sync_id) {
+                    let worker_id = status.worker_id as usize;
+                    if route.epochs.is_current(worker_id, status.epoch) {
+                        route
+                            .last_seen
+                            .lock()
+                            .unwrap()
+                            .insert(worker_id, (status.epoch, Instant::now()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+type CommandStream = Pin<Box<dyn Stream<Item = Result<WorkerCommand, Status>> + Send>>;
+
+/// gRPC Worker Stream Service implementation
+struct WorkerStreamServiceImpl {
+    hub: Arc<StreamHub>,
+}
+
+#[tonic::async_trait]
+impl WorkerStreamService for WorkerStreamServiceImpl {
+    type OpenStreamStream = CommandStream;
+
+    async fn open_stream(
+        &self,
+        request: Request<Streaming<WorkerEvent>>,
+    ) -> Result<Response<Self::OpenStreamStream>, Status> {
+        let mut events = request.into_inner();
+
+        // The first event identifies the worker
+        let connection_id = match events.message().await? {
+            Some(WorkerEvent {
+                event: Some(Event::Hello(hello)),
+            }) => hello.connection_id,
+            _ => return Err(Status::invalid_argument("Expected hello as first event")),
+        };
+
+        let commands = self
+            .hub
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&connection_id)
+            .ok_or_else(|| {
+                Status::not_found(format!("Unknown worker connection {}", connection_id))
+            })?;
+
+        let hub = self.hub.clone();
+        tokio::spawn(async move {
+            while let Ok(Some(event)) = events.message().await {
+                if let Some(event) = event.event {
+                    hub.dispatch(event).await;
+                }
+            }
+        });
+
+        let stream = ReceiverStream::new(commands).map(Ok);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
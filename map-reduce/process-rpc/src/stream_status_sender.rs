@@ -0,0 +1,81 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::HEARTBEAT_INTERVAL_MS;
+use crate::grpc_aggregator::route_results;
+use crate::rpc::proto;
+use crate::stream_work_receiver::send_event;
+use crate::wire_format::encode_results;
+use async_trait::async_trait;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::status_sender::StatusSender;
+use map_reduce_core::task_key::TaskKey;
+use proto::worker_event::Event;
+use proto::{StreamCompletion, StreamStatus};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Stream Synchronization Token
+/// Reports status up the worker's stream, tagged with the phase it belongs to
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct StreamStatusSender {
+    pub sync_id: u64,
+    pub worker_id: usize,
+    /// Epoch of the worker this token was handed to
+    pub epoch: u64,
+    /// Task whose outcome this token reports, None for the token handed over at initialization
+    pub task: Option<TaskKey>,
+    /// Aggregators the final results of reduce tasks are handed to, reported with the
+    /// completions if there are none
+    #[serde(default)]
+    pub aggregators: Vec<String>,
+}
+
+impl StreamStatusSender {
+    fn status(&self) -> StreamStatus {
+        StreamStatus {
+            sync_id: self.sync_id,
+            worker_id: self.worker_id as u64,
+            epoch: self.epoch,
+        }
+    }
+}
+
+#[async_trait]
+impl StatusSender for StreamStatusSender {
+    async fn register(&self, _worker_id: usize) -> bool {
+        send_event(Event::Register(self.status()))
+    }
+
+    fn task(&self) -> Option<TaskKey> {
+        self.task
+    }
+
+    async fn send(
+        &self,
+        result: Result<usize, ()>,
+        counters: Counters,
+        results: Vec<KeyResult>,
+    ) -> bool {
+        let (result, results) = route_results(&self.aggregators, self.task, result, results).await;
+        send_event(Event::Completion(StreamCompletion {
+            sync_id: self.sync_id,
+            worker_id: self.worker_id as u64,
+            success: result.is_ok(),
+            epoch: self.epoch,
+            task_id: self.task.map(|task| task.index as u64),
+            counters: counters.into_iter().collect(),
+            results: encode_results(results),
+        }))
+    }
+
+    fn heartbeat_interval(&self) -> Option<Duration> {
+        Some(Duration::from_millis(HEARTBEAT_INTERVAL_MS))
+    }
+
+    async fn heartbeat(&self, _worker_id: usize) -> bool {
+        send_event(Event::Heartbeat(self.status()))
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::auth::AttachToken;
+use crate::rpc::proto;
+use crate::tls;
+use crate::wire_format;
+use async_trait::async_trait;
+use map_reduce_core::job_context::BroadcastContext;
+use map_reduce_core::work_receiver::WorkReceiver;
+use map_reduce_core::worker_message::WorkerMessage;
+use proto::worker_command::Command;
+use proto::worker_event::Event;
+use proto::worker_stream_service_client::WorkerStreamServiceClient;
+use proto::{StreamHello, WorkerCommand, WorkerEvent};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::Streaming;
+
+/// Upstream half of this worker process's stream to the coordinator
+/// A worker process hosts a single worker, so the stream is process-wide
+static UPSTREAM: Mutex<Option<UnboundedSender<WorkerEvent>>> = Mutex::new(None);
+
+/// Send an event up the worker's stream
+/// Returns false if the stream is not connected
+pub fn send_event(event: Event) -> bool {
+    match UPSTREAM.lock().unwrap().as_ref() {
+        Some(upstream) => upstream.send(WorkerEvent { event: Some(event) }).is_ok(),
+        None => false,
+    }
+}
+
+/// Worker end of a worker stream
+/// Dials the coordinator on first receive and decodes the commands flowing down the stream
+#[derive(Serialize, Deserialize)]
+pub struct StreamWorkReceiver<A: BroadcastContext, C> {
+    coordinator_addr: String,
+    connection_id: u64,
+    #[serde(skip)]
+    commands: Option<Streaming<WorkerCommand>>,
+    /// Connection attempt in progress, kept across receives that time out
+    #[serde(skip)]
+    connecting: Option<JoinHandle<Option<Streaming<WorkerCommand>>>>,
+    /// Set on shutdown, so later receives don't dial the coordinator again
+    #[serde(skip)]
+    closed: bool,
+    #[serde(skip)]
+    _phantom: PhantomData<(A, C)>,
+}
+
+impl<A: BroadcastContext, C> StreamWorkReceiver<A, C> {
+    pub fn new(coordinator_addr: String, connection_id: u64) -> Self {
+        Self {
+            coordinator_addr,
+            connection_id,
+            commands: None,
+            connecting: None,
+            closed: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Open the stream to the coordinator, retrying while it comes up
+async fn connect(coordinator_addr: String, connection_id: u64) -> Option<Streaming<WorkerCommand>> {
+    let endpoint = tls::endpoint(&coordinator_addr).ok()?;
+
+    for _ in 0..5 {
+        if let Ok(channel) = endpoint.connect().await {
+            let mut client = WorkerStreamServiceClient::with_interceptor(channel, AttachToken);
+            let (upstream, events) = mpsc::unbounded_channel();
+            let hello = Event::Hello(StreamHello { connection_id });
+            let _ = upstream.send(WorkerEvent { event: Some(hello) });
+
+            match client
+                .open_stream(UnboundedReceiverStream::new(events))
+                .await
+            {
+                Ok(response) => {
+                    *UPSTREAM.lock().unwrap() = Some(upstream);
+                    return Some(response.into_inner());
+                }
+                Err(e) => {
+                    eprintln!("Failed to open worker stream: {}", e);
+                    return None;
+                }
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+
+    eprintln!("Failed to connect to coordinator at {}", coordinator_addr);
+    None
+}
+
+fn decode_command<A, C>(command: WorkerCommand) -> Result<WorkerMessage<A, C>, tonic::Status>
+where
+    A: DeserializeOwned + BroadcastContext,
+    C: DeserializeOwned,
+{
+    match command.command {
+        Some(Command::Initialize(initialize)) => Ok(WorkerMessage::Initialize(
+            wire_format::decode(command.version, &initialize.token, "synchronization token")?,
+            wire_format::decode(command.version, &initialize.context, "job context")?,
+        )),
+        Some(Command::Work(work)) => Ok(WorkerMessage::Work(
+            wire_format::decode_assignment(command.version, work.assignment, work.compressed)?,
+            wire_format::decode(command.version, &work.completion, "completion token")?,
+        )),
+        None => Err(tonic::Status::invalid_argument("Empty command")),
+    }
+}
+
+#[async_trait]
+impl<A, C> WorkReceiver<A, C> for StreamWorkReceiver<A, C>
+where
+    A: Send + Sync + DeserializeOwned + BroadcastContext + 'static,
+    C: Send + Sync + DeserializeOwned + 'static,
+{
+    async fn recv(&mut self) -> Option<WorkerMessage<A, C>> {
+        if self.closed {
+            return None;
+        }
+        if self.commands.is_none() {
+            // Connect in a task, so a caller timing out this receive doesn't abandon
+            // a stream the coordinator has already handed out
+            let connecting = self.connecting.get_or_insert_with(|| {
+                tokio::spawn(connect(self.coordinator_addr.clone(), self.connection_id))
+            });
+            let commands = connecting.await.ok().flatten();
+            self.connecting = None;
+            self.commands = Some(commands?);
+        }
+
+        let commands = self.commands.as_mut()?;
+        loop {
+            match commands.message().await {
+                Ok(Some(command)) => match decode_command(command) {
+                    Ok(message) => return Some(message),
+                    Err(e) => eprintln!("Dropping invalid command: {}", e.message()),
+                },
+                // Stream closed by the coordinator
+                Ok(None) | Err(_) => return None,
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) {
+        self.closed = true;
+        if let Some(connecting) = self.connecting.take() {
+            connecting.abort();
+        }
+        // Commands still in flight on the stream are dropped with it,
+        // ending the upstream half lets the coordinator close its end
+        self.commands = None;
+        UPSTREAM.lock().unwrap().take();
+    }
+}
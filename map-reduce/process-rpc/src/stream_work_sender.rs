@@ -0,0 +1,114 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::WORK_MESSAGE_VERSION;
+use crate::rpc::proto;
+use crate::stream_hub::hub;
+use crate::stream_work_receiver::StreamWorkReceiver;
+use crate::wire_format;
+use map_reduce_core::job_context::{BroadcastContext, JobContextOf};
+use map_reduce_core::work_sender::WorkSender;
+use proto::worker_command::Command;
+use proto::{StreamInitialize, StreamWork, WorkerCommand};
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+
+/// Coordinator end of a worker stream
+/// Commands are queued until the worker connects, then flow down its stream
+/// The queue is bounded, so sending work waits while the worker is behind
+#[derive(Clone)]
+pub struct StreamWorkSender<A, C> {
+    commands: Sender<WorkerCommand>,
+    bytes_sent: Arc<AtomicU64>,
+    raw_bytes_sent: Arc<AtomicU64>,
+    _phantom: PhantomData<(A, C)>,
+}
+
+impl<A: BroadcastContext, C> StreamWorkSender<A, C> {
+    /// Create a work channel pair
+    /// The worker dials the coordinator's stream hub, so no worker port is needed
+    pub fn create_pair() -> (Self, StreamWorkReceiver<A, C>) {
+        let hub = hub();
+        let (connection_id, commands) = hub.open_connection();
+
+        let sender = Self {
+            commands,
+            bytes_sent: Arc::new(AtomicU64::new(0)),
+            raw_bytes_sent: Arc::new(AtomicU64::new(0)),
+            _phantom: PhantomData,
+        };
+        let receiver = StreamWorkReceiver::new(hub.addr().to_string(), connection_id);
+
+        (sender, receiver)
+    }
+
+    /// Wrap a command in the versioned envelope
+    fn command(command: Command) -> WorkerCommand {
+        WorkerCommand {
+            version: WORK_MESSAGE_VERSION,
+            command: Some(command),
+        }
+    }
+}
+
+impl<A, C> WorkSender<A, C> for StreamWorkSender<A, C>
+where
+    A: Clone + Send + Sync + Serialize + BroadcastContext + 'static,
+    C: Clone + Send + Sync + Serialize + 'static,
+{
+    fn initialize(&self, token: C, context: JobContextOf<A>) {
+        let token = wire_format::encode(&token);
+        let context = wire_format::encode(&context);
+        let len = token.len() + context.len();
+        // The queue of a fresh worker is empty, so the token always fits
+        if self
+            .commands
+            .try_send(Self::command(Command::Initialize(StreamInitialize {
+                token,
+                context,
+            })))
+            .is_ok()
+        {
+            self.bytes_sent.fetch_add(len as u64, Ordering::Relaxed);
+            self.raw_bytes_sent.fetch_add(len as u64, Ordering::Relaxed);
+        } else {
+            eprintln!("Failed to queue initialization, worker stream is closed");
+        }
+    }
+
+    async fn send_work(&self, assignment: A, completion: C) -> Result<(), String> {
+        let assignment = wire_format::encode_assignment(&assignment);
+        let completion = wire_format::encode(&completion);
+        let len = assignment.bytes.len() + completion.len();
+        let raw_len = assignment.raw_len + completion.len();
+        let command = Self::command(Command::Work(StreamWork {
+            assignment: assignment.bytes,
+            completion,
+            compressed: assignment.compressed,
+        }));
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| "Failed to queue work, worker stream is closed".to_string())?;
+        self.bytes_sent.fetch_add(len as u64, Ordering::Relaxed);
+        self.raw_bytes_sent
+            .fetch_add(raw_len as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    fn raw_bytes_sent(&self) -> u64 {
+        self.raw_bytes_sent.load(Ordering::Relaxed)
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.commands.max_capacity() - self.commands.capacity()
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::aggregators;
+use crate::grpc_worker_synchronization::{
+    monitor_heartbeats, next_status, LastSeen, StatusEvent, SyncRoute,
+};
+use crate::stream_hub::hub;
+use crate::stream_status_sender::StreamStatusSender;
+use map_reduce_core::task_key::TaskKey;
+use map_reduce_core::worker_epochs::WorkerEpochs;
+use map_reduce_core::worker_synchronization::{WorkerStatus, WorkerSynchronization};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::Notify;
+
+/// Stream Synchronization Signaling
+/// Coordinator receives the status events routed by the stream hub for this phase
+pub struct StreamWorkerSynchronization {
+    sync_id: u64,
+    completion_rx: Receiver<StatusEvent>,
+    readiness_notifiers: Arc<Vec<Arc<Notify>>>,
+    epochs: WorkerEpochs,
+}
+
+impl WorkerSynchronization for StreamWorkerSynchronization {
+    type StatusSender = StreamStatusSender;
+
+    fn setup(num_workers: usize) -> Self {
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+
+        let mut notifiers = Vec::with_capacity(num_workers);
+        for _ in 0..num_workers {
+            notifiers.push(Arc::new(Notify::new()));
+        }
+        let notifiers = Arc::new(notifiers);
+        let last_seen: LastSeen = Arc::new(Mutex::new(HashMap::new()));
+        let epochs = WorkerEpochs::new(num_workers);
+
+        tokio::spawn(monitor_heartbeats(tx.clone(), last_seen.clone()));
+
+        let sync_id = hub().add_route(SyncRoute {
+            completion_tx: tx,
+            readiness_notifiers: notifiers.clone(),
+            last_seen,
+            epochs: epochs.clone(),
+        });
+
+        Self {
+            sync_id,
+            completion_rx: rx,
+            readiness_notifiers: notifiers,
+            epochs,
+        }
+    }
+
+    fn switch_phase(&mut self) {
+        self.epochs.advance_all();
+    }
+
+    fn get_status_sender(&self, worker_id: usize) -> Self::StatusSender {
+        StreamStatusSender {
+            sync_id: self.sync_id,
+            worker_id,
+            epoch: self.epochs.current(worker_id),
+            task: None,
+            aggregators: aggregators().to_vec(),
+        }
+    }
+
+    fn get_task_status_sender(&self, worker_id: usize, task: TaskKey) -> Self::StatusSender {
+        StreamStatusSender {
+            task: Some(task),
+            ..self.get_status_sender(worker_id)
+        }
+    }
+
+    async fn wait_for_worker_ready(&self, worker_id: usize) -> bool {
+        if let Some(notify) = self.readiness_notifiers.get(worker_id) {
+            notify.notified().await;
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn wait_next(&mut self) -> Option<WorkerStatus> {
+        next_status(&mut self.completion_rx, &self.epochs).await
+    }
+
+    async fn reset_worker(&mut self, worker_id: usize) -> Self::StatusSender {
+        // No explicit reset needed for Notify as it consumes the permit on wait
+        self.epochs.advance(worker_id);
+        self.get_status_sender(worker_id)
+    }
+}
+
+impl Drop for StreamWorkerSynchronization {
+    fn drop(&mut self) {
+        hub().remove_route(self.sync_id);
+    }
+}
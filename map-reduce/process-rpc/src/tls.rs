@@ -0,0 +1,71 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::{tls, TlsConfig};
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity, Server, ServerTlsConfig};
+
+/// Certificates of the TLS configuration, read once
+struct TlsMaterial {
+    ca: Certificate,
+    identity: Identity,
+}
+
+static MATERIAL: OnceLock<Option<TlsMaterial>> = OnceLock::new();
+
+/// Read the configured certificates, so a bad path fails at startup rather than on first use
+pub fn load_certificates() -> io::Result<()> {
+    if MATERIAL.get().is_some() {
+        return Ok(());
+    }
+    let material = tls().map(read_material).transpose()?;
+    let _ = MATERIAL.set(material);
+    Ok(())
+}
+
+fn read_material(config: &TlsConfig) -> io::Result<TlsMaterial> {
+    let read = |path: &str| {
+        fs::read(path).map_err(|e| io::Error::new(e.kind(), format!("Cannot read {}: {}", path, e)))
+    };
+    Ok(TlsMaterial {
+        ca: Certificate::from_pem(read(&config.ca_path)?),
+        identity: Identity::from_pem(read(&config.cert_path)?, read(&config.key_path)?),
+    })
+}
+
+fn material() -> Option<&'static TlsMaterial> {
+    MATERIAL
+        .get_or_init(|| {
+            tls().map(|config| {
+                read_material(config)
+                    .unwrap_or_else(|e| panic!("Failed to load TLS certificates: {}", e))
+            })
+        })
+        .as_ref()
+}
+
+/// Endpoint of the service listening on `addr`, reached over TLS when it is configured
+pub fn endpoint(addr: &str) -> Result<Endpoint, tonic::transport::Error> {
+    match (tls(), material()) {
+        (Some(config), Some(material)) => Endpoint::from_shared(format!("https://{}", addr))?
+            .tls_config(
+                ClientTlsConfig::new()
+                    .ca_certificate(material.ca.clone())
+                    .domain_name(config.domain.clone()),
+            ),
+        _ => Endpoint::from_shared(format!("http://{}", addr)),
+    }
+}
+
+/// Builder of a gRPC server, serving TLS when it is configured
+pub fn server() -> Server {
+    match material() {
+        Some(material) => Server::builder()
+            .tls_config(ServerTlsConfig::new().identity(material.identity.clone()))
+            .expect("Invalid TLS certificate or key"),
+        None => Server::builder(),
+    }
+}
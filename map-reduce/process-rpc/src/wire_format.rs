@@ -0,0 +1,89 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::config::{work_compression, WORK_MESSAGE_VERSION};
+use crate::rpc::proto::ReduceResult;
+use bincode::Options;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::result_value::ResultValue;
+use map_reduce_core::work_compression::{compress_payload, decompress_payload, WirePayload};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tonic::Status;
+
+/// Bincode with variable-length integers, so short strings don't pay for 8-byte length prefixes
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+/// Encode a work message payload with bincode
+pub fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    options()
+        .serialize(value)
+        .expect("Failed to encode work message payload")
+}
+
+/// Decode a work message payload, rejecting envelopes of another version
+pub fn decode<T: DeserializeOwned>(version: u32, bytes: &[u8], what: &str) -> Result<T, Status> {
+    if version != WORK_MESSAGE_VERSION {
+        return Err(Status::failed_precondition(format!(
+            "Unsupported {} encoding version {} (expected {})",
+            what, version, WORK_MESSAGE_VERSION
+        )));
+    }
+
+    options()
+        .deserialize(bytes)
+        .map_err(|e| Status::invalid_argument(format!("Invalid {}: {}", what, e)))
+}
+
+/// Encode an assignment, compressed if work compression is enabled and it is worth it
+pub fn encode_assignment<T: Serialize>(value: &T) -> WirePayload {
+    compress_payload(encode(value), work_compression())
+}
+
+/// Decode an assignment, decompressing it first if its envelope flags it as compressed
+pub fn decode_assignment<T: DeserializeOwned>(
+    version: u32,
+    bytes: Vec<u8>,
+    compressed: bool,
+) -> Result<T, Status> {
+    let bytes = decompress_payload(bytes, compressed)
+        .map_err(|e| Status::invalid_argument(format!("Invalid compressed assignment: {}", e)))?;
+    decode(version, &bytes, "assignment")
+}
+
+/// Results of a reduce task as carried by its completion report
+pub fn encode_results(results: Vec<KeyResult>) -> Vec<ReduceResult> {
+    results
+        .into_iter()
+        .map(|(key, value)| match value {
+            ResultValue::Scalar(value) => ReduceResult {
+                key,
+                values: vec![value],
+                list: false,
+            },
+            ResultValue::List(values) => ReduceResult {
+                key,
+                values,
+                list: true,
+            },
+        })
+        .collect()
+}
+
+/// Undo `encode_results`
+pub fn decode_results(results: Vec<ReduceResult>) -> Vec<KeyResult> {
+    results
+        .into_iter()
+        .map(|result| {
+            let value = if result.list {
+                ResultValue::List(result.values)
+            } else {
+                ResultValue::Scalar(result.values.first().copied().unwrap_or(0))
+            };
+            (result.key, value)
+        })
+        .collect()
+}
@@ -0,0 +1,99 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::grpc_status_sender::GrpcStatusSender;
+use crate::grpc_work_receiver::GrpcWorkReceiver;
+use crate::grpc_work_sender::GrpcWorkSender;
+use crate::grpc_worker_synchronization::GrpcWorkerSynchronization;
+use crate::stream_status_sender::StreamStatusSender;
+use crate::stream_work_receiver::StreamWorkReceiver;
+use crate::stream_work_sender::StreamWorkSender;
+use crate::stream_worker_synchronization::StreamWorkerSynchronization;
+use map_reduce_core::job_context::BroadcastContext;
+use map_reduce_core::status_sender::StatusSender;
+use map_reduce_core::work_receiver::WorkReceiver;
+use map_reduce_core::work_sender::WorkSender;
+use map_reduce_core::worker_synchronization::WorkerSynchronization;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+
+/// Bounds of the messages exchanged with worker processes
+pub trait WireMessage: Clone + Send + Sync + Serialize + DeserializeOwned + 'static {}
+
+impl<T> WireMessage for T where T: Clone + Send + Sync + Serialize + DeserializeOwned + 'static {}
+
+/// Transport carrying work and status messages between the coordinator and worker processes
+pub trait WorkTransport: Send + Sync + 'static {
+    /// Name passed to worker processes so they decode their task for the same transport
+    const NAME: &'static str;
+
+    type StatusSender: StatusSender + WireMessage;
+    type Synchronization: WorkerSynchronization<StatusSender = Self::StatusSender>;
+    type Sender<A>: WorkSender<A, Self::StatusSender>
+    where
+        A: WireMessage + BroadcastContext;
+    type Receiver<A>: WorkReceiver<A, Self::StatusSender> + Serialize + DeserializeOwned + 'static
+    where
+        A: WireMessage + BroadcastContext;
+
+    /// Create both ends of a worker's work channel
+    /// `port` is the worker's listening port, for transports where the worker is the server
+    fn create_pair<A>(
+        port: u16,
+    ) -> impl Future<Output = (Self::Sender<A>, Self::Receiver<A>)> + Send
+    where
+        A: WireMessage + BroadcastContext;
+}
+
+/// Unary RPCs: workers serve work on fixed ports and report status to a synchronization service
+pub struct UnaryTransport;
+
+impl WorkTransport for UnaryTransport {
+    const NAME: &'static str = "unary";
+
+    type StatusSender = GrpcStatusSender;
+    type Synchronization = GrpcWorkerSynchronization;
+    type Sender<A>
+        = GrpcWorkSender<A, GrpcStatusSender>
+    where
+        A: WireMessage + BroadcastContext;
+    type Receiver<A>
+        = GrpcWorkReceiver<A, GrpcStatusSender>
+    where
+        A: WireMessage + BroadcastContext;
+
+    async fn create_pair<A>(port: u16) -> (Self::Sender<A>, Self::Receiver<A>)
+    where
+        A: WireMessage + BroadcastContext,
+    {
+        GrpcWorkSender::create_pair(port).await
+    }
+}
+
+/// Bidirectional streaming: each worker dials the coordinator and holds a single stream
+/// carrying work down and status up, so workers need no listening port
+pub struct StreamTransport;
+
+impl WorkTransport for StreamTransport {
+    const NAME: &'static str = "stream";
+
+    type StatusSender = StreamStatusSender;
+    type Synchronization = StreamWorkerSynchronization;
+    type Sender<A>
+        = StreamWorkSender<A, StreamStatusSender>
+    where
+        A: WireMessage + BroadcastContext;
+    type Receiver<A>
+        = StreamWorkReceiver<A, StreamStatusSender>
+    where
+        A: WireMessage + BroadcastContext;
+
+    async fn create_pair<A>(_port: u16) -> (Self::Sender<A>, Self::Receiver<A>)
+    where
+        A: WireMessage + BroadcastContext,
+    {
+        StreamWorkSender::create_pair()
+    }
+}
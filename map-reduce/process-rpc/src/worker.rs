@@ -0,0 +1,213 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::cli_args::{ChaosArgs, WorkerRole};
+use crate::config::{set_job_token, set_kill_switch, set_tls, WorkerLimits};
+use crate::grpc_admin::force_fault;
+use crate::grpc_shutdown_signal::GrpcShutdownSignal;
+use crate::grpc_state_store::GrpcStateStore;
+use crate::tls;
+use crate::work_transport::{StreamTransport, UnaryTransport, WireMessage, WorkTransport};
+use map_reduce_core::config::JOB_TOKEN_ENV;
+use map_reduce_core::job_kind::JobKind;
+use map_reduce_core::kill_switch::ForcedFault;
+use map_reduce_core::local_object_store::{
+    DEFAULT_LOCAL_OBJECT_STORE_ROOT, LOCAL_OBJECT_STORE_ROOT_ENV,
+};
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::mapper::MapperTask;
+use map_reduce_core::reducer::ReducerTask;
+use map_reduce_core::worker_runtime::WorkerTask;
+use map_reduce_inverted_index::InvertedIndexProblem;
+use map_reduce_join::JoinProblem;
+use map_reduce_sort::SortProblem;
+use map_reduce_word_count::WordCountProblem;
+use map_reduce_word_search::WordSearchProblem;
+use std::io;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Run a worker process started by the coordinator
+pub async fn run_worker(role: WorkerRole) {
+    let (worker_type, args) = match role {
+        WorkerRole::Mapper(args) => ("mapper", args),
+        WorkerRole::Reducer(args) => ("reducer", args),
+        WorkerRole::Chaos(args) => return run_chaos(args).await,
+    };
+
+    set_tls(args.tls.to_config());
+    if let Err(e) = tls::load_certificates() {
+        panic!("Failed to load TLS certificates: {}", e);
+    }
+    match std::env::var(JOB_TOKEN_ENV) {
+        Ok(token) => set_job_token(token),
+        Err(_) => panic!("Missing job token: workers are started by the coordinator"),
+    }
+
+    match args.job {
+        JobKind::WordSearch => {
+            run_job::<WordSearchProblem>(worker_type, &args.transport, &args.task).await
+        }
+        JobKind::WordCount => {
+            run_job::<WordCountProblem>(worker_type, &args.transport, &args.task).await
+        }
+        JobKind::InvertedIndex => {
+            run_job::<InvertedIndexProblem>(worker_type, &args.transport, &args.task).await
+        }
+        JobKind::Sort => run_job::<SortProblem>(worker_type, &args.transport, &args.task).await,
+        JobKind::Join => run_job::<JoinProblem>(worker_type, &args.transport, &args.task).await,
+    }
+}
+
+/// Force a fault onto a running worker, authenticated with the job token of its coordinator
+async fn run_chaos(args: ChaosArgs) {
+    let fault = match args.fault.as_str() {
+        "clear" => None,
+        name => Some(
+            name.parse::<ForcedFault>()
+                .unwrap_or_else(|e| panic!("Invalid fault: {}", e)),
+        ),
+    };
+    set_tls(args.tls.to_config());
+    if let Err(e) = tls::load_certificates() {
+        panic!("Failed to load TLS certificates: {}", e);
+    }
+    match std::env::var(JOB_TOKEN_ENV) {
+        Ok(token) => set_job_token(token),
+        Err(_) => panic!(
+            "Missing job token: set {} to the token the coordinator was started with",
+            JOB_TOKEN_ENV
+        ),
+    }
+
+    match force_fault(&args.worker, fault).await {
+        Ok(()) => match fault {
+            Some(fault) => println!("💉 Forced {} onto worker {}", fault, args.worker),
+            None => println!("💉 Cleared the forced fault of worker {}", args.worker),
+        },
+        Err(e) => {
+            eprintln!(
+                "Failed to reach the admin service of worker {}: {}",
+                args.worker, e
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn run_job<P>(worker_type: &str, transport: &str, task_json: &str)
+where
+    P: MapReduceJob,
+    P::MapAssignment: WireMessage,
+    P::ReduceAssignment: WireMessage,
+{
+    match transport {
+        UnaryTransport::NAME => run::<UnaryTransport, P>(worker_type, task_json).await,
+        StreamTransport::NAME => run::<StreamTransport, P>(worker_type, task_json).await,
+        transport => panic!("Unknown transport: {}", transport),
+    }
+}
+
+async fn run<T, P>(worker_type: &str, task_json: &str)
+where
+    T: WorkTransport,
+    P: MapReduceJob,
+    P::MapAssignment: WireMessage,
+    P::ReduceAssignment: WireMessage,
+{
+    // Exit with the coordinator, even if it dies without killing this worker,
+    // so a standby taking over can start its own workers on the same ports
+    tokio::spawn(async {
+        let mut stdin = tokio::io::stdin();
+        let mut buf = [0u8; 64];
+        while matches!(stdin.read(&mut buf).await, Ok(n) if n > 0) {}
+        std::process::exit(0);
+    });
+
+    match worker_type {
+        "mapper" => {
+            let task: MapperTask<
+                P,
+                GrpcStateStore,
+                GrpcShutdownSignal,
+                T::Receiver<P::MapAssignment>,
+                T::StatusSender,
+            > = serde_json::from_str(task_json).expect("Failed to deserialize mapper task");
+            set_kill_switch(task.failures.kill_switch().clone());
+            task.run().await;
+        }
+        "reducer" => {
+            let task: ReducerTask<
+                P,
+                GrpcStateStore,
+                GrpcShutdownSignal,
+                T::Receiver<P::ReduceAssignment>,
+                T::StatusSender,
+            > = serde_json::from_str(task_json).expect("Failed to deserialize reducer task");
+            set_kill_switch(task.failures.kill_switch().clone());
+            task.run().await;
+        }
+        _ => panic!("Unknown worker type: {}", worker_type),
+    }
+
+    // The stdin watcher holds a blocking read the runtime would wait for on shutdown
+    std::process::exit(0);
+}
+
+/// Apply the coordinator's resource limits to a worker process, before its runtime starts:
+/// the niceness is set per thread and inherited by the threads started afterwards
+pub fn apply_worker_limits(role: &WorkerRole) {
+    let (worker_type, args) = match role {
+        WorkerRole::Mapper(args) => ("mapper", args),
+        WorkerRole::Reducer(args) => ("reducer", args),
+        WorkerRole::Chaos(_) => return,
+    };
+    if let Err(e) = apply_limits(worker_type, &args.limits.to_limits()) {
+        panic!("Failed to apply {} limits: {}", worker_type, e);
+    }
+}
+
+/// Apply the coordinator's resource limits to this process and move it to its own directory
+/// The coordinator passes absolute paths then, and the local object store is kept where it was
+fn apply_limits(worker_type: &str, limits: &WorkerLimits) -> io::Result<()> {
+    if let Some(dir) = &limits.work_dir {
+        let store_root = std::env::var(LOCAL_OBJECT_STORE_ROOT_ENV)
+            .unwrap_or_else(|_| DEFAULT_LOCAL_OBJECT_STORE_ROOT.to_string());
+        std::env::set_var(
+            LOCAL_OBJECT_STORE_ROOT_ENV,
+            std::path::absolute(store_root)?,
+        );
+
+        let dir = Path::new(dir).join(format!("{}-{}", worker_type, std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::env::set_current_dir(&dir)?;
+    }
+
+    #[cfg(unix)]
+    {
+        if let Some(memory_mb) = limits.memory_limit_mb {
+            let bytes = (memory_mb * 1024 * 1024) as libc::rlim_t;
+            let limit = libc::rlimit {
+                rlim_cur: bytes,
+                rlim_max: bytes,
+            };
+            // SAFETY: setrlimit only reads the rlimit it is given
+            if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        if let Some(nice) = limits.nice {
+            // SAFETY: setpriority takes no pointers, 0 is this process
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    if limits.memory_limit_mb.is_some() || limits.nice.is_some() {
+        eprintln!("Memory limits and niceness are only applied on Unix");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,284 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use async_trait::async_trait;
+use map_reduce_core::config::Config;
+use map_reduce_core::counters::{TaskCounters, KEYS_REDUCED};
+use map_reduce_core::data_chunk::DataChunk;
+use map_reduce_core::input_sampling::quantile_boundaries;
+use map_reduce_core::input_source::InputSplit;
+use map_reduce_core::job_context::{BroadcastContext, PhaseContext};
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::partitioner::{Partitioner, RangePartitioner};
+use map_reduce_core::reduce_output::ReduceOutput;
+use map_reduce_core::result_order::ResultOrder;
+use map_reduce_core::result_value::ResultValue;
+use map_reduce_core::scheduling_policy::TaskSize;
+use map_reduce_core::state_store::StateStore;
+use map_reduce_core::utils::read_split_records;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Range partitions per reducer when `reduce_partitions` is unset, so reducers that free up
+/// take over the remaining ranges instead of one slow reducer holding a fixed share
+pub const PARTITIONS_PER_REDUCER: usize = 4;
+
+/// Numbers the temporary files of the runs written by this process
+static NEXT_RUN_ATTEMPT: AtomicUsize = AtomicUsize::new(0);
+
+pub struct SortProblem;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct MapWorkAssignment {
+    pub chunk_id: usize,
+    pub data: DataChunk,
+    #[serde(default)]
+    pub split: Option<InputSplit>,
+}
+
+/// Range of records sorted by a reducer into the run `<output_dir>/part-<partition>`
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReduceWorkAssignment {
+    pub partition: usize,
+    pub keys: Vec<String>,
+    pub output_dir: String,
+}
+
+/// Partition `i` holds the records from `boundaries[i - 1]` (inclusive) to `boundaries[i]`
+/// The boundaries are set from the sampling pre-pass, before the map phase
+#[derive(Clone)]
+pub struct SortContext {
+    pub boundaries: Vec<String>,
+    pub output_dir: String,
+}
+
+impl TaskSize for MapWorkAssignment {
+    fn task_size(&self) -> u64 {
+        match &self.split {
+            Some(split) => split.length,
+            None => self.data.len() as u64,
+        }
+    }
+}
+
+impl TaskSize for ReduceWorkAssignment {
+    fn task_size(&self) -> u64 {
+        self.keys.len() as u64
+    }
+}
+
+impl BroadcastContext for MapWorkAssignment {
+    type JobContext = ();
+}
+
+impl BroadcastContext for ReduceWorkAssignment {
+    type JobContext = ();
+}
+
+#[async_trait]
+impl MapReduceJob for SortProblem {
+    type Input = Arc<[String]>;
+    type MapAssignment = MapWorkAssignment;
+    type ReduceAssignment = ReduceWorkAssignment;
+    type Context = SortContext;
+
+    const NAME: &'static str = "sort";
+
+    /// Clear the sorted runs of a previous job, the partition boundaries are left to the sample
+    fn create_context(
+        config: &Config,
+        _data: &Self::Input,
+        _targets: Vec<String>,
+    ) -> Self::Context {
+        let output_dir = prepare_output_dir(&Path::new(&config.work_dir).join("sorted"))
+            .expect("Failed to prepare the sorted output directory");
+        println!("Sorted runs: {}/part-*", output_dir.display());
+
+        SortContext {
+            boundaries: Vec::new(),
+            output_dir: output_dir.to_string_lossy().into_owned(),
+        }
+    }
+
+    fn sample_fraction(config: &Config) -> f64 {
+        config.sample_fraction
+    }
+
+    /// Each reducer writes the run of its range, which the records of a split key would leave
+    fn hot_key_threshold(_config: &Config) -> Option<usize> {
+        None
+    }
+
+    /// Split the sampled records into `reduce_partitions` range partitions
+    /// (several per reducer by default)
+    fn apply_sample(
+        config: &Config,
+        context: &mut Self::Context,
+        sample: &HashMap<String, Vec<i32>>,
+    ) {
+        let partitions = config
+            .reduce_partitions
+            .unwrap_or(config.num_reducers * PARTITIONS_PER_REDUCER);
+        context.boundaries = quantile_boundaries(sample, partitions);
+        println!(
+            "Sampled {} distinct records into {} range partitions",
+            sample.len(),
+            context.boundaries.len() + 1
+        );
+    }
+
+    fn create_map_assignments(
+        data: Self::Input,
+        _context: Self::Context,
+        chunks: Vec<Range<usize>>,
+    ) -> Vec<Self::MapAssignment> {
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, range)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::new(data.clone(), range),
+                split: None,
+            })
+            .collect()
+    }
+
+    fn create_split_assignments(
+        splits: Vec<InputSplit>,
+        _context: Self::Context,
+    ) -> Vec<Self::MapAssignment> {
+        splits
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, split)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::default(),
+                split: Some(split),
+            })
+            .collect()
+    }
+
+    /// Range partitioner: each key goes to the partition of the boundaries surrounding it,
+    /// so the runs of consecutive partitions follow each other in sort order
+    fn partitioner(context: &Self::Context) -> Box<dyn Partitioner> {
+        Box::new(RangePartitioner::new(context.boundaries.clone()))
+    }
+
+    fn create_reduce_assignments(
+        context: Self::Context,
+        partitions: Vec<Vec<String>>,
+    ) -> Vec<Self::ReduceAssignment> {
+        partitions
+            .into_iter()
+            .enumerate()
+            .map(|(partition, keys)| ReduceWorkAssignment {
+                partition,
+                keys,
+                output_dir: context.output_dir.clone(),
+            })
+            .collect()
+    }
+
+    fn result_order() -> ResultOrder {
+        ResultOrder::ByKey
+    }
+
+    async fn map_work<S>(
+        assignment: &Self::MapAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        let counts = match &assignment.split {
+            Some(split) => {
+                match read_split_records(split, counters, |lines| count_records(lines)) {
+                    Ok(counts) => counts,
+                    Err(e) => {
+                        eprintln!("Failed to read split {:?}: {}", split, e);
+                        return;
+                    }
+                }
+            }
+            None => {
+                counters.records_read(&assignment.data);
+                count_records(assignment.data.iter().cloned())
+            }
+        };
+
+        // Duplicate records are kept as a count, restored when the run is written
+        state.update_many(counts.into_iter().collect()).await;
+    }
+
+    /// Count the records of the partition and write them as a sorted run
+    /// Keys arrive sorted, the partitioner keeps the order of the keys it is given
+    async fn reduce_work<S>(
+        assignment: &Self::ReduceAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        output: &ReduceOutput,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        counters.increment(KEYS_REDUCED, assignment.keys.len() as u64);
+        let mut run = Vec::with_capacity(assignment.keys.len());
+        let counts = state
+            .merge_reduce(&assignment.keys, Self::partial_aggregate)
+            .await;
+        for (key, count) in assignment.keys.iter().zip(counts) {
+            state.replace(key.clone(), vec![count]).await;
+            output.emit(key.clone(), ResultValue::Scalar(count));
+            run.push((key.as_str(), count));
+        }
+
+        let path =
+            Path::new(&assignment.output_dir).join(format!("part-{:05}", assignment.partition));
+        if let Err(e) = write_run(&path, &run) {
+            eprintln!("Failed to write sorted run {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Records of a chunk with their number of occurrences, empty lines are skipped
+fn count_records(lines: impl Iterator<Item = String>) -> HashMap<String, i32> {
+    let mut counts = HashMap::new();
+    for line in lines.filter(|line| !line.is_empty()) {
+        *counts.entry(line).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Empty output directory for the runs, as an absolute path since worker processes
+/// may run in their own working directory
+fn prepare_output_dir(dir: &Path) -> io::Result<PathBuf> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+    dir.canonicalize()
+}
+
+/// Write a run next to its final path and rename it into place,
+/// so a retried reducer never leaves a partial run
+fn write_run(path: &Path, run: &[(&str, i32)]) -> io::Result<()> {
+    // Unique even for reducers sharing a process, e.g. a straggler and its retry
+    let attempt = NEXT_RUN_ATTEMPT.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_extension(format!("tmp-{}-{}", std::process::id(), attempt));
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    for (record, count) in run {
+        for _ in 0..*count {
+            writeln!(writer, "{}", record)?;
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+    fs::rename(&tmp, path)
+}
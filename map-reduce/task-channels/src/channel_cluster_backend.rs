@@ -0,0 +1,21 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::coordinator::run_with_control;
+use async_trait::async_trait;
+use map_reduce_core::cluster::ClusterBackend;
+use map_reduce_core::config::Config;
+use map_reduce_core::job_control::JobControl;
+
+/// Runs the jobs of a `MapReduceCluster` with workers as Tokio tasks talking over channels,
+/// within the embedding program
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChannelClusterBackend;
+
+#[async_trait]
+impl ClusterBackend for ChannelClusterBackend {
+    async fn run(&self, config: Config, control: JobControl) -> Result<(), String> {
+        run_with_control(config, control).await
+    }
+}
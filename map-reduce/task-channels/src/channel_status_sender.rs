@@ -2,13 +2,21 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use crate::channel_worker_synchronization::CompletionMessage;
 use async_trait::async_trait;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
 use map_reduce_core::status_sender::StatusSender;
+use map_reduce_core::task_key::TaskKey;
 use tokio::sync::mpsc;
 
 #[derive(Clone)]
 pub struct ChannelStatusSender {
-    pub tx: mpsc::Sender<Result<usize, ()>>,
+    pub tx: mpsc::Sender<CompletionMessage>,
+    /// Epoch of the worker this sender was handed to
+    pub epoch: u64,
+    /// Task whose outcome this sender reports, None for the sender handed over at initialization
+    pub task: Option<TaskKey>,
 }
 
 #[async_trait]
@@ -17,7 +25,20 @@ impl StatusSender for ChannelStatusSender {
         true
     }
 
-    async fn send(&self, result: Result<usize, ()>) -> bool {
-        self.tx.send(result).await.is_ok()
+    fn task(&self) -> Option<TaskKey> {
+        self.task
+    }
+
+    async fn send(
+        &self,
+        result: Result<usize, ()>,
+        counters: Counters,
+        results: Vec<KeyResult>,
+    ) -> bool {
+        let result = result.map(|worker_id| (worker_id, counters, results));
+        self.tx
+            .send((self.epoch, self.task.map(|task| task.index), result))
+            .await
+            .is_ok()
     }
 }
@@ -3,20 +3,26 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use async_trait::async_trait;
+use map_reduce_core::job_context::BroadcastContext;
 use map_reduce_core::{work_receiver::WorkReceiver, worker_message::WorkerMessage};
 use tokio::sync::mpsc;
 
-pub struct ChannelWorkReceiver<A, C> {
+pub struct ChannelWorkReceiver<A: BroadcastContext, C> {
     pub rx: mpsc::Receiver<WorkerMessage<A, C>>,
 }
 
 #[async_trait]
 impl<A, C> WorkReceiver<A, C> for ChannelWorkReceiver<A, C>
 where
-    A: Send,
+    A: Send + BroadcastContext,
     C: Send,
 {
     async fn recv(&mut self) -> Option<WorkerMessage<A, C>> {
         self.rx.recv().await
     }
+
+    async fn shutdown(&mut self) {
+        // Later sends fail, so the coordinator treats them as delivery failures
+        self.rx.close();
+    }
 }
@@ -2,18 +2,19 @@
 // Licensed under the Apache License, Version 2.0
 // http://www.apache.org/licenses/LICENSE-2.0
 
+use map_reduce_core::job_context::{BroadcastContext, JobContextOf};
 use map_reduce_core::work_sender::WorkSender;
 use map_reduce_core::worker_message::WorkerMessage;
 use tokio::sync::mpsc::{self, Receiver, Sender};
-use tokio::task;
 
 /// Tokio mpsc channel-based work channel
+/// Bounded, so sending waits while the worker is behind
 #[derive(Clone)]
-pub struct ChannelWorkSender<A, C> {
+pub struct ChannelWorkSender<A: BroadcastContext, C> {
     tx: Sender<WorkerMessage<A, C>>,
 }
 
-impl<A, C> ChannelWorkSender<A, C> {
+impl<A: BroadcastContext, C> ChannelWorkSender<A, C> {
     pub fn create_pair(buffer: usize) -> (Self, Receiver<WorkerMessage<A, C>>) {
         let (tx, rx) = mpsc::channel(buffer);
         (Self { tx }, rx)
@@ -22,20 +23,29 @@ impl<A, C> ChannelWorkSender<A, C> {
 
 impl<A, C> WorkSender<A, C> for ChannelWorkSender<A, C>
 where
-    A: Clone + Send + 'static,
+    A: Clone + Send + BroadcastContext + 'static,
     C: Clone + Send + 'static,
 {
-    fn initialize(&self, token: C) {
-        let tx = self.tx.clone();
-        task::spawn(async move {
-            let _ = tx.send(WorkerMessage::Initialize(token)).await;
-        });
+    fn initialize(&self, token: C, context: JobContextOf<A>) {
+        // Queued right away, so work sent next never overtakes the job context it needs
+        // The queue of a worker between phases or of a fresh one is empty, so it always fits
+        if self
+            .tx
+            .try_send(WorkerMessage::Initialize(token, context))
+            .is_err()
+        {
+            eprintln!("Failed to queue initialization, worker channel is full or closed");
+        }
     }
 
-    fn send_work(&self, assignment: A, completion: C) {
-        let tx = self.tx.clone();
-        task::spawn(async move {
-            let _ = tx.send(WorkerMessage::Work(assignment, completion)).await;
-        });
+    async fn send_work(&self, assignment: A, completion: C) -> Result<(), String> {
+        self.tx
+            .send(WorkerMessage::Work(assignment, completion))
+            .await
+            .map_err(|_| "worker channel is closed".to_string())
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
     }
 }
@@ -20,6 +20,10 @@ where
         task::spawn(task.run())
     }
 
+    fn exit_reason(handle: &Self::Handle) -> Option<String> {
+        handle.is_finished().then(|| "task exited".to_string())
+    }
+
     async fn join(handle: Self::Handle) -> Result<(), Self::Error> {
         handle.await
     }
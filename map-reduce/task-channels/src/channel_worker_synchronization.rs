@@ -3,18 +3,28 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use crate::channel_status_sender::ChannelStatusSender;
-use map_reduce_core::worker_synchronization::WorkerSynchronization;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::task_key::TaskKey;
+use map_reduce_core::worker_epochs::WorkerEpochs;
+use map_reduce_core::worker_synchronization::{WorkerStatus, WorkerSynchronization};
 use tokio::sync::mpsc::{self, Sender};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::{StreamExt, StreamMap};
 
-/// Completion message: Ok for success, Err for failure
-pub type CompletionMessage = Result<usize, ()>;
+/// Completion message tagged with the sender's epoch and task:
+/// Ok with the task's counters and results for success, Err for failure
+pub type CompletionMessage = (
+    u64,
+    Option<usize>,
+    Result<(usize, Counters, Vec<KeyResult>), ()>,
+);
 
 /// Channel-based completion signaling using tokio mpsc and StreamMap
 pub struct ChannelWorkerSynchronization {
     completion_txs: Vec<Sender<CompletionMessage>>,
     completion_streams: StreamMap<usize, ReceiverStream<CompletionMessage>>,
+    epochs: WorkerEpochs,
 }
 
 impl WorkerSynchronization for ChannelWorkerSynchronization {
@@ -33,12 +43,26 @@ impl WorkerSynchronization for ChannelWorkerSynchronization {
         Self {
             completion_txs,
             completion_streams,
+            epochs: WorkerEpochs::new(num_workers),
         }
     }
 
+    fn switch_phase(&mut self) {
+        self.epochs.advance_all();
+    }
+
     fn get_status_sender(&self, worker_id: usize) -> Self::StatusSender {
         ChannelStatusSender {
             tx: self.completion_txs[worker_id].clone(),
+            epoch: self.epochs.current(worker_id),
+            task: None,
+        }
+    }
+
+    fn get_task_status_sender(&self, worker_id: usize, task: TaskKey) -> Self::StatusSender {
+        ChannelStatusSender {
+            task: Some(task),
+            ..self.get_status_sender(worker_id)
         }
     }
 
@@ -47,36 +71,36 @@ impl WorkerSynchronization for ChannelWorkerSynchronization {
     }
 
     async fn reset_worker(&mut self, worker_id: usize) -> Self::StatusSender {
-        // Remove old stream
-        if let Some(mut stream) = self.completion_streams.remove(&worker_id) {
-            // Drain pending messages
-            while let Ok(Some(_)) =
-                tokio::time::timeout(tokio::time::Duration::from_millis(10), stream.next()).await
-            {
-                // Discard
-            }
-        }
-
-        // Create new channel
-        let (tx, rx) = mpsc::channel::<CompletionMessage>(10);
-
-        // Update tx and stream
-        self.completion_txs[worker_id] = tx;
-        self.completion_streams
-            .insert(worker_id, ReceiverStream::new(rx));
-
+        self.epochs.advance(worker_id);
         self.get_status_sender(worker_id)
     }
 
-    async fn wait_next(&mut self) -> Option<Result<usize, usize>> {
-        self.completion_streams
-            .next()
-            .await
-            .map(|(stream_idx, msg)| {
-                match msg {
-                    Ok(worker_id) => Ok(worker_id),
-                    Err(_) => Err(stream_idx), // stream_idx is the failed worker_id
+    async fn wait_next(&mut self) -> Option<WorkerStatus> {
+        while let Some((stream_idx, (epoch, task_id, msg))) = self.completion_streams.next().await {
+            // Late message of a replaced worker, only its completions are accounted for
+            if !self.epochs.is_current(stream_idx, epoch) {
+                if msg.is_ok() {
+                    return Some(WorkerStatus::Superseded {
+                        worker_id: stream_idx,
+                        task_id,
+                    });
                 }
-            })
+                continue;
+            }
+            return Some(match msg {
+                Ok((worker_id, counters, results)) => WorkerStatus::Completed {
+                    worker_id,
+                    task_id,
+                    counters,
+                    results,
+                },
+                // stream_idx is the failed worker_id
+                Err(_) => WorkerStatus::Failed {
+                    worker_id: stream_idx,
+                    task_id,
+                },
+            });
+        }
+        None
     }
 }
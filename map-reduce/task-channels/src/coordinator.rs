@@ -0,0 +1,201 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::channel_shutdown_signal::ChannelShutdownSignal;
+use crate::channel_status_sender::ChannelStatusSender;
+use crate::channel_work_sender::ChannelWorkSender;
+use crate::channel_worker_runtime::TokioRuntime;
+use crate::channel_worker_synchronization::ChannelWorkerSynchronization;
+use crate::mapper::{Mapper, MapperFactory};
+use crate::reducer::{Reducer, ReducerFactory};
+use map_reduce_core::config::Config;
+use map_reduce_core::failure_injection::{resolve_failure_seed, FailureInjection};
+use map_reduce_core::in_memory_state_store::LocalStateAccess;
+use map_reduce_core::job_control::JobControl;
+use map_reduce_core::job_kind::JobKind;
+use map_reduce_core::job_queue::JobQueue;
+use map_reduce_core::job_runner::JobRunner;
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::output_result_sink::OutputResultSink;
+use map_reduce_core::utils::{create_local_state, initialize_phase};
+use map_reduce_inverted_index::InvertedIndexProblem;
+use map_reduce_join::JoinProblem;
+use map_reduce_sort::SortProblem;
+use map_reduce_word_count::WordCountProblem;
+use map_reduce_word_search::WordSearchProblem;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::{signal, spawn};
+use tokio_util::sync::CancellationToken;
+use tracing::{info_span, Instrument};
+
+/// Run the job, and the jobs queued after it, with workers as Tokio tasks talking over channels
+pub async fn run(config: Config) -> Result<(), String> {
+    let control = JobControl::new(&config, Box::new(OutputResultSink::new()));
+
+    // Setup Ctrl+C handler
+    let cancellation = control.cancellation.clone();
+    spawn(async move {
+        signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
+        println!("\n\n=== Ctrl+C received, initiating shutdown ===");
+        cancellation.cancel();
+    });
+
+    run_with_control(config, control).await
+}
+
+/// Run the jobs, handing their final results to the control's sink instead of the configured
+/// outputs, until they are done or cancelled
+/// Fails with the jobs whose results could not be handed over, e.g. with dead-lettered tasks
+pub async fn run_with_control(config: Config, control: JobControl) -> Result<(), String> {
+    let span = info_span!("pool", job = %config.job);
+    async move {
+        match config.job {
+            JobKind::WordSearch => run_pool::<WordSearchProblem>(config, control).await,
+            JobKind::WordCount => run_pool::<WordCountProblem>(config, control).await,
+            JobKind::InvertedIndex => run_pool::<InvertedIndexProblem>(config, control).await,
+            JobKind::Sort => run_pool::<SortProblem>(config, control).await,
+            JobKind::Join => run_pool::<JoinProblem>(config, control).await,
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+/// Start a pool of workers and run the queued jobs on it, one after the other
+async fn run_pool<P>(config: Config, mut control: JobControl) -> Result<(), String>
+where
+    P: MapReduceJob<Input = Arc<[String]>>,
+{
+    let start_time = Instant::now();
+
+    println!(
+        "=== MAP-REDUCE {} ===",
+        P::NAME.to_uppercase().replace('-', " ")
+    );
+    config.print_summary();
+    let failure_seed = resolve_failure_seed(&config);
+    let queue = JobQueue::from_config(&config).expect("Failed to queue jobs");
+
+    // Create state access layer
+    let state = create_local_state(&config).expect("Failed to create state store");
+
+    println!("\nStarting MapReduce...");
+
+    // Create cancellation token
+    let cancel_token = CancellationToken::new();
+    let shutdown_signal = ChannelShutdownSignal::new(cancel_token.clone());
+
+    // Create mapper factory
+    let mapper_factory =
+        MapperFactory::<P, LocalStateAccess, TokioRuntime, ChannelShutdownSignal>::new(
+            state.clone(),
+            shutdown_signal.clone(),
+            FailureInjection::mappers(&config, failure_seed),
+        );
+
+    // Create initial mapper pool
+    let mut mappers = initialize_phase::<
+        Mapper<
+            P,
+            LocalStateAccess,
+            ChannelWorkSender<P::MapAssignment, ChannelStatusSender>,
+            TokioRuntime,
+            ChannelShutdownSignal,
+        >,
+        ChannelWorkerSynchronization,
+        _,
+    >(
+        config.num_mappers,
+        mapper_factory,
+        config.mapper_timeout_ms,
+        config.max_task_retries,
+        config.max_task_timeouts,
+        config.straggler_factor,
+        config.scheduling_policy,
+        false,
+        &config.failure_domains,
+    )
+    .await;
+
+    // Create reducer factory
+    let reducer_factory =
+        ReducerFactory::<P, LocalStateAccess, TokioRuntime, ChannelShutdownSignal>::new(
+            state.clone(),
+            shutdown_signal.clone(),
+            FailureInjection::reducers(&config, failure_seed),
+            config.top_k,
+        );
+
+    // Create initial reducer pool
+    let mut reducers = initialize_phase::<
+        Reducer<
+            P,
+            LocalStateAccess,
+            ChannelWorkSender<P::ReduceAssignment, ChannelStatusSender>,
+            TokioRuntime,
+            ChannelShutdownSignal,
+        >,
+        ChannelWorkerSynchronization,
+        _,
+    >(
+        config.num_reducers,
+        reducer_factory,
+        config.reducer_timeout_ms,
+        config.max_task_retries,
+        config.max_task_timeouts,
+        config.straggler_factor,
+        config.scheduling_policy,
+        config.partition_affinity,
+        &config.failure_domains,
+    )
+    .await;
+
+    // Cancelling the jobs stops the workers
+    let cancellation = control.cancellation.clone();
+    let cancelled_token = cancel_token.clone();
+    let cancel_watch = spawn(async move {
+        cancellation.cancelled().await;
+        cancelled_token.cancel();
+    });
+
+    // Run the queued jobs on the same workers, each on a state of its own
+    let outcome = JobRunner {
+        variant: "task-channels".to_string(),
+        state: &state,
+        shutdown_signal: &shutdown_signal,
+        hooks: &(),
+    }
+    .run::<P, _, _, _, _, _, _>(
+        &config,
+        queue,
+        None,
+        &mut mappers,
+        &mut reducers,
+        &mut control,
+    )
+    .await;
+
+    // Initiate shutdown
+    println!("\n=== SHUTTING DOWN ===");
+    cancel_watch.abort();
+    cancel_token.cancel();
+
+    // Wait for all workers to shut down
+    for (idx, worker) in mappers.workers.into_iter().enumerate() {
+        if let Err(e) = worker.wait().await {
+            eprintln!("Mapper {} shutdown failed: {}", idx, e);
+        }
+    }
+    for (idx, worker) in reducers.workers.into_iter().enumerate() {
+        if let Err(e) = worker.wait().await {
+            eprintln!("Reducer {} shutdown failed: {}", idx, e);
+        }
+    }
+
+    let elapsed = start_time.elapsed();
+    println!("\n=== PROGRAM COMPLETE ===");
+    println!("Total time: {:.2}s", elapsed.as_secs_f64());
+    outcome
+}
@@ -0,0 +1,14 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+pub mod channel_cluster_backend;
+mod channel_shutdown_signal;
+mod channel_status_sender;
+mod channel_work_receiver;
+mod channel_work_sender;
+mod channel_worker_runtime;
+mod channel_worker_synchronization;
+pub mod coordinator;
+mod mapper;
+mod reducer;
@@ -6,12 +6,15 @@ use crate::channel_status_sender::ChannelStatusSender;
 use crate::channel_work_receiver::ChannelWorkReceiver;
 use crate::channel_work_sender::ChannelWorkSender;
 use async_trait::async_trait;
+use map_reduce_core::failure_injection::FailureInjection;
 use map_reduce_core::map_reduce_job::MapReduceJob;
 use map_reduce_core::reducer::ReducerTask;
 use map_reduce_core::shutdown_signal::ShutdownSignal;
 use map_reduce_core::state_store::StateStore;
+use map_reduce_core::work_sender::WORK_QUEUE_CAPACITY;
 use map_reduce_core::worker_factory::WorkerFactory;
 use map_reduce_core::worker_runtime::WorkerRuntime;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 pub type Reducer<P, S, W, R, SD> = map_reduce_core::reducer::Reducer<
@@ -27,26 +30,22 @@ pub type Reducer<P, S, W, R, SD> = map_reduce_core::reducer::Reducer<
 pub struct ReducerFactory<P, S, R, SD> {
     state: S,
     shutdown: SD,
-    failure_prob: u32,
-    straggler_prob: u32,
-    straggler_delay: u64,
+    failures: FailureInjection,
+    /// Results each reduce task reports at most
+    top_k: Option<usize>,
+    /// Workers created so far in each slot, the epoch of the next one
+    epochs: HashMap<usize, u64>,
     _phantom: PhantomData<(P, R)>,
 }
 
 impl<P, S, R, SD> ReducerFactory<P, S, R, SD> {
-    pub fn new(
-        state: S,
-        shutdown: SD,
-        failure_prob: u32,
-        straggler_prob: u32,
-        straggler_delay: u64,
-    ) -> Self {
+    pub fn new(state: S, shutdown: SD, failures: FailureInjection, top_k: Option<usize>) -> Self {
         Self {
             state,
             shutdown,
-            failure_prob,
-            straggler_prob,
-            straggler_delay,
+            failures,
+            top_k,
+            epochs: HashMap::new(),
             _phantom: PhantomData,
         }
     }
@@ -94,18 +93,22 @@ where
         let (work_channel, work_rx) = ChannelWorkSender::<
             <P as MapReduceJob>::ReduceAssignment,
             ChannelStatusSender,
-        >::create_pair(10);
+        >::create_pair(WORK_QUEUE_CAPACITY);
         let wrapped_rx = ChannelWorkReceiver { rx: work_rx };
 
+        // Replacements of a worker get a fresh epoch, hence a failure schedule of their own
+        let epoch = self.epochs.entry(id).or_default();
+        let failures = self.failures.for_worker(id, *epoch);
+        *epoch += 1;
+
         map_reduce_core::reducer::Reducer::new(
             id,
             self.state.clone(),
             self.shutdown.clone(),
             wrapped_rx,
             work_channel,
-            self.failure_prob,
-            self.straggler_prob,
-            self.straggler_delay,
+            failures,
+            self.top_k,
         )
     }
 }
@@ -0,0 +1,201 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use crate::mapper::{Mapper, MapperFactory};
+use crate::reducer::{Reducer, ReducerFactory};
+use crate::socket_shutdown_signal::SocketShutdownSignal;
+use crate::socket_status_sender::SocketStatusSender;
+use crate::socket_work_sender::SocketWorkSender;
+use crate::socket_worker_runtime::ThreadRuntime;
+use crate::socket_worker_synchronization::SocketWorkerSynchronization;
+use map_reduce_core::config::Config;
+use map_reduce_core::failure_injection::{resolve_failure_seed, FailureInjection};
+use map_reduce_core::in_memory_state_store::LocalStateAccess;
+use map_reduce_core::job_control::JobControl;
+use map_reduce_core::job_kind::JobKind;
+use map_reduce_core::job_queue::JobQueue;
+use map_reduce_core::job_runner::JobRunner;
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::output_result_sink::OutputResultSink;
+use map_reduce_core::utils::{create_local_state, initialize_phase};
+use map_reduce_inverted_index::InvertedIndexProblem;
+use map_reduce_join::JoinProblem;
+use map_reduce_sort::SortProblem;
+use map_reduce_word_count::WordCountProblem;
+use map_reduce_word_search::WordSearchProblem;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info_span, Instrument};
+
+/// Run the job, and the jobs queued after it, with workers as OS threads talking over TCP sockets
+pub async fn run(config: Config) -> Result<(), String> {
+    let control = JobControl::new(&config, Box::new(OutputResultSink::new()));
+
+    // Setup Ctrl+C handler
+    let cancellation = control.cancellation.clone();
+    ctrlc::set_handler(move || {
+        println!("\n\n=== Ctrl+C received, initiating shutdown ===");
+        cancellation.cancel();
+    })
+    .expect("Error setting Ctrl-C handler");
+
+    run_with_control(config, control).await
+}
+
+/// Run the jobs, handing their final results to the control's sink instead of the configured
+/// outputs, until they are done or cancelled
+/// Fails with the jobs whose results could not be handed over, e.g. with dead-lettered tasks
+pub async fn run_with_control(config: Config, control: JobControl) -> Result<(), String> {
+    let span = info_span!("pool", job = %config.job);
+    async move {
+        match config.job {
+            JobKind::WordSearch => run_pool::<WordSearchProblem>(config, control).await,
+            JobKind::WordCount => run_pool::<WordCountProblem>(config, control).await,
+            JobKind::InvertedIndex => run_pool::<InvertedIndexProblem>(config, control).await,
+            JobKind::Sort => run_pool::<SortProblem>(config, control).await,
+            JobKind::Join => run_pool::<JoinProblem>(config, control).await,
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+/// Start a pool of workers and run the queued jobs on it, one after the other
+async fn run_pool<P>(config: Config, mut control: JobControl) -> Result<(), String>
+where
+    P: MapReduceJob<Input = Arc<[String]>>,
+    P::MapAssignment: Serialize + DeserializeOwned,
+    P::ReduceAssignment: Serialize + DeserializeOwned,
+{
+    let start_time = Instant::now();
+
+    println!(
+        "=== MAP-REDUCE {} (Thread-Socket) ===",
+        P::NAME.to_uppercase().replace('-', " ")
+    );
+    config.print_summary();
+    let failure_seed = resolve_failure_seed(&config);
+    let queue = JobQueue::from_config(&config).expect("Failed to queue jobs");
+
+    // Create state
+    let state = create_local_state(&config).expect("Failed to create state store");
+
+    println!("\nStarting MapReduce...");
+
+    // Create shutdown signal
+    let shutdown_signal = SocketShutdownSignal::new();
+
+    // Cancelling the jobs stops the workers
+    let cancellation = control.cancellation.clone();
+    let cancelled_signal = shutdown_signal.clone();
+    let cancel_watch = tokio::spawn(async move {
+        cancellation.cancelled().await;
+        cancelled_signal.shutdown();
+    });
+
+    // Create mapper factory
+    let mapper_factory =
+        MapperFactory::<P, LocalStateAccess, ThreadRuntime, SocketShutdownSignal>::new(
+            state.clone(),
+            shutdown_signal.clone(),
+            FailureInjection::mappers(&config, failure_seed),
+            config.compress_work,
+        );
+
+    // Initialize mapper phase
+    let mut mappers = initialize_phase::<
+        Mapper<
+            P,
+            LocalStateAccess,
+            SocketWorkSender<P::MapAssignment, SocketStatusSender>,
+            ThreadRuntime,
+            SocketShutdownSignal,
+        >,
+        SocketWorkerSynchronization,
+        _,
+    >(
+        config.num_mappers,
+        mapper_factory,
+        config.mapper_timeout_ms,
+        config.max_task_retries,
+        config.max_task_timeouts,
+        config.straggler_factor,
+        config.scheduling_policy,
+        false,
+        &config.failure_domains,
+    )
+    .await;
+
+    // Create reducer factory
+    let reducer_factory =
+        ReducerFactory::<P, LocalStateAccess, ThreadRuntime, SocketShutdownSignal>::new(
+            state.clone(),
+            shutdown_signal.clone(),
+            FailureInjection::reducers(&config, failure_seed),
+            config.compress_work,
+            config.top_k,
+        );
+
+    // Initialize reducer phase
+    let mut reducers = initialize_phase::<
+        Reducer<
+            P,
+            LocalStateAccess,
+            SocketWorkSender<P::ReduceAssignment, SocketStatusSender>,
+            ThreadRuntime,
+            SocketShutdownSignal,
+        >,
+        SocketWorkerSynchronization,
+        _,
+    >(
+        config.num_reducers,
+        reducer_factory,
+        config.reducer_timeout_ms,
+        config.max_task_retries,
+        config.max_task_timeouts,
+        config.straggler_factor,
+        config.scheduling_policy,
+        config.partition_affinity,
+        &config.failure_domains,
+    )
+    .await;
+
+    // Run the queued jobs on the same workers, each on a state of its own
+    let outcome = JobRunner {
+        variant: "thread-socket".to_string(),
+        state: &state,
+        shutdown_signal: &shutdown_signal,
+        hooks: &(),
+    }
+    .run::<P, _, _, _, _, _, _>(
+        &config,
+        queue,
+        None,
+        &mut mappers,
+        &mut reducers,
+        &mut control,
+    )
+    .await;
+
+    // Shutdown signal and wait for workers to exit
+    println!("\n=== SHUTTING DOWN ===");
+    cancel_watch.abort();
+    shutdown_signal.shutdown();
+
+    // Drop workers to release resources
+    drop(mappers);
+    drop(reducers);
+
+    // Give threads a moment to check shutdown flag and exit
+    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+    println!("All workers terminated gracefully");
+
+    let elapsed = start_time.elapsed();
+    println!("\n=== PROGRAM COMPLETE ===");
+    println!("Total time: {:.2}s", elapsed.as_secs_f64());
+    outcome
+}
@@ -0,0 +1,14 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+pub mod coordinator;
+mod mapper;
+mod reducer;
+pub mod socket_cluster_backend;
+mod socket_shutdown_signal;
+mod socket_status_sender;
+mod socket_work_receiver;
+mod socket_work_sender;
+mod socket_worker_runtime;
+mod socket_worker_synchronization;
@@ -6,12 +6,14 @@ use crate::socket_status_sender::SocketStatusSender;
 use crate::socket_work_receiver::SocketWorkReceiver;
 use crate::socket_work_sender::SocketWorkSender;
 use async_trait::async_trait;
+use map_reduce_core::failure_injection::FailureInjection;
 use map_reduce_core::map_reduce_job::MapReduceJob;
 use map_reduce_core::reducer::ReducerTask;
 use map_reduce_core::shutdown_signal::ShutdownSignal;
 use map_reduce_core::state_store::StateStore;
 use map_reduce_core::worker_factory::WorkerFactory;
 use map_reduce_core::worker_runtime::WorkerRuntime;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 pub type Reducer<P, S, W, R, SD> = map_reduce_core::reducer::Reducer<
@@ -27,9 +29,13 @@ pub type Reducer<P, S, W, R, SD> = map_reduce_core::reducer::Reducer<
 pub struct ReducerFactory<P, S, R, SD> {
     state: S,
     shutdown: SD,
-    failure_prob: u32,
-    straggler_prob: u32,
-    straggler_delay: u64,
+    failures: FailureInjection,
+    /// Compress the workers' assignments
+    compress_work: bool,
+    /// Results each reduce task reports at most
+    top_k: Option<usize>,
+    /// Workers created so far in each slot, the epoch of the next one
+    epochs: HashMap<usize, u64>,
     _phantom: PhantomData<(P, R)>,
 }
 
@@ -37,16 +43,17 @@ impl<P, S, R, SD> ReducerFactory<P, S, R, SD> {
     pub fn new(
         state: S,
         shutdown: SD,
-        failure_prob: u32,
-        straggler_prob: u32,
-        straggler_delay: u64,
+        failures: FailureInjection,
+        compress_work: bool,
+        top_k: Option<usize>,
     ) -> Self {
         Self {
             state,
             shutdown,
-            failure_prob,
-            straggler_prob,
-            straggler_delay,
+            failures,
+            compress_work,
+            top_k,
+            epochs: HashMap::new(),
             _phantom: PhantomData,
         }
     }
@@ -92,7 +99,12 @@ where
         R,
         SD,
     > {
-        let (work_channel, work_rx) = SocketWorkSender::create_pair(0);
+        let (work_channel, work_rx) = SocketWorkSender::create_pair(0, self.compress_work);
+
+        // Replacements of a worker get a fresh epoch, hence a failure schedule of their own
+        let epoch = self.epochs.entry(id).or_default();
+        let failures = self.failures.for_worker(id, *epoch);
+        *epoch += 1;
 
         map_reduce_core::reducer::Reducer::new(
             id,
@@ -100,9 +112,8 @@ where
             self.shutdown.clone(),
             work_rx,
             work_channel,
-            self.failure_prob,
-            self.straggler_prob,
-            self.straggler_delay,
+            failures,
+            self.top_k,
         )
     }
 }
@@ -4,7 +4,10 @@
 
 use crate::socket_worker_synchronization::CompletionMessage;
 use async_trait::async_trait;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
 use map_reduce_core::status_sender::StatusSender;
+use map_reduce_core::task_key::TaskKey;
 use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
 
@@ -13,6 +16,10 @@ use tokio::io::AsyncWriteExt;
 pub struct SocketStatusSender {
     pub port: u16,
     pub worker_id: usize,
+    /// Epoch of the worker this sender was handed to
+    pub epoch: u64,
+    /// Task whose outcome this sender reports, None for the sender handed over at initialization
+    pub task: Option<TaskKey>,
 }
 
 #[async_trait]
@@ -21,11 +28,30 @@ impl StatusSender for SocketStatusSender {
         true
     }
 
-    async fn send(&self, result: Result<usize, ()>) -> bool {
+    fn task(&self) -> Option<TaskKey> {
+        self.task
+    }
+
+    async fn send(
+        &self,
+        result: Result<usize, ()>,
+        counters: Counters,
+        results: Vec<KeyResult>,
+    ) -> bool {
         let addr = format!("127.0.0.1:{}", self.port);
         let message = match result {
-            Ok(id) => CompletionMessage::Success(id),
-            Err(_) => CompletionMessage::Failure(self.worker_id),
+            Ok(id) => CompletionMessage::Success {
+                worker_id: id,
+                epoch: self.epoch,
+                task_id: self.task.map(|task| task.index),
+                counters,
+                results,
+            },
+            Err(_) => CompletionMessage::Failure {
+                worker_id: self.worker_id,
+                epoch: self.epoch,
+                task_id: self.task.map(|task| task.index),
+            },
         };
         if let Ok(mut stream) = tokio::net::TcpStream::connect(&addr).await {
             if let Ok(serialized) = serde_json::to_vec(&message) {
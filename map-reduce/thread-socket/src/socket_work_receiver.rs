@@ -3,39 +3,92 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use async_trait::async_trait;
+use map_reduce_core::job_context::BroadcastContext;
+use map_reduce_core::work_compression::decompress_payload;
 use map_reduce_core::work_receiver::WorkReceiver;
+use map_reduce_core::work_sender::WORK_QUEUE_CAPACITY;
 use map_reduce_core::worker_message::WorkerMessage;
 use serde::Deserialize;
 use std::marker::PhantomData;
-use std::sync::Arc;
-use tokio::io::AsyncReadExt;
-use tokio::net::TcpListener;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinHandle;
 
 /// Socket-based work receiver
-pub struct SocketWorkReceiver<A, C> {
-    pub listener: Arc<TcpListener>,
+pub struct SocketWorkReceiver<A: BroadcastContext, C> {
+    /// Listener handed to the accept loop on first receive
+    pub listener: Option<TcpListener>,
+    /// Bounded queue fed by the accept loop, started on first receive
+    pub queue: Option<Receiver<WorkerMessage<A, C>>>,
+    /// Accept loop owning the listener, aborted on shutdown to close it
+    pub accepting: Option<JoinHandle<()>>,
     pub _phantom: PhantomData<(A, C)>,
 }
 
+/// Read one length-prefixed message, decompressing it if flagged
+async fn read_message<A, C>(stream: &mut TcpStream) -> Option<WorkerMessage<A, C>>
+where
+    A: for<'de> Deserialize<'de> + BroadcastContext,
+    C: for<'de> Deserialize<'de>,
+{
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).await.ok()?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    let compressed = stream.read_u8().await.ok()? != 0;
+    let mut buffer = vec![0u8; len];
+    stream.read_exact(&mut buffer).await.ok()?;
+    let buffer = decompress_payload(buffer, compressed).ok()?;
+    serde_json::from_slice(&buffer).ok()
+}
+
+/// Queue incoming messages, acknowledging each with the resulting queue depth
+/// While the queue is full the acknowledgement is held back, blocking the sender
+async fn accept_messages<A, C>(listener: TcpListener, queue: Sender<WorkerMessage<A, C>>)
+where
+    A: for<'de> Deserialize<'de> + BroadcastContext,
+    C: for<'de> Deserialize<'de>,
+{
+    while let Ok((mut stream, _)) = listener.accept().await {
+        let Some(message) = read_message(&mut stream).await else {
+            continue;
+        };
+        if queue.send(message).await.is_err() {
+            // The worker stopped receiving
+            break;
+        }
+        let depth = (queue.max_capacity() - queue.capacity()) as u32;
+        let _ = stream.write_all(&depth.to_be_bytes()).await;
+    }
+}
+
 #[async_trait]
 impl<A, C> WorkReceiver<A, C> for SocketWorkReceiver<A, C>
 where
-    A: for<'de> Deserialize<'de> + Send,
-    C: for<'de> Deserialize<'de> + Send,
+    A: for<'de> Deserialize<'de> + BroadcastContext + Send + 'static,
+    C: for<'de> Deserialize<'de> + Send + 'static,
 {
     async fn recv(&mut self) -> Option<WorkerMessage<A, C>> {
-        if let Ok((mut stream, _)) = self.listener.accept().await {
-            let mut len_bytes = [0u8; 4];
-            if stream.read_exact(&mut len_bytes).await.is_ok() {
-                let len = u32::from_be_bytes(len_bytes) as usize;
-                let mut buffer = vec![0u8; len];
-                if stream.read_exact(&mut buffer).await.is_ok() {
-                    if let Ok(message) = serde_json::from_slice(&buffer) {
-                        return Some(message);
-                    }
-                }
+        if self.queue.is_none() {
+            let (tx, rx) = mpsc::channel(WORK_QUEUE_CAPACITY);
+            self.queue = Some(rx);
+            if let Some(listener) = self.listener.take() {
+                self.accepting = Some(tokio::spawn(accept_messages(listener, tx)));
             }
         }
-        None
+        self.queue.as_mut()?.recv().await
+    }
+
+    async fn shutdown(&mut self) {
+        // Dropping the listener closes the port, also when no message was ever received
+        self.listener = None;
+        if let Some(accepting) = self.accepting.take() {
+            accepting.abort();
+            let _ = accepting.await;
+        }
+        match self.queue.as_mut() {
+            Some(queue) => queue.close(),
+            None => self.queue = Some(mpsc::channel(1).1),
+        }
     }
 }
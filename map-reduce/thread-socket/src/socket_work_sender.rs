@@ -3,24 +3,40 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use crate::socket_work_receiver::SocketWorkReceiver;
+use map_reduce_core::job_context::{BroadcastContext, JobContextOf};
+use map_reduce_core::work_compression::compress_payload;
 use map_reduce_core::work_sender::WorkSender;
 use map_reduce_core::worker_message::WorkerMessage;
 use serde::Serialize;
-use std::io::Write;
+use std::io::{self, Read, Write};
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio::task;
+
+/// Length prefix and compression flag preceding every message
+const FRAME_HEADER_BYTES: u64 = 5;
 
 /// Socket-based work channel
 #[derive(Clone)]
 pub struct SocketWorkSender<A, C> {
     addr: Arc<String>,
+    /// Compress messages worth it, flagging them in their frame
+    compress: bool,
+    bytes_sent: Arc<AtomicU64>,
+    raw_bytes_sent: Arc<AtomicU64>,
+    /// Queue depth reported by the worker's last acknowledgement
+    queue_depth: Arc<AtomicUsize>,
+    /// Held while the initialization is written, so work sent next never overtakes it
+    initializing: Arc<Mutex<()>>,
     _phantom: PhantomData<(A, C)>,
 }
 
-impl<A, C> SocketWorkSender<A, C> {
-    pub fn create_pair(port: u16) -> (Self, SocketWorkReceiver<A, C>) {
+impl<A: BroadcastContext, C> SocketWorkSender<A, C> {
+    pub fn create_pair(port: u16, compress: bool) -> (Self, SocketWorkReceiver<A, C>) {
         let addr = format!("127.0.0.1:{}", port);
         let std_listener = std::net::TcpListener::bind(&addr).expect("Failed to bind");
         std_listener
@@ -34,46 +50,83 @@ impl<A, C> SocketWorkSender<A, C> {
 
         let channel = Self {
             addr: Arc::new(actual_addr.to_string()),
+            compress,
+            bytes_sent: Arc::new(AtomicU64::new(0)),
+            raw_bytes_sent: Arc::new(AtomicU64::new(0)),
+            queue_depth: Arc::new(AtomicUsize::new(0)),
+            initializing: Arc::new(Mutex::new(())),
             _phantom: PhantomData,
         };
         let receiver = SocketWorkReceiver {
-            listener: Arc::new(listener),
+            listener: Some(listener),
+            queue: None,
+            accepting: None,
             _phantom: PhantomData,
         };
         (channel, receiver)
     }
+
+    /// Connect to the worker and write one length-prefixed message, flagged if compressed
+    /// Waits for the worker to queue it, returning the queue depth it acknowledged
+    fn write_message<M: Serialize>(&self, message: &M) -> io::Result<usize> {
+        let mut stream = std::net::TcpStream::connect(self.addr.as_str())?;
+        let payload = compress_payload(serde_json::to_vec(message)?, self.compress);
+        let len = payload.bytes.len() as u32;
+        stream.write_all(&len.to_be_bytes())?;
+        stream.write_all(&[payload.compressed as u8])?;
+        stream.write_all(&payload.bytes)?;
+        self.bytes_sent
+            .fetch_add(FRAME_HEADER_BYTES + len as u64, Ordering::Relaxed);
+        self.raw_bytes_sent.fetch_add(
+            FRAME_HEADER_BYTES + payload.raw_len as u64,
+            Ordering::Relaxed,
+        );
+
+        let mut depth = [0u8; 4];
+        stream.read_exact(&mut depth)?;
+        Ok(u32::from_be_bytes(depth) as usize)
+    }
 }
 
 impl<A, C> WorkSender<A, C> for SocketWorkSender<A, C>
 where
-    A: Clone + Send + Serialize + 'static,
-    C: Clone + Send + Serialize + 'static,
+    A: Clone + Send + Sync + Serialize + BroadcastContext + 'static,
+    C: Clone + Send + Sync + Serialize + 'static,
 {
-    fn initialize(&self, token: C) {
-        let addr = self.addr.clone();
+    fn initialize(&self, token: C, context: JobContextOf<A>) {
+        let sender = self.clone();
+        let guard = self.initializing.clone().try_lock_owned().ok();
         thread::spawn(move || {
-            if let Ok(mut stream) = std::net::TcpStream::connect(addr.as_str()) {
-                let message = WorkerMessage::<A, C>::Initialize(token);
-                if let Ok(serialized) = serde_json::to_vec(&message) {
-                    let len = serialized.len() as u32;
-                    let _ = stream.write_all(&len.to_be_bytes());
-                    let _ = stream.write_all(&serialized);
-                }
-            }
+            let message = WorkerMessage::<A, C>::Initialize(token, context);
+            let _ = sender.write_message(&message);
+            drop(guard);
         });
     }
 
-    fn send_work(&self, assignment: A, completion: C) {
-        let addr = self.addr.clone();
-        thread::spawn(move || {
-            if let Ok(mut stream) = std::net::TcpStream::connect(addr.as_str()) {
-                let message = WorkerMessage::Work(assignment, completion);
-                if let Ok(serialized) = serde_json::to_vec(&message) {
-                    let len = serialized.len() as u32;
-                    let _ = stream.write_all(&len.to_be_bytes());
-                    let _ = stream.write_all(&serialized);
-                }
-            }
-        });
+    async fn send_work(&self, assignment: A, completion: C) -> Result<(), String> {
+        drop(self.initializing.lock().await);
+        let sender = self.clone();
+        let depth = task::spawn_blocking(move || {
+            let message = WorkerMessage::Work(assignment, completion);
+            sender
+                .write_message(&message)
+                .map_err(|e| format!("Failed to send work to {}: {}", sender.addr, e))
+        })
+        .await
+        .map_err(|e| format!("Work sender task failed: {}", e))??;
+        self.queue_depth.store(depth, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    fn raw_bytes_sent(&self) -> u64 {
+        self.raw_bytes_sent.load(Ordering::Relaxed)
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
     }
 }
@@ -3,35 +3,98 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use map_reduce_core::worker_runtime::{WorkerRuntime, WorkerTask};
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use tokio::sync::oneshot;
 
 /// Thread-based runtime
 #[derive(Clone, Copy)]
 pub struct ThreadRuntime;
 
+/// Worker thread, killed when its handle is dropped, e.g. when the executor replaces the worker
+/// The thread stops at the task's next await point and drops its runtime,
+/// closing the socket listener of its work channel
+pub struct WorkerThread {
+    thread: JoinHandle<()>,
+    /// How the thread ended, set once it did
+    exit_reason: Arc<Mutex<Option<String>>>,
+    /// Dropping the sender kills the thread
+    _kill: oneshot::Sender<()>,
+}
+
+impl WorkerThread {
+    /// How the thread ended, if it already did
+    fn exit_reason(&self) -> Option<String> {
+        if !self.thread.is_finished() {
+            return None;
+        }
+        self.exit_reason.lock().unwrap().clone()
+    }
+}
+
 impl<T> WorkerRuntime<T> for ThreadRuntime
 where
     T: WorkerTask<Output = ()> + Send + 'static,
 {
-    type Handle = JoinHandle<()>;
+    type Handle = WorkerThread;
     type Error = Box<dyn std::error::Error + Send + Sync>;
 
     fn spawn(task: T) -> Self::Handle {
+        let (kill, killed) = oneshot::channel::<()>();
+        let exit_reason = Arc::new(Mutex::new(None));
+        let thread_exit_reason = exit_reason.clone();
+
         // For thread-based runtime, we need to block on the future
-        thread::spawn(move || {
+        let thread = thread::spawn(move || {
             // Create a simple runtime for blocking on the future
             let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(task.run());
-        })
+            let outcome = catch_unwind(AssertUnwindSafe(|| {
+                rt.block_on(async {
+                    tokio::select! {
+                        _ = task.run() => "exited",
+                        _ = killed => "killed",
+                    }
+                })
+            }));
+            *thread_exit_reason.lock().unwrap() = Some(match outcome {
+                Ok(reason) => reason.to_string(),
+                Err(panic) => format!("panicked: {}", panic_message(panic.as_ref())),
+            });
+        });
+
+        WorkerThread {
+            thread,
+            exit_reason,
+            _kill: kill,
+        }
+    }
+
+    fn exit_reason(handle: &Self::Handle) -> Option<String> {
+        handle.exit_reason()
     }
 
     async fn join(handle: Self::Handle) -> Result<(), Self::Error> {
+        // The handle is moved along, so the thread is not killed while it is joined
         tokio::task::spawn_blocking(move || {
-            handle
+            let WorkerThread {
+                thread,
+                exit_reason,
+                _kill,
+            } = handle;
+            thread
                 .join()
                 .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> {
                     format!("Thread join error: {:?}", e).into()
-                })
+                })?;
+            let reason = exit_reason.lock().unwrap().take();
+            match reason {
+                Some(reason) if reason.starts_with("panicked") => {
+                    Err(format!("Worker thread {}", reason).into())
+                }
+                _ => Ok(()),
+            }
         })
         .await
         .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> {
@@ -39,3 +102,12 @@ where
         })?
     }
 }
+
+/// Message of a panic payload, for the usual `&str` and `String` payloads
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
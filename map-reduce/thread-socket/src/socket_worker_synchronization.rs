@@ -3,25 +3,97 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use crate::socket_status_sender::SocketStatusSender;
-use map_reduce_core::worker_synchronization::WorkerSynchronization;
+use map_reduce_core::counters::Counters;
+use map_reduce_core::reduce_output::KeyResult;
+use map_reduce_core::task_key::TaskKey;
+use map_reduce_core::worker_epochs::WorkerEpochs;
+use map_reduce_core::worker_synchronization::{WorkerStatus, WorkerSynchronization};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::io::AsyncReadExt;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::TcpListenerStream;
 use tokio_stream::{StreamExt, StreamMap};
+use tracing::warn;
 
-/// Completion message type
+/// Largest completion message accepted, well above the results a reduce task reports
+/// A longer length prefix drops the connection instead of allocating its buffer
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Pause after a failed accept, e.g. when out of file descriptors, before accepting again
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Completion message, tagged with the epoch of the worker that sent it and the task it ran
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CompletionMessage {
-    Success(usize),
-    Failure(usize),
+    Success {
+        worker_id: usize,
+        epoch: u64,
+        task_id: Option<usize>,
+        /// Counters the task incremented
+        counters: Counters,
+        /// Results the task emitted, in the job's result order
+        #[serde(default)]
+        results: Vec<KeyResult>,
+    },
+    Failure {
+        worker_id: usize,
+        epoch: u64,
+        task_id: Option<usize>,
+    },
 }
 
 /// Socket-based completion signaling
+/// Completions are accepted and read in the background, each connection on its own task,
+/// so a slow sender holds up no other and `wait_next` only takes what has already arrived
 pub struct SocketWorkerSynchronization {
-    listeners: StreamMap<usize, TcpListenerStream>,
+    messages: UnboundedReceiver<(usize, CompletionMessage)>,
+    accepting: JoinHandle<()>,
     ports: HashMap<usize, u16>,
+    epochs: WorkerEpochs,
+}
+
+/// Read one length-prefixed completion message
+async fn read_message(stream: &mut TcpStream) -> Option<CompletionMessage> {
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).await.ok()?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_MESSAGE_BYTES {
+        warn!(len, "Dropping oversized completion message");
+        return None;
+    }
+    let mut buffer = vec![0u8; len];
+    stream.read_exact(&mut buffer).await.ok()?;
+    // Malformed messages are dropped
+    serde_json::from_slice(&buffer).ok()
+}
+
+/// Accept connections on every worker's listener, handing each message on with the worker slot
+/// whose listener received it, which is the worker the message is accounted to
+async fn accept_messages(
+    mut listeners: StreamMap<usize, TcpListenerStream>,
+    messages: UnboundedSender<(usize, CompletionMessage)>,
+) {
+    while let Some((slot, connection_result)) = listeners.next().await {
+        // Errors accepting a connection leave the listener usable
+        let mut stream = match connection_result {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!(slot, error = %e, "Failed to accept a completion connection");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let messages = messages.clone();
+        tokio::spawn(async move {
+            if let Some(message) = read_message(&mut stream).await {
+                let _ = messages.send((slot, message));
+            }
+        });
+    }
 }
 
 impl SocketWorkerSynchronization {
@@ -49,7 +121,13 @@ impl SocketWorkerSynchronization {
             ports.insert(i, actual_port);
         }
 
-        Self { listeners, ports }
+        let (tx, messages) = mpsc::unbounded_channel();
+        Self {
+            messages,
+            accepting: tokio::spawn(accept_messages(listeners, tx)),
+            ports,
+            epochs: WorkerEpochs::new(num_workers),
+        }
     }
 
     pub fn get_sender(&self, worker_id: usize) -> SocketStatusSender {
@@ -58,7 +136,12 @@ impl SocketWorkerSynchronization {
             .get(&worker_id)
             .copied()
             .expect("Invalid worker_id");
-        SocketStatusSender { port, worker_id }
+        SocketStatusSender {
+            port,
+            worker_id,
+            epoch: self.epochs.current(worker_id),
+            task: None,
+        }
     }
 }
 
@@ -69,64 +152,76 @@ impl WorkerSynchronization for SocketWorkerSynchronization {
         Self::new(num_workers)
     }
 
+    fn switch_phase(&mut self) {
+        self.epochs.advance_all();
+    }
+
     fn get_status_sender(&self, worker_id: usize) -> Self::StatusSender {
         self.get_sender(worker_id)
     }
 
+    fn get_task_status_sender(&self, worker_id: usize, task: TaskKey) -> Self::StatusSender {
+        SocketStatusSender {
+            task: Some(task),
+            ..self.get_sender(worker_id)
+        }
+    }
+
     async fn wait_for_worker_ready(&self, _worker_id: usize) -> bool {
         true
     }
 
     async fn reset_worker(&mut self, worker_id: usize) -> Self::StatusSender {
-        // Remove old listener (closes socket)
-        // This implicitly drains any pending connections because the listener is dropped
-        self.listeners.remove(&worker_id);
-
-        // Create new listener
-        let std_listener =
-            std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind completion listener");
-        std_listener
-            .set_nonblocking(true)
-            .expect("Failed to set nonblocking");
-        let actual_port = std_listener
-            .local_addr()
-            .expect("Failed to get local address")
-            .port();
-
-        let tokio_listener =
-            TcpListener::from_std(std_listener).expect("Failed to convert to tokio listener");
-        let stream = TcpListenerStream::new(tokio_listener);
-
-        // Insert new listener and update port
-        self.listeners.insert(worker_id, stream);
-        self.ports.insert(worker_id, actual_port);
-
+        self.epochs.advance(worker_id);
         self.get_sender(worker_id)
     }
 
-    async fn wait_next(&mut self) -> Option<Result<usize, usize>> {
-        while let Some((_worker_id, connection_result)) = self.listeners.next().await {
-            match connection_result {
-                Ok(mut stream) => {
-                    let mut len_bytes = [0u8; 4];
-                    if stream.read_exact(&mut len_bytes).await.is_ok() {
-                        let len = u32::from_be_bytes(len_bytes) as usize;
-                        let mut buffer = vec![0u8; len];
-                        if stream.read_exact(&mut buffer).await.is_ok() {
-                            if let Ok(msg) = serde_json::from_slice::<CompletionMessage>(&buffer) {
-                                return Some(match msg {
-                                    CompletionMessage::Success(id) => Ok(id),
-                                    CompletionMessage::Failure(id) => Err(id),
-                                });
-                            }
-                        }
-                    }
+    async fn wait_next(&mut self) -> Option<WorkerStatus> {
+        // Cancel safe: a message is only taken off the channel once it was read in full
+        // The worker is that of the listener slot the message came in on, never the id it
+        // claims, so a bogus id cannot point outside the worker set
+        while let Some((slot, message)) = self.messages.recv().await {
+            match message {
+                CompletionMessage::Success {
+                    epoch,
+                    task_id,
+                    counters,
+                    results,
+                    ..
+                } if self.epochs.is_current(slot, epoch) => {
+                    return Some(WorkerStatus::Completed {
+                        worker_id: slot,
+                        task_id,
+                        counters,
+                        results,
+                    })
+                }
+                CompletionMessage::Failure { epoch, task_id, .. }
+                    if self.epochs.is_current(slot, epoch) =>
+                {
+                    return Some(WorkerStatus::Failed {
+                        worker_id: slot,
+                        task_id,
+                    })
                 }
-                Err(_) => {
-                    // Error occurred accepting connection
+                // Late completion of a replaced worker
+                CompletionMessage::Success { task_id, .. } => {
+                    return Some(WorkerStatus::Superseded {
+                        worker_id: slot,
+                        task_id,
+                    })
                 }
+                // Late failure of a replaced worker
+                CompletionMessage::Failure { .. } => {}
             }
         }
         None
     }
 }
+
+impl Drop for SocketWorkerSynchronization {
+    fn drop(&mut self) {
+        // Closes the listeners
+        self.accepting.abort();
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2025 Umberto Gotti <umberto.gotti@umbertogotti.dev>
+// Licensed under the Apache License, Version 2.0
+// http://www.apache.org/licenses/LICENSE-2.0
+
+use async_trait::async_trait;
+use map_reduce_core::config::Config;
+use map_reduce_core::counters::{TaskCounters, KEYS_REDUCED};
+use map_reduce_core::data_chunk::DataChunk;
+use map_reduce_core::input_source::InputSplit;
+use map_reduce_core::job_context::{BroadcastContext, PhaseContext};
+use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::reduce_output::ReduceOutput;
+use map_reduce_core::result_value::ResultValue;
+use map_reduce_core::scheduling_policy::TaskSize;
+use map_reduce_core::state_store::StateStore;
+use map_reduce_core::utils::read_split_records;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+pub struct WordCountProblem;
+
+/// Side input listing words left out of the counts, one per line
+pub const STOP_WORDS: &str = "stop_words";
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct MapWorkAssignment {
+    pub chunk_id: usize,
+    pub data: DataChunk,
+    #[serde(default)]
+    pub split: Option<InputSplit>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReduceWorkAssignment {
+    pub keys: Vec<String>,
+}
+
+/// Word count needs no context, every word of the input is a key
+#[derive(Clone)]
+pub struct WordCountContext;
+
+impl TaskSize for MapWorkAssignment {
+    fn task_size(&self) -> u64 {
+        match &self.split {
+            Some(split) => split.length,
+            None => self.data.len() as u64,
+        }
+    }
+}
+
+impl TaskSize for ReduceWorkAssignment {
+    fn task_size(&self) -> u64 {
+        self.keys.len() as u64
+    }
+}
+
+impl BroadcastContext for MapWorkAssignment {
+    type JobContext = ();
+}
+
+impl BroadcastContext for ReduceWorkAssignment {
+    type JobContext = ();
+}
+
+#[async_trait]
+impl MapReduceJob for WordCountProblem {
+    type Input = Arc<[String]>;
+    type MapAssignment = MapWorkAssignment;
+    type ReduceAssignment = ReduceWorkAssignment;
+    type Context = WordCountContext;
+
+    const NAME: &'static str = "word-count";
+
+    fn create_context(
+        _config: &Config,
+        _data: &Self::Input,
+        _targets: Vec<String>,
+    ) -> Self::Context {
+        WordCountContext
+    }
+
+    fn create_map_assignments(
+        data: Self::Input,
+        _context: Self::Context,
+        chunks: Vec<Range<usize>>,
+    ) -> Vec<Self::MapAssignment> {
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, range)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::new(data.clone(), range),
+                split: None,
+            })
+            .collect()
+    }
+
+    fn create_split_assignments(
+        splits: Vec<InputSplit>,
+        _context: Self::Context,
+    ) -> Vec<Self::MapAssignment> {
+        splits
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, split)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::default(),
+                split: Some(split),
+            })
+            .collect()
+    }
+
+    fn create_reduce_assignments(
+        _context: Self::Context,
+        partitions: Vec<Vec<String>>,
+    ) -> Vec<Self::ReduceAssignment> {
+        partitions
+            .into_iter()
+            .map(|keys| ReduceWorkAssignment { keys })
+            .collect()
+    }
+
+    async fn map_work<S>(
+        assignment: &Self::MapAssignment,
+        context: &PhaseContext<()>,
+        state: &S,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        let stop_words = context.side_inputs.set(STOP_WORDS);
+        let counts = match &assignment.split {
+            Some(split) => {
+                match read_split_records(split, counters, |lines| count_words(lines, &stop_words)) {
+                    Ok(counts) => counts,
+                    Err(e) => {
+                        eprintln!("Failed to read split {:?}: {}", split, e);
+                        return;
+                    }
+                }
+            }
+            None => {
+                counters.records_read(&assignment.data);
+                count_words(assignment.data.iter(), &stop_words)
+            }
+        };
+
+        // Write the chunk's counts to shared state
+        state.update_many(counts.into_iter().collect()).await;
+    }
+
+    async fn reduce_work<S>(
+        assignment: &Self::ReduceAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        output: &ReduceOutput,
+        counters: &TaskCounters,
+    ) where
+        S: StateStore,
+    {
+        counters.increment(KEYS_REDUCED, assignment.keys.len() as u64);
+        // Sums fold each key's values as the state streams them, e.g. run by run once spilled
+        let sums = state
+            .merge_reduce(&assignment.keys, Self::partial_aggregate)
+            .await;
+        for (key, sum) in assignment.keys.iter().zip(sums) {
+            state.replace(key.clone(), vec![sum]).await;
+            output.emit(key.clone(), ResultValue::Scalar(sum));
+        }
+    }
+}
+
+/// Words of a line: runs of alphanumeric characters, lowercased
+/// so "The", "the" and "THE," are counted as the same word
+pub fn tokenize(line: &str) -> impl Iterator<Item = String> + '_ {
+    line.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn count_words(
+    lines: impl Iterator<Item = impl AsRef<str>>,
+    stop_words: &HashSet<&str>,
+) -> HashMap<String, i32> {
+    let mut counts = HashMap::new();
+    for line in lines {
+        for word in tokenize(line.as_ref()) {
+            if stop_words.contains(word.as_str()) {
+                continue;
+            }
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+    counts
+}
@@ -3,20 +3,32 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 
 use async_trait::async_trait;
+use map_reduce_core::config::Config;
+use map_reduce_core::counters::{TaskCounters, KEYS_REDUCED};
+use map_reduce_core::data_chunk::DataChunk;
+use map_reduce_core::input_source::InputSplit;
+use map_reduce_core::job_context::{BroadcastContext, PhaseContext};
 use map_reduce_core::map_reduce_job::MapReduceJob;
+use map_reduce_core::reduce_output::ReduceOutput;
+use map_reduce_core::result_value::ResultValue;
+use map_reduce_core::scheduling_policy::TaskSize;
 use map_reduce_core::state_store::StateStore;
-use std::cmp::min;
+use map_reduce_core::utils::read_split_records;
 use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
 
 /// Word search problem definition - searches for target words in text data
 pub struct WordSearchProblem;
 
-/// Map assignment: chunk of data with target words to search for
+/// Map assignment: chunk of data to search, for the target words of its job context
+/// When `split` is set, the chunk is read from the input file instead of `data`
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct MapWorkAssignment {
     pub chunk_id: usize,
-    pub data: Vec<String>,
-    pub targets: Vec<String>,
+    pub data: DataChunk,
+    #[serde(default)]
+    pub split: Option<InputSplit>,
 }
 
 /// Reduce assignment: keys to aggregate
@@ -26,102 +38,170 @@ pub struct ReduceWorkAssignment {
 }
 
 /// Problem context: list of target words to search for
-#[derive(Clone)]
+/// Broadcast to the mappers as their job context, rather than copied into every assignment
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct WordSearchContext {
     pub targets: Vec<String>,
 }
 
+impl TaskSize for MapWorkAssignment {
+    fn task_size(&self) -> u64 {
+        match &self.split {
+            Some(split) => split.length,
+            None => self.data.len() as u64,
+        }
+    }
+}
+
+impl TaskSize for ReduceWorkAssignment {
+    fn task_size(&self) -> u64 {
+        self.keys.len() as u64
+    }
+}
+
+impl BroadcastContext for MapWorkAssignment {
+    type JobContext = WordSearchContext;
+}
+
+impl BroadcastContext for ReduceWorkAssignment {
+    type JobContext = ();
+}
+
 #[async_trait]
 impl MapReduceJob for WordSearchProblem {
-    type Input = Vec<String>;
+    type Input = Arc<[String]>;
     type MapAssignment = MapWorkAssignment;
     type ReduceAssignment = ReduceWorkAssignment;
     type Context = WordSearchContext;
 
+    const NAME: &'static str = "word-search";
+
+    fn create_context(
+        _config: &Config,
+        _data: &Self::Input,
+        targets: Vec<String>,
+    ) -> Self::Context {
+        WordSearchContext { targets }
+    }
+
+    fn initial_keys(context: &Self::Context) -> Vec<String> {
+        context.targets.clone()
+    }
+
+    fn map_context(context: &Self::Context) -> WordSearchContext {
+        context.clone()
+    }
+
     fn create_map_assignments(
         data: Self::Input,
-        context: Self::Context,
-        partition_size: usize,
+        _context: Self::Context,
+        chunks: Vec<Range<usize>>,
     ) -> Vec<Self::MapAssignment> {
-        let num_partitions = data.len().div_ceil(partition_size);
-        let mut data_chunks = Vec::new();
-
-        for i in 0..num_partitions {
-            let start = i * partition_size;
-            let end = min(start + partition_size, data.len());
-            data_chunks.push(data[start..end].to_vec());
-        }
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_id, range)| MapWorkAssignment {
+                chunk_id,
+                data: DataChunk::new(data.clone(), range),
+                split: None,
+            })
+            .collect()
+    }
 
-        data_chunks
+    fn create_split_assignments(
+        splits: Vec<InputSplit>,
+        _context: Self::Context,
+    ) -> Vec<Self::MapAssignment> {
+        splits
             .into_iter()
             .enumerate()
-            .map(|(chunk_id, data)| MapWorkAssignment {
+            .map(|(chunk_id, split)| MapWorkAssignment {
                 chunk_id,
-                data,
-                targets: context.targets.clone(),
+                data: DataChunk::default(),
+                split: Some(split),
             })
             .collect()
     }
 
     fn create_reduce_assignments(
-        context: Self::Context,
-        keys_per_reducer: usize,
+        _context: Self::Context,
+        partitions: Vec<Vec<String>>,
     ) -> Vec<Self::ReduceAssignment> {
-        let targets = context.targets;
-        let num_key_partitions = targets.len().div_ceil(keys_per_reducer);
-        let mut key_partitions = Vec::new();
-
-        for partition_id in 0..num_key_partitions {
-            let start = partition_id * keys_per_reducer;
-            let end = min(start + keys_per_reducer, targets.len());
-            key_partitions.push(targets[start..end].to_vec());
-        }
-
-        key_partitions
+        partitions
             .into_iter()
             .map(|keys| ReduceWorkAssignment { keys })
             .collect()
     }
 
-    async fn map_work<S>(assignment: &Self::MapAssignment, state: &S)
-    where
+    async fn map_work<S>(
+        assignment: &Self::MapAssignment,
+        context: &PhaseContext<WordSearchContext>,
+        state: &S,
+        counters: &TaskCounters,
+    ) where
         S: StateStore,
     {
-        let results = map_logic(&assignment.data, &assignment.targets);
+        let results = match &assignment.split {
+            Some(split) => match read_split_records(split, counters, |lines| {
+                map_logic(lines, &context.targets)
+            }) {
+                Ok(results) => results,
+                Err(e) => {
+                    eprintln!("Failed to read split {:?}: {}", split, e);
+                    return;
+                }
+            },
+            None => {
+                counters.records_read(&assignment.data);
+                map_logic(assignment.data.iter(), &context.targets)
+            }
+        };
 
         // Write results to shared state
-        for (key, value) in results {
-            if value > 0 {
-                state.update(key, value).await;
-            }
-        }
+        let updates = results
+            .into_iter()
+            .filter(|(_, value)| *value > 0)
+            .collect();
+        state.update_many(updates).await;
     }
 
-    async fn reduce_work<S>(assignment: &Self::ReduceAssignment, state: &S)
-    where
+    async fn reduce_work<S>(
+        assignment: &Self::ReduceAssignment,
+        _context: &PhaseContext<()>,
+        state: &S,
+        output: &ReduceOutput,
+        counters: &TaskCounters,
+    ) where
         S: StateStore,
     {
-        for key in &assignment.keys {
-            let values = state.get(key).await;
-            let sum: i32 = values.iter().sum();
-            state.replace(key.clone(), sum).await;
+        counters.increment(KEYS_REDUCED, assignment.keys.len() as u64);
+        // Sums fold each key's values as the state streams them, e.g. run by run once spilled
+        let sums = state
+            .merge_reduce(&assignment.keys, Self::partial_aggregate)
+            .await;
+        for (key, sum) in assignment.keys.iter().zip(sums) {
+            state.replace(key.clone(), vec![sum]).await;
+            output.emit(key.clone(), ResultValue::Scalar(sum));
         }
     }
 }
 
 /// Pure business logic for mapping phase
 /// Searches for target words in data and returns counts
-fn map_logic(data: &[String], targets: &[String]) -> HashMap<String, i32> {
-    let mut results = HashMap::new();
-
-    for target in targets {
-        let mut count = 0;
-        for text in data {
-            if text.contains(target) {
-                count += 1;
+/// The data is read once, so it can be streamed
+fn map_logic(
+    data: impl Iterator<Item = impl AsRef<str>>,
+    targets: &[String],
+) -> HashMap<String, i32> {
+    let mut results: HashMap<String, i32> =
+        targets.iter().map(|target| (target.clone(), 0)).collect();
+
+    for text in data {
+        for target in targets {
+            if text.as_ref().contains(target.as_str()) {
+                *results.get_mut(target).unwrap() += 1;
             }
         }
-        results.insert(target.clone(), count);
     }
 
     results